@@ -1,23 +1,101 @@
 #![no_std]
 #![no_main]
 
-use core::{
-    mem::{self, ManuallyDrop},
-    ptr,
-};
+use core::{fmt, mem, time::Duration};
+
+use rand::Rng;
+
+const RESTING_BPM: u16 = 70;
+const MIN_BPM: u16 = 30;
+const MAX_BPM: u16 = 220;
+
+/// The beats per minute stress adds.
+const STRESS_BPM: u16 = 40;
+
+/// The chance per second a heart stops on its own.
+const CRASH_RATE: f64 = 1e-5;
+/// The chance per second a fibrillating heart stops.
+const ARREST_RATE: f64 = 1e-1;
+/// The chance per second a collapsed lung reinflates.
+const RECOVERY_RATE: f64 = 1e-2;
+
+/// The chance of something happening at `rate` per second within `secs`.
+#[inline]
+fn chance(rate: f64, secs: f64) -> f64 {
+    (rate * secs).clamp(0.0, 1.0)
+}
+
+/// A part of a [`Body`] that keeps it alive.
+pub trait Organ {
+    fn name(&self) -> &'static str;
+
+    fn is_functioning(&self) -> bool;
+
+    /// Whether the body dies once this organ stops functioning.
+    #[inline]
+    fn is_critical(&self) -> bool {
+        true
+    }
+
+    fn fail(&mut self);
+
+    fn revive(&mut self) -> bool;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum HeartState {
+    Beating {
+        bpm: u16,
+    },
+    /// Quivering instead of pumping, it stops unless stabilized.
+    Fibrillating,
+    #[default]
+    Stopped,
+}
+
+impl HeartState {
+    pub const RESTING: Self = Self::Beating { bpm: RESTING_BPM };
+
+    /// The beats per minute, `None` unless beating.
+    #[inline]
+    pub const fn bpm(&self) -> Option<u16> {
+        match *self {
+            Self::Beating { bpm } => Some(bpm),
+            _ => None,
+        }
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct Heart {
-    state: bool,
+    state: HeartState,
 }
 
 impl Heart {
     pub const fn alive() -> Self {
-        Self { state: true }
+        Self {
+            state: HeartState::RESTING,
+        }
     }
 
     pub const fn dead() -> Self {
-        Self { state: false }
+        Self {
+            state: HeartState::Stopped,
+        }
+    }
+
+    #[inline]
+    pub const fn state(&self) -> HeartState {
+        self.state
+    }
+
+    /// The beats per minute, `0` unless beating.
+    #[inline]
+    pub const fn bpm(&self) -> u16 {
+        match self.state.bpm() {
+            Some(bpm) => bpm,
+            None => 0,
+        }
     }
 
     #[inline]
@@ -27,30 +105,360 @@ impl Heart {
 
     #[inline]
     pub const fn is_dead(&self) -> bool {
-        self.state == false
+        matches!(self.state, HeartState::Stopped)
     }
 
+    /// Restart a stopped heart at its resting rate.
     #[inline]
     #[cold]
     pub fn revive(&mut self) -> bool {
-        if !self.state {
-            self.state = true;
+        if self.is_dead() {
+            self.state = HeartState::RESTING;
+        }
+        self.is_alive()
+    }
+
+    /// Speed the heart up, sending it into fibrillation past its highest rate.
+    pub fn stress(&mut self) -> HeartState {
+        if let HeartState::Beating { bpm } = self.state {
+            self.state = match bpm.saturating_add(STRESS_BPM) {
+                bpm if bpm > MAX_BPM => HeartState::Fibrillating,
+                bpm => HeartState::Beating { bpm },
+            };
         }
         self.state
     }
+
+    /// Bring a beating or fibrillating heart back to its resting rate, a stopped one has to
+    /// be revived.
+    pub fn stabilize(&mut self) -> HeartState {
+        if self.is_alive() {
+            self.state = HeartState::RESTING;
+        }
+        self.state
+    }
+
+    /// Let the rate wander over `dt`, pulled back towards resting. A fibrillating heart may
+    /// stop.
+    fn drift<R: Rng + ?Sized>(&mut self, dt: Duration, rng: &mut R) {
+        match self.state {
+            HeartState::Beating { bpm } => {
+                let spread = 1 + dt.as_secs().min(60) as i32;
+                let pull = (RESTING_BPM as i32 - bpm as i32) / 10;
+                let bpm = bpm as i32 + pull + rng.gen_range(-spread..=spread);
+                self.state = HeartState::Beating {
+                    bpm: bpm.clamp(MIN_BPM as i32, MAX_BPM as i32) as u16,
+                };
+            }
+            HeartState::Fibrillating => {
+                if rng.gen_bool(chance(ARREST_RATE, dt.as_secs_f64())) {
+                    self.state = HeartState::Stopped;
+                }
+            }
+            HeartState::Stopped => {}
+        }
+    }
+}
+
+impl Organ for Heart {
+    #[inline]
+    fn name(&self) -> &'static str {
+        "heart"
+    }
+
+    #[inline]
+    fn is_functioning(&self) -> bool {
+        self.is_alive()
+    }
+
+    #[inline]
+    fn fail(&mut self) {
+        *self = Self::dead();
+    }
+
+    #[inline]
+    fn revive(&mut self) -> bool {
+        Heart::revive(self)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Brain {
+    active: bool,
+}
+
+impl Brain {
+    pub const fn active() -> Self {
+        Self { active: true }
+    }
+
+    pub const fn dead() -> Self {
+        Self { active: false }
+    }
+
+    #[inline]
+    pub const fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+impl Organ for Brain {
+    #[inline]
+    fn name(&self) -> &'static str {
+        "brain"
+    }
+
+    #[inline]
+    fn is_functioning(&self) -> bool {
+        self.active
+    }
+
+    #[inline]
+    fn fail(&mut self) {
+        self.active = false;
+    }
+
+    #[inline]
+    fn revive(&mut self) -> bool {
+        self.active = true;
+        self.active
+    }
+}
+
+/// A pair of lungs, breathing as long as either of them works.
+#[derive(Debug, Default)]
+pub struct Lungs {
+    left: bool,
+    right: bool,
+}
+
+impl Lungs {
+    pub const fn healthy() -> Self {
+        Self {
+            left: true,
+            right: true,
+        }
+    }
+
+    pub const fn collapsed() -> Self {
+        Self {
+            left: false,
+            right: false,
+        }
+    }
+
+    /// The number of working lungs.
+    #[inline]
+    pub const fn capacity(&self) -> u8 {
+        self.left as u8 + self.right as u8
+    }
+
+    #[inline]
+    pub fn collapse_left(&mut self) {
+        self.left = false;
+    }
+
+    #[inline]
+    pub fn collapse_right(&mut self) {
+        self.right = false;
+    }
+}
+
+impl Organ for Lungs {
+    #[inline]
+    fn name(&self) -> &'static str {
+        "lungs"
+    }
+
+    #[inline]
+    fn is_functioning(&self) -> bool {
+        self.capacity() > 0
+    }
+
+    #[inline]
+    fn fail(&mut self) {
+        *self = Self::collapsed();
+    }
+
+    #[inline]
+    fn revive(&mut self) -> bool {
+        *self = Self::healthy();
+        true
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum BloodType {
+    #[default]
+    O,
+    A,
+    B,
+    AB,
+}
+
+impl BloodType {
+    /// Whether the organs of this blood type can be given to a recipient of `other`.
+    #[inline]
+    pub const fn can_donate_to(&self, other: BloodType) -> bool {
+        matches!(
+            (*self, other),
+            (Self::O, _)
+                | (Self::A, Self::A | Self::AB)
+                | (Self::B, Self::B | Self::AB)
+                | (Self::AB, Self::AB)
+        )
+    }
+}
+
+/// The organs of a [`Person`], alive as long as every critical one functions.
+#[derive(Debug, Default)]
+pub struct Body {
+    heart: Heart,
+    brain: Brain,
+    lungs: Lungs,
+    blood: BloodType,
+}
+
+impl Body {
+    #[inline]
+    pub const fn new(heart: Heart, brain: Brain, lungs: Lungs) -> Self {
+        Self {
+            heart,
+            brain,
+            lungs,
+            blood: BloodType::O,
+        }
+    }
+
+    #[inline]
+    pub const fn with_blood(mut self, blood: BloodType) -> Self {
+        self.blood = blood;
+        self
+    }
+
+    #[inline]
+    pub const fn blood(&self) -> BloodType {
+        self.blood
+    }
+
+    /// A healthy body around a heart.
+    #[inline]
+    pub const fn with_heart(heart: Heart) -> Self {
+        Self::new(heart, Brain::active(), Lungs::healthy())
+    }
+
+    #[inline]
+    pub const fn heart(&self) -> &Heart {
+        &self.heart
+    }
+
+    #[inline]
+    pub fn heart_mut(&mut self) -> &mut Heart {
+        &mut self.heart
+    }
+
+    #[inline]
+    pub const fn brain(&self) -> &Brain {
+        &self.brain
+    }
+
+    #[inline]
+    pub fn brain_mut(&mut self) -> &mut Brain {
+        &mut self.brain
+    }
+
+    #[inline]
+    pub const fn lungs(&self) -> &Lungs {
+        &self.lungs
+    }
+
+    #[inline]
+    pub fn lungs_mut(&mut self) -> &mut Lungs {
+        &mut self.lungs
+    }
+
+    #[inline]
+    pub fn organs(&self) -> [&dyn Organ; 3] {
+        [&self.heart, &self.brain, &self.lungs]
+    }
+
+    #[inline]
+    pub fn organs_mut(&mut self) -> [&mut dyn Organ; 3] {
+        [&mut self.heart, &mut self.brain, &mut self.lungs]
+    }
+
+    /// The critical organs that stopped functioning.
+    pub fn failing(&self) -> impl Iterator<Item = &dyn Organ> {
+        self.organs()
+            .into_iter()
+            .filter(|organ| organ.is_critical() && !organ.is_functioning())
+    }
+
+    #[inline]
+    pub fn is_alive(&self) -> bool {
+        self.failing().next().is_none()
+    }
+}
+
+/// Why [`Person::transfer`] failed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TransferError {
+    /// The donor's heart stopped already.
+    DonorDead,
+    /// The recipient's heart still beats.
+    RecipientAlive,
+    /// The donor's blood type can't be given to the recipient.
+    Incompatible {
+        donor: BloodType,
+        recipient: BloodType,
+    },
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DonorDead => write!(f, "DonorDead"),
+            Self::RecipientAlive => write!(f, "RecipientAlive"),
+            Self::Incompatible { donor, recipient } => write!(
+                f,
+                "Incompatible(donor: {:?}, recipient: {:?})",
+                donor, recipient
+            ),
+        }
+    }
+}
+
+impl core::error::Error for TransferError {}
+
+/// How a [`Person`] died.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Death {
+    cause: &'static str,
+}
+
+impl Death {
+    /// The name of the critical organ found failing first.
+    #[inline]
+    pub const fn cause(&self) -> &'static str {
+        self.cause
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct Person {
-    heart: ManuallyDrop<Heart>,
+    body: Body,
+    death: Option<Death>,
 }
 
 impl Person {
     #[inline]
     pub const fn new(heart: Heart) -> Self {
-        Self {
-            heart: ManuallyDrop::new(heart),
-        }
+        Self::with_body(Body::with_heart(heart))
+    }
+
+    #[inline]
+    pub const fn with_body(body: Body) -> Self {
+        Self { body, death: None }
     }
 
     #[inline]
@@ -64,40 +472,169 @@ impl Person {
     }
 
     #[inline]
-    pub fn crash(&mut self) -> bool {
-        let state = mem::replace(&mut self.heart.state, rand::random());
+    pub fn body(&self) -> &Body {
+        &self.body
+    }
 
-        if self.heart.is_dead() {
-            let death = {
-                let gone = mem::take(self);
-                let state = gone.heart.state;
-                mem::forget(gone);
-                state
-            };
-            return death;
+    #[inline]
+    pub fn body_mut(&mut self) -> &mut Body {
+        &mut self.body
+    }
+
+    #[inline]
+    pub fn is_alive(&self) -> bool {
+        self.body.is_alive()
+    }
+
+    /// How this person died, `None` while alive.
+    pub fn death(&self) -> Option<Death> {
+        self.death.or_else(|| {
+            self.body.failing().next().map(|organ| Death {
+                cause: organ.name(),
+            })
+        })
+    }
+
+    /// Record the outcome of a transition, returning the death if it was fatal.
+    fn record(&mut self) -> Option<Death> {
+        match self.body.failing().next().map(|organ| organ.name()) {
+            None => {
+                self.death = None;
+                None
+            }
+            Some(_) if self.death.is_some() => None,
+            Some(cause) => {
+                self.death = Some(Death { cause });
+                self.death
+            }
+        }
+    }
+
+    /// Evolve over `dt`, returning the death if this step was fatal.
+    ///
+    /// The heart rate drifts, the heart may stop on its own or after fibrillating, and a
+    /// collapsed lung may reinflate. The dead stay dead.
+    pub fn step<R: Rng + ?Sized>(&mut self, dt: Duration, rng: &mut R) -> Option<Death> {
+        if !self.is_alive() {
+            return None;
+        }
+        let secs = dt.as_secs_f64();
+        let body = self.body_mut();
+
+        body.heart.drift(dt, rng);
+        if rng.gen_bool(chance(CRASH_RATE, secs)) {
+            body.heart.fail();
         }
-        state
+        if body.lungs.capacity() == 1 && rng.gen_bool(chance(RECOVERY_RATE, secs)) {
+            let _ = body.lungs.revive();
+        }
+        self.record()
     }
 
+    /// Stop the heart at even odds, returning the death if it did.
     #[inline]
-    pub fn transfer(mut self, mut other: Person) -> Result<Person, ()> {
-        if self.heart.is_dead() {
-            log::error!("you're already dead.");
-            return Err(());
+    pub fn crash(&mut self) -> Option<Death> {
+        if self.is_alive() && rand::random() {
+            self.body.heart.fail();
         }
+        self.record()
+    }
 
-        if other.heart.is_alive() {
-            log::error!("other already alive.");
-            return Err(());
+    /// Give the heart of this person to a dead `other`, bringing them back. The donor dies.
+    ///
+    /// On error both are dropped, neither's heart is touched.
+    #[inline]
+    pub fn transfer(mut self, mut other: Person) -> Result<Person, TransferError> {
+        if self.body.heart.is_dead() {
+            return Err(TransferError::DonorDead);
         }
-        unsafe { ptr::swap_nonoverlapping(&mut self.heart, &mut other.heart, 1) }
-        // Drop self.
-        let _ = self.kill();
+        if other.body.heart.is_alive() {
+            return Err(TransferError::RecipientAlive);
+        }
+        if !self.body.blood.can_donate_to(other.body.blood) {
+            return Err(TransferError::Incompatible {
+                donor: self.body.blood,
+                recipient: other.body.blood,
+            });
+        }
+        mem::swap(&mut self.body.heart, &mut other.body.heart);
+        let _ = other.record();
+        // The donor is left with the dead heart, dropping records it.
         Ok(other)
     }
 
+    /// Stop the heart, returning how this person died.
+    #[inline]
+    pub fn kill(mut self) -> Death {
+        self.body.heart.fail();
+        let _ = self.record();
+        self.death.unwrap_or(Death {
+            cause: self.body.heart.name(),
+        })
+    }
+}
+
+impl Drop for Person {
+    fn drop(&mut self) {
+        let _ = self.record();
+        match self.death {
+            Some(death) => log::info!("died of a failing {}.", death.cause()),
+            None => log::info!("left alive."),
+        }
+    }
+}
+
+/// People living side by side, evolved a fixed step at a time.
+#[derive(Debug)]
+pub struct World<R, const N: usize> {
+    people: [Person; N],
+    rng: R,
+    dt: Duration,
+    elapsed: Duration,
+}
+
+impl<R: Rng, const N: usize> World<R, N> {
+    #[inline]
+    pub const fn new(people: [Person; N], rng: R, dt: Duration) -> Self {
+        Self {
+            people,
+            rng,
+            dt,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Step everyone forward, returning how many are still alive.
+    pub fn tick(&mut self) -> usize {
+        let dt = self.dt;
+        for person in &mut self.people {
+            let _ = person.step(dt, &mut self.rng);
+        }
+        self.elapsed += dt;
+        self.alive()
+    }
+
+    #[inline]
+    pub fn alive(&self) -> usize {
+        self.people
+            .iter()
+            .filter(|person| person.is_alive())
+            .count()
+    }
+
+    #[inline]
+    pub fn people(&self) -> &[Person] {
+        &self.people
+    }
+
+    #[inline]
+    pub fn people_mut(&mut self) -> &mut [Person] {
+        &mut self.people
+    }
+
+    /// The time simulated so far.
     #[inline]
-    pub const fn kill(self) -> Heart {
-        ManuallyDrop::into_inner(self.heart)
+    pub const fn elapsed(&self) -> Duration {
+        self.elapsed
     }
 }
@@ -0,0 +1,96 @@
+//! Fire alarm inputs, unlocking doors for evacuation while the alarm is asserted.
+//!
+//! The service polls its [`AlarmInput`]s in [`NfcService::check_alarms`]. While any of them
+//! is asserted the doors configured through [`NfcService::unlock_on_fire`] are switched to
+//! the emergency-unlock policy, opening for every card, and switched back once all clear.
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{door::DoorId, events::NfcEvent, Kernel, NfcService};
+
+/// A fire alarm panel input, i.e. a dry contact on a GPIO pin.
+pub trait AlarmInput: Send {
+    /// Whether the alarm is asserted.
+    fn asserted(&mut self) -> bool;
+}
+
+/// The alarm inputs of a service and the doors unlocked by them.
+pub(crate) struct Alarms {
+    inputs: Vec<Box<dyn AlarmInput>>,
+    /// The doors unlocked while a fire alarm is asserted.
+    doors: Vec<DoorId>,
+    /// The doors the asserted alarm unlocked, `None` while it's clear.
+    unlocked: Option<Vec<DoorId>>,
+}
+
+impl Alarms {
+    pub const fn new() -> Self {
+        Self {
+            inputs: Vec::new(),
+            doors: Vec::new(),
+            unlocked: None,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Subscribe to a fire alarm input, checked by [`NfcService::check_alarms`].
+    pub fn add_alarm_input<A: AlarmInput + 'static>(&mut self, input: A) {
+        self.alarms.inputs.push(Box::new(input));
+    }
+
+    /// Unlock doors while a fire alarm is asserted, replacing the doors unlocked before.
+    ///
+    /// Takes effect the next time the alarm is asserted.
+    pub fn unlock_on_fire(&mut self, doors: &[DoorId]) {
+        self.alarms.doors = doors.into();
+    }
+
+    /// Whether a fire alarm is asserted, as of the last [`NfcService::check_alarms`].
+    #[inline]
+    pub const fn fire_alarm(&self) -> bool {
+        self.alarms.unlocked.is_some()
+    }
+
+    /// Check the fire alarm inputs, call this periodically.
+    ///
+    /// When one is asserted the configured doors are emergency unlocked and
+    /// [`NfcEvent::FireAlarmRaised`] is emitted. When all of them clear again, the doors the
+    /// alarm unlocked are reverted and [`NfcEvent::FireAlarmCleared`] is emitted. Doors
+    /// that were emergency unlocked before the alarm stay unlocked.
+    pub fn check_alarms(&mut self) -> bool {
+        // Every input is polled, so edge-triggered inputs don't miss a change.
+        let mut asserted = false;
+        for input in &mut self.alarms.inputs {
+            asserted |= input.asserted();
+        }
+
+        match (asserted, self.alarms.unlocked.is_some()) {
+            (true, false) => {
+                let unlocked: Vec<DoorId> = self
+                    .alarms
+                    .doors
+                    .iter()
+                    .copied()
+                    .filter(|door| !self.policy.is_emergency_unlocked(*door))
+                    .collect();
+                for door in &unlocked {
+                    self.policy.emergency_unlock(*door);
+                }
+                self.alarms.unlocked = Some(unlocked);
+                self.emit(NfcEvent::FireAlarmRaised);
+            }
+            (false, true) => {
+                for door in self.alarms.unlocked.take().unwrap_or_default() {
+                    let _ = self.policy.lift_emergency_unlock(door);
+                }
+                self.emit(NfcEvent::FireAlarmCleared);
+            }
+            _ => {}
+        }
+        asserted
+    }
+}
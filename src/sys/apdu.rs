@@ -0,0 +1,293 @@
+//! ISO 7816-4 APDUs exchanged with ISO 14443-4 tags.
+use alloc::vec::Vec;
+
+use crate::errors::ApduError;
+
+/// The largest command data an extended length APDU can carry.
+pub const MAX_DATA: usize = u16::MAX as usize;
+
+/// A command APDU sent to the tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    cla: u8,
+    ins: u8,
+    p1: u8,
+    p2: u8,
+    data: Vec<u8>,
+    le: Option<u32>,
+}
+
+#[allow(dead_code)]
+impl Command {
+    /// Create a new Command without data that expects no response data.
+    #[inline]
+    pub const fn new(cla: u8, ins: u8, p1: u8, p2: u8) -> Self {
+        Self {
+            cla,
+            ins,
+            p1,
+            p2,
+            data: Vec::new(),
+            le: None,
+        }
+    }
+
+    /// SELECT an application by its identifier.
+    pub fn select(aid: &[u8]) -> Result<Self, ApduError> {
+        Self::new(0x00, 0xa4, 0x04, 0x00)
+            .data(aid)
+            .map(|cmd| cmd.le(256))
+    }
+
+    /// GET RESPONSE to fetch `len` bytes of remaining response data.
+    #[inline]
+    pub const fn get_response(len: u8) -> Self {
+        Self::new(0x00, 0xc0, 0x00, 0x00).le(if len == 0 { 256 } else { len as u32 })
+    }
+
+    /// Set the command data.
+    pub fn data(mut self, data: &[u8]) -> Result<Self, ApduError> {
+        if data.len() > MAX_DATA {
+            return Err(ApduError::TooLong);
+        }
+        self.data = data.into();
+        Ok(self)
+    }
+
+    /// Set the maximum number of response bytes expected, up to 65536.
+    #[inline]
+    pub const fn le(mut self, le: u32) -> Self {
+        self.le = Some(if le > 65536 { 65536 } else { le });
+        self
+    }
+
+    /// Parse a Command from the raw bytes sent by a reader, i.e. to a tag being emulated.
+    pub fn parse(bytes: &[u8]) -> Result<Self, ApduError> {
+        let [cla, ins, p1, p2, body @ ..] = bytes else {
+            return Err(ApduError::Truncated);
+        };
+        let mut command = Self::new(*cla, *ins, *p1, *p2);
+        let (data, le) = match body {
+            [] => (&[][..], None),
+            [le] => (&[][..], Some(if *le == 0 { 256 } else { *le as u32 })),
+            [0, hi, lo] => (&[][..], Some(extended_le(*hi, *lo))),
+            [0, hi, lo, rest @ ..] => {
+                let lc = u16::from_be_bytes([*hi, *lo]) as usize;
+                match rest.len().checked_sub(lc) {
+                    Some(0) => (rest, None),
+                    Some(2) => (&rest[..lc], Some(extended_le(rest[lc], rest[lc + 1]))),
+                    _ => return Err(ApduError::Length),
+                }
+            }
+            [lc, rest @ ..] => {
+                let lc = *lc as usize;
+                match rest.len().checked_sub(lc) {
+                    Some(0) => (rest, None),
+                    Some(1) => (
+                        &rest[..lc],
+                        Some(if rest[lc] == 0 { 256 } else { rest[lc] as u32 }),
+                    ),
+                    _ => return Err(ApduError::Length),
+                }
+            }
+        };
+        command.data = data.into();
+        command.le = le;
+        Ok(command)
+    }
+
+    #[inline]
+    pub const fn cla(&self) -> u8 {
+        self.cla
+    }
+
+    #[inline]
+    pub const fn ins(&self) -> u8 {
+        self.ins
+    }
+
+    #[inline]
+    pub const fn p1(&self) -> u8 {
+        self.p1
+    }
+
+    #[inline]
+    pub const fn p2(&self) -> u8 {
+        self.p2
+    }
+
+    /// The maximum number of response bytes expected, `None` if no response data is.
+    #[inline]
+    pub const fn expected(&self) -> Option<u32> {
+        self.le
+    }
+
+    #[inline]
+    pub fn payload(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Encode this Command, using extended length fields only when needed.
+    pub fn encode(&self) -> Vec<u8> {
+        let extended = self.data.len() > 255 || self.le.is_some_and(|le| le > 256);
+        let mut bytes = Vec::with_capacity(4 + 3 + self.data.len() + 3);
+        bytes.extend_from_slice(&[self.cla, self.ins, self.p1, self.p2]);
+
+        if !self.data.is_empty() {
+            if extended {
+                bytes.push(0);
+                bytes.extend_from_slice(&(self.data.len() as u16).to_be_bytes());
+            } else {
+                bytes.push(self.data.len() as u8);
+            }
+            bytes.extend_from_slice(&self.data);
+        }
+
+        if let Some(le) = self.le {
+            // The maximum length is encoded as 0.
+            if extended {
+                if self.data.is_empty() {
+                    bytes.push(0);
+                }
+                bytes.extend_from_slice(&(le as u16).to_be_bytes());
+            } else {
+                bytes.push(le as u8);
+            }
+        }
+        bytes
+    }
+}
+
+/// An extended Le, where 0 encodes the maximum length.
+#[inline]
+const fn extended_le(hi: u8, lo: u8) -> u32 {
+    match u16::from_be_bytes([hi, lo]) {
+        0 => 65536,
+        le => le as u32,
+    }
+}
+
+/// The two status bytes ending every response APDU.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StatusWord(pub u16);
+
+#[allow(dead_code)]
+impl StatusWord {
+    pub const SUCCESS: StatusWord = StatusWord(0x9000);
+    pub const WRONG_LENGTH: StatusWord = StatusWord(0x6700);
+    pub const CONDITIONS_NOT_SATISFIED: StatusWord = StatusWord(0x6985);
+    pub const FILE_NOT_FOUND: StatusWord = StatusWord(0x6a82);
+    pub const WRONG_OFFSET: StatusWord = StatusWord(0x6b00);
+    pub const INS_NOT_SUPPORTED: StatusWord = StatusWord(0x6d00);
+    pub const CLA_NOT_SUPPORTED: StatusWord = StatusWord(0x6e00);
+
+    #[inline]
+    pub const fn new(sw1: u8, sw2: u8) -> Self {
+        Self(u16::from_be_bytes([sw1, sw2]))
+    }
+
+    #[inline]
+    pub const fn sw1(&self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    #[inline]
+    pub const fn sw2(&self) -> u8 {
+        self.0 as u8
+    }
+
+    #[inline]
+    pub const fn is_success(&self) -> bool {
+        self.0 == Self::SUCCESS.0
+    }
+
+    /// `61XX`, more response data is available through GET RESPONSE.
+    #[inline]
+    pub const fn bytes_remaining(&self) -> Option<u8> {
+        if self.sw1() == 0x61 {
+            Some(self.sw2())
+        } else {
+            None
+        }
+    }
+
+    /// `6CXX`, the command has to be reissued with the given Le.
+    #[inline]
+    pub const fn wrong_le(&self) -> Option<u8> {
+        if self.sw1() == 0x6c {
+            Some(self.sw2())
+        } else {
+            None
+        }
+    }
+}
+
+/// A response APDU received from the tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    data: Vec<u8>,
+    status: StatusWord,
+}
+
+#[allow(dead_code)]
+impl Response {
+    /// Create a new Response, i.e. answering as a tag being emulated.
+    #[inline]
+    pub const fn new(data: Vec<u8>, status: StatusWord) -> Self {
+        Self { data, status }
+    }
+
+    /// Parse a Response from the raw bytes returned by the tag.
+    pub fn parse(bytes: &[u8]) -> Result<Self, ApduError> {
+        match bytes {
+            [data @ .., sw1, sw2] => Ok(Self {
+                data: data.into(),
+                status: StatusWord::new(*sw1, *sw2),
+            }),
+            _ => Err(ApduError::Truncated),
+        }
+    }
+
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    #[inline]
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    #[inline]
+    pub const fn status(&self) -> StatusWord {
+        self.status
+    }
+
+    #[inline]
+    pub const fn is_success(&self) -> bool {
+        self.status.is_success()
+    }
+
+    /// Turn a non successful response into an error.
+    #[inline]
+    pub fn success(self) -> Result<Self, ApduError> {
+        if self.is_success() {
+            Ok(self)
+        } else {
+            Err(ApduError::Status(self.status.0))
+        }
+    }
+
+    /// Encode this Response, its data followed by the status word.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.data.len() + 2);
+        bytes.extend_from_slice(&self.data);
+        bytes.extend_from_slice(&self.status.0.to_be_bytes());
+        bytes
+    }
+
+    pub(crate) fn extend(&mut self, other: Response) {
+        self.data.extend_from_slice(&other.data);
+        self.status = other.status;
+    }
+}
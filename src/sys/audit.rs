@@ -0,0 +1,212 @@
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    codec, door::DoorId, errors::AccessError, events::AccessEvent, role::RoleId,
+    transaction::TransactionId, Permissions, ReaderId, Timestamp, Uid,
+};
+
+/// Where an audited operation originated from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Origin {
+    /// The card was presented to, or written by a reader.
+    Reader(ReaderId),
+    /// An administrative operation performed on the service.
+    Admin,
+}
+
+/// The operation an [`AuditEntry`] records.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditAction {
+    /// A card was read from its tag.
+    Read,
+    /// A card was written to its tag.
+    Write,
+    /// Permissions were granted to a card.
+    Grant(Permissions),
+    /// The permissions of a card were replaced.
+    SetPermissions(Permissions),
+    /// A role was assigned to a card.
+    AssignRole(RoleId),
+    /// A role was removed from a card.
+    UnassignRole(RoleId),
+    /// A card was revoked.
+    Revoke,
+    /// A card's revocation was lifted.
+    Reinstate,
+    /// A card was unbound from the service.
+    Unbind,
+    /// An access decision was made for a presented card.
+    Access,
+    /// A presented card attempted to open a door.
+    Open(DoorId),
+    /// A card asked for additional permissions.
+    RequestEscalation(Permissions),
+    /// An admin denied a card's request for additional permissions.
+    DenyEscalation(Permissions),
+    /// Permissions of a card expired and were dropped.
+    Expire(Permissions),
+    /// Permissions were granted to a card for a while.
+    Elevate(Permissions),
+    /// The permissions of a card's elevation were taken back.
+    Revert(Permissions),
+}
+
+#[allow(dead_code)]
+impl AuditAction {
+    /// The name of this action, i.e. `open`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Grant(..) => "grant",
+            Self::SetPermissions(..) => "set_permissions",
+            Self::AssignRole(..) => "assign_role",
+            Self::UnassignRole(..) => "unassign_role",
+            Self::Revoke => "revoke",
+            Self::Reinstate => "reinstate",
+            Self::Unbind => "unbind",
+            Self::Access => "access",
+            Self::Open(..) => "open",
+            Self::RequestEscalation(..) => "request_escalation",
+            Self::DenyEscalation(..) => "deny_escalation",
+            Self::Expire(..) => "expire",
+            Self::Elevate(..) => "elevate",
+            Self::Revert(..) => "revert",
+        }
+    }
+}
+
+/// A single record in the [`AuditLog`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// The card this operation was performed on.
+    pub card: Uid,
+    /// When the operation happened.
+    pub at: Timestamp,
+    /// Where the operation originated from.
+    pub origin: Origin,
+    pub action: AuditAction,
+    pub result: Result<(), AccessError>,
+    /// The transaction of the reader the operation originated from, if it did.
+    #[serde(default)]
+    #[cfg_attr(
+        not(feature = "postcard"),
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    pub transaction: Option<TransactionId>,
+}
+
+impl AuditEntry {
+    /// Whether the recorded operation succeeded.
+    #[inline]
+    pub const fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// The access decision this entry records, if it records one made at a reader.
+    pub fn access_event(&self) -> Option<AccessEvent> {
+        match (self.origin, self.action) {
+            (
+                Origin::Reader(reader),
+                AuditAction::Read | AuditAction::Access | AuditAction::Open(..),
+            ) => Some(AccessEvent::new(
+                self.transaction,
+                self.card,
+                reader,
+                &self.result,
+                self.at,
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Something every [`AuditEntry`] is written to as it's recorded, i.e. a remote log.
+///
+/// Implemented for closures, i.e. `service.add_audit_sink(|entry: &AuditEntry| ..)`.
+pub trait AuditSink: Send {
+    fn record(&mut self, entry: &AuditEntry);
+}
+
+impl<F> AuditSink for F
+where
+    F: FnMut(&AuditEntry) + Send,
+{
+    #[inline]
+    fn record(&mut self, entry: &AuditEntry) {
+        self(entry)
+    }
+}
+
+/// An append-only log of every operation performed by the service.
+#[derive(Debug, Clone, Default)]
+pub struct AuditLog {
+    entries: Vec<AuditEntry>,
+}
+
+#[allow(dead_code)]
+impl AuditLog {
+    /// Create a new empty `AuditLog`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Append an entry to the log.
+    #[inline]
+    pub(crate) fn record(&mut self, entry: AuditEntry) {
+        self.entries.push(entry);
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// All the entries in the order they were recorded.
+    #[inline]
+    pub fn entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+
+    /// The entries recorded for a specific card.
+    pub fn by_card(&self, card_id: Uid) -> impl Iterator<Item = &AuditEntry> + '_ {
+        self.entries.iter().filter(move |e| e.card == card_id)
+    }
+
+    /// The entries recorded within `from..=to`.
+    pub fn between(
+        &self,
+        from: Timestamp,
+        to: Timestamp,
+    ) -> impl Iterator<Item = &AuditEntry> + '_ {
+        self.entries
+            .iter()
+            .filter(move |e| e.at >= from && e.at <= to)
+    }
+
+    /// The entries of operations that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &AuditEntry> + '_ {
+        self.entries.iter().filter(|e| !e.is_ok())
+    }
+
+    /// The access decisions made at readers, see [`AuditEntry::access_event`].
+    pub fn access_events(&self) -> impl Iterator<Item = AccessEvent> + '_ {
+        self.entries.iter().filter_map(AuditEntry::access_event)
+    }
+
+    /// Export the log as a bytes payload.
+    #[inline]
+    pub fn export(&self) -> Vec<u8> {
+        // The entries are plain data which always serialize.
+        codec::to_vec(&self.entries).unwrap_or_default()
+    }
+}
@@ -0,0 +1,136 @@
+//! The permissions each [`Position`] is issued by default, and the cards deviating from them.
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::{template::CardTemplate, Card, Kernel, NfcService, Permissions, Position, Uid};
+
+/// The default permissions of each [`Position`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PositionDefaults {
+    pub manager: Permissions,
+    pub director: Permissions,
+    pub coordinator: Permissions,
+}
+
+impl Default for PositionDefaults {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+#[allow(dead_code)]
+impl PositionDefaults {
+    /// Coordinators get regular access, managers can also operate the doors and directors
+    /// administer on top of that.
+    pub const STANDARD: Self = Self {
+        manager: Permissions::REGULAR.union(Permissions::OPEN_DOORS),
+        director: Permissions::REGULAR
+            .union(Permissions::OPEN_DOORS)
+            .union(Permissions::ADMIN),
+        coordinator: Permissions::REGULAR,
+    };
+
+    /// The default permissions of a position.
+    #[inline]
+    pub const fn get(&self, position: Position) -> Permissions {
+        match position {
+            Position::Manager => self.manager,
+            Position::Director => self.director,
+            Position::Coordinator => self.coordinator,
+        }
+    }
+
+    /// Replace the default permissions of a position, returning the previous ones.
+    pub fn set(&mut self, position: Position, permissions: Permissions) -> Permissions {
+        let defaults = match position {
+            Position::Manager => &mut self.manager,
+            Position::Director => &mut self.director,
+            Position::Coordinator => &mut self.coordinator,
+        };
+        core::mem::replace(defaults, permissions)
+    }
+
+    /// A template issuing cards for a position with its default permissions.
+    #[inline]
+    pub const fn template(&self, position: Position) -> CardTemplate {
+        CardTemplate::new(self.get(position)).with_position(position)
+    }
+}
+
+/// A card whose permissions deviate from its position's defaults.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Deviation {
+    pub id: Uid,
+    pub position: Position,
+    /// The default permissions of the position.
+    pub baseline: Permissions,
+    /// The permissions granted to the card.
+    pub permissions: Permissions,
+}
+
+#[allow(dead_code)]
+impl Deviation {
+    /// The permissions of the baseline the card lacks.
+    #[inline]
+    pub const fn missing(&self) -> Permissions {
+        self.baseline.difference(self.permissions)
+    }
+
+    /// The permissions the card holds beyond the baseline.
+    #[inline]
+    pub const fn extra(&self) -> Permissions {
+        self.permissions.difference(self.baseline)
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Issue new cards with `defaults` as the baseline of each position.
+    #[must_use]
+    pub fn with_position_defaults(mut self, defaults: PositionDefaults) -> Self {
+        self.set_position_defaults(defaults);
+        self
+    }
+
+    /// Replace the position defaults of this service, see [`NfcService::with_position_defaults`].
+    #[inline]
+    pub fn set_position_defaults(&mut self, defaults: PositionDefaults) {
+        self.position_defaults = defaults;
+    }
+
+    /// The default permissions of each position, [`PositionDefaults::STANDARD`] by default.
+    #[inline]
+    pub const fn position_defaults(&self) -> &PositionDefaults {
+        &self.position_defaults
+    }
+
+    /// Mint a card for a position with its default permissions and register it,
+    /// see [`NfcService::issue`].
+    pub fn issue_for(&mut self, position: Position) -> Card {
+        let template = self.position_defaults.template(position);
+        self.issue(&template)
+    }
+
+    /// The registered cards whose permissions differ from their position's defaults,
+    /// in ascending id order.
+    ///
+    /// Only the permissions granted to cards directly are compared, those their roles
+    /// expand to aren't.
+    pub fn deviations(&self) -> Vec<Deviation> {
+        self.cards
+            .values()
+            .filter_map(|card| {
+                let baseline = self.position_defaults.get(card.position);
+                (card.permissions != baseline).then_some(Deviation {
+                    id: card.id,
+                    position: card.position,
+                    baseline,
+                    permissions: card.permissions,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,124 @@
+//! Transactional writes of many cards, i.e. for mass re-keying.
+use alloc::vec::Vec;
+
+use crate::{
+    audit::{AuditAction, Origin},
+    errors::{AccessError, BatchError},
+    events::NfcEvent,
+    logging::kernel_error,
+    read_card, Card, Kernel, NfcService, ReaderId,
+};
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Write a batch of registered cards to their tags through a reader.
+    ///
+    /// Every write is verified by reading the tag back. If any card fails, the tags written
+    /// so far get their previous payloads restored and the registry is left untouched,
+    /// otherwise the registry is updated with the written cards.
+    pub fn write_batch(&mut self, reader: ReaderId, cards: &[Card]) -> Result<(), BatchError> {
+        let now = self.now();
+        if !self.readers.contains_key(&reader) {
+            return Err(BatchError::Refused(AccessError::UnknownReader(reader)));
+        }
+        for card in cards {
+            if self.revoked.is_revoked(card.id) {
+                return Err(BatchError::Refused(AccessError::Revoked(card.id)));
+            }
+            if !self.cards.contains_key(&card.id) {
+                return Err(BatchError::Refused(AccessError::Unknown(card.id)));
+            }
+        }
+
+        // The payloads on the tags before the batch, restored on failure.
+        let mut previous = Vec::with_capacity(cards.len());
+        let mut failure = None;
+        for card in cards {
+            match self.stage(reader, card) {
+                Ok(old) => previous.push(old),
+                Err(reason) => {
+                    failure = Some((card, reason));
+                    break;
+                }
+            }
+        }
+
+        let Some((card, reason)) = failure else {
+            for card in cards {
+                let _ = self.cards.insert(card.id, *card);
+                self.log(
+                    card.id,
+                    now,
+                    Origin::Reader(reader),
+                    AuditAction::Write,
+                    Ok(()),
+                );
+            }
+            self.registry_changed();
+            return Ok(());
+        };
+
+        self.emit(NfcEvent::WriteFailed {
+            reader,
+            id: card.id,
+            reason,
+        });
+        self.log(
+            card.id,
+            now,
+            Origin::Reader(reader),
+            AuditAction::Write,
+            Err(reason),
+        );
+
+        let mut restored = true;
+        if let Some(kernel) = self.readers.get_mut(&reader) {
+            for old in previous.iter().rev() {
+                let bytes = old.try_to_bytes().map_err(|_| AccessError::Kernel);
+                restored &= bytes
+                    .and_then(|bytes| {
+                        kernel
+                            .write(old, &bytes)
+                            .map_err(|why| kernel_error(reader, why))
+                    })
+                    .is_ok();
+            }
+        }
+        Err(BatchError::Failed {
+            id: card.id,
+            reason,
+            restored,
+        })
+    }
+
+    /// Write a single card of a batch and verify it, returning the payload it replaced.
+    fn stage(&mut self, reader: ReaderId, card: &Card) -> Result<Card, AccessError> {
+        let kernel = self
+            .readers
+            .get_mut(&reader)
+            .ok_or(AccessError::UnknownReader(reader))?;
+
+        let old = read_card(kernel, card.id).map_err(|why| kernel_error(reader, why))?;
+        let bytes = card.try_to_bytes().map_err(|_| AccessError::Kernel)?;
+
+        let written = kernel
+            .write(card, &bytes)
+            .and_then(|_| read_card(kernel, card.id).map(|written| written == *card));
+        match written {
+            Ok(true) => Ok(old),
+            // The tag may have been partially written, put the old payload back.
+            result => {
+                if let Ok(bytes) = old.try_to_bytes() {
+                    let _ = kernel.write(&old, &bytes);
+                }
+                Err(result.map_or_else(
+                    |why| kernel_error(reader, why),
+                    |_| AccessError::WriteVerifyFailed(card.id),
+                ))
+            }
+        }
+    }
+}
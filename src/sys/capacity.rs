@@ -0,0 +1,84 @@
+//! Bounding the number of cards cached on constrained devices.
+use alloc::collections::btree_map::BTreeMap;
+use core::num::NonZeroUsize;
+
+use crate::{events::NfcEvent, Kernel, NfcService, Uid};
+
+/// The cap on the registry and how recently each card was seen.
+#[derive(Debug, Clone)]
+pub(crate) struct Capacity {
+    limit: Option<NonZeroUsize>,
+    seen: BTreeMap<Uid, u64>,
+    tick: u64,
+}
+
+impl Capacity {
+    #[inline]
+    pub(crate) const fn new() -> Self {
+        Self {
+            limit: None,
+            seen: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    pub(crate) fn touch(&mut self, card_id: Uid) {
+        self.tick = self.tick.wrapping_add(1);
+        let _ = self.seen.insert(card_id, self.tick);
+    }
+
+    pub(crate) fn forget(&mut self, card_id: &Uid) {
+        let _ = self.seen.remove(card_id);
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Cache at most `limit` cards, evicting the least recently seen ones beyond it.
+    #[must_use]
+    pub fn with_capacity(mut self, limit: NonZeroUsize) -> Self {
+        self.set_capacity(Some(limit));
+        self
+    }
+
+    /// Change the maximum number of cards cached, `None` lifts the limit.
+    ///
+    /// Cards beyond a lowered limit are evicted right away.
+    pub fn set_capacity(&mut self, limit: Option<NonZeroUsize>) {
+        self.capacity.limit = limit;
+        self.evict();
+    }
+
+    /// The maximum number of cards cached, `None` if it isn't limited.
+    #[inline]
+    pub const fn capacity(&self) -> Option<NonZeroUsize> {
+        self.capacity.limit
+    }
+
+    /// Evict the least recently seen cards until the registry fits its limit.
+    ///
+    /// A card is seen when it's registered or granted access, cards restored from
+    /// storage count as the least recently seen. Emits [`NfcEvent::CardEvicted`].
+    pub(crate) fn evict(&mut self) {
+        let Some(limit) = self.capacity.limit else {
+            return;
+        };
+        while self.cards.len() > limit.get() {
+            let oldest = self
+                .cards
+                .keys()
+                .min_by_key(|id| self.capacity.seen.get(id).copied().unwrap_or(0))
+                .copied();
+            let Some(id) = oldest else {
+                break;
+            };
+            let _ = self.cards.remove(&id);
+            let _ = self.duress.remove(&id);
+            self.capacity.forget(&id);
+            self.emit(NfcEvent::CardEvicted { id });
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! A challenge-response handshake proving a tag holds its card's key.
+//!
+//! 1. The reader sends a random nonce to the tag.
+//! 2. The tag answers with a nonce of its own and a MAC over both nonces and its UID.
+//! 3. The reader checks that MAC and proves itself to the tag with a MAC over
+//!    the nonces in reverse order.
+//!
+//! A payload dumped from a tag doesn't carry the key, so it can't pass the handshake alone.
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{errors::AccessError, logging::kernel_error, Card, Kernel, NfcService, ReaderId, Uid};
+
+/// The size of a nonce in bytes.
+pub const NONCE_SIZE: usize = 16;
+
+/// The size of a MAC in bytes.
+pub const MAC_SIZE: usize = 16;
+
+/// A random challenge sent by either side of the handshake.
+pub type Nonce = [u8; NONCE_SIZE];
+
+/// A message authentication code computed with a card's key.
+pub type Mac = [u8; MAC_SIZE];
+
+/// Prefixes the MACed messages so a tag's proof can't be reflected back as the reader's.
+const TAG_PROOF: u8 = 0x01;
+const READER_PROOF: u8 = 0x02;
+
+/// A tag's answer to the reader's challenge.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Answer {
+    /// The tag's own challenge for the reader.
+    pub nonce: Nonce,
+    /// The tag's proof, see [`tag_message`].
+    pub mac: Mac,
+}
+
+/// Generates nonces and computes MACs with the key of each card.
+///
+/// See [`KeyedAuthenticator`](crate::keys::KeyedAuthenticator) for keys held in memory
+/// and [`ElementAuthenticator`](crate::secure::ElementAuthenticator) for keys held by a secure element.
+pub trait Authenticator {
+    /// Generate a fresh random nonce, `None` if no randomness is available.
+    fn nonce(&mut self) -> Option<Nonce>;
+
+    /// MAC `data` with the key of a card, `None` if no key is known for it.
+    fn mac(&mut self, card: &Card, data: &[u8]) -> Option<Mac>;
+
+    /// Check `mac` was computed over `data` with the key of a card.
+    fn verify(&mut self, card: &Card, data: &[u8], mac: &Mac) -> bool {
+        self.mac(card, data)
+            .is_some_and(|expected| macs_match(&expected, mac))
+    }
+}
+
+/// The message a tag MACs to answer the reader's `challenge` with its own `nonce`.
+pub fn tag_message(card: Uid, challenge: &Nonce, nonce: &Nonce) -> Vec<u8> {
+    message(TAG_PROOF, card, challenge, nonce)
+}
+
+/// The message the reader MACs to answer the tag's `nonce` to its `challenge`.
+pub fn reader_message(card: Uid, challenge: &Nonce, nonce: &Nonce) -> Vec<u8> {
+    message(READER_PROOF, card, nonce, challenge)
+}
+
+fn message(direction: u8, card: Uid, first: &Nonce, second: &Nonce) -> Vec<u8> {
+    let uid = card.as_bytes();
+    let mut message = Vec::with_capacity(1 + 2 * NONCE_SIZE + uid.len());
+    message.push(direction);
+    message.extend_from_slice(first);
+    message.extend_from_slice(second);
+    message.extend_from_slice(uid);
+    message
+}
+
+/// Compare two MACs without leaking how much of them matched.
+pub(crate) fn macs_match(a: &Mac, b: &Mac) -> bool {
+    a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Require cards read through [`NfcService::read`] to pass the handshake first.
+    #[must_use]
+    pub fn with_authenticator<A>(mut self, authenticator: A) -> Self
+    where
+        A: Authenticator + Send + 'static,
+    {
+        self.set_authenticator(authenticator);
+        self
+    }
+
+    /// Replace the authenticator of this service, see [`NfcService::with_authenticator`].
+    pub fn set_authenticator<A>(&mut self, authenticator: A)
+    where
+        A: Authenticator + Send + 'static,
+    {
+        self.authenticator = Some(Box::new(authenticator));
+    }
+
+    /// Run the handshake with a registered card's tag in a reader's field.
+    ///
+    /// Succeeds right away if this service has no authenticator.
+    pub fn authenticate(&mut self, reader: ReaderId, card_id: Uid) -> Result<(), AccessError> {
+        let kernel = self
+            .readers
+            .get_mut(&reader)
+            .ok_or(AccessError::UnknownReader(reader))?;
+        let Some(authenticator) = self.authenticator.as_mut() else {
+            return Ok(());
+        };
+        let card = self
+            .cards
+            .get(&card_id)
+            .ok_or(AccessError::Unknown(card_id))?;
+
+        let challenge = authenticator
+            .nonce()
+            .ok_or(AccessError::Unauthenticated(card_id))?;
+        let answer = kernel
+            .challenge(card_id, &challenge)
+            .map_err(|why| kernel_error(reader, why))?;
+
+        let message = tag_message(card_id, &challenge, &answer.nonce);
+        if !authenticator.verify(card, &message, &answer.mac) {
+            return Err(AccessError::Unauthenticated(card_id));
+        }
+
+        let proof = authenticator
+            .mac(card, &reader_message(card_id, &challenge, &answer.nonce))
+            .ok_or(AccessError::Unauthenticated(card_id))?;
+        kernel
+            .confirm(card_id, &proof)
+            .map_err(|why| kernel_error(reader, why))
+    }
+}
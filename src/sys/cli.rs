@@ -0,0 +1,181 @@
+//! The command line interface administering a persistent card store.
+use alloc::{string::String, vec::Vec};
+use std::{
+    eprintln,
+    fs::OpenOptions,
+    io::{self, Read, Write},
+    path::PathBuf,
+    println,
+    process::ExitCode,
+};
+
+use crate::{
+    errors::AccessError,
+    frame::FrameDecoder,
+    store::{FileStore, StoreError},
+    NfcService, Permissions, System, Uid,
+};
+
+const USAGE: &str = "\
+usage: lowa [--store <dir>] nfc <command>
+
+commands:
+    list                      List the registered cards
+    grant <id> <permissions>  Grant permissions to a card, i.e. OPEN_DOORS|IT_SUPPORT
+    revoke <id>               Revoke a card
+    provision [port]          Serve the provisioning protocol on a serial port, or stdin/stdout";
+
+/// The directory cards are stored in unless `--store` is given.
+const DEFAULT_STORE: &str = "lowa-store";
+
+#[derive(Debug)]
+enum CliError {
+    Usage,
+    InvalidId(String),
+    InvalidPermissions(String),
+    Access(AccessError),
+    Store(StoreError<io::Error>),
+    Io(io::Error),
+}
+
+impl core::fmt::Display for CliError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Usage => f.write_str(USAGE),
+            Self::InvalidId(id) => write!(f, "invalid card id `{}`", id),
+            Self::InvalidPermissions(perms) => write!(f, "invalid permissions `{}`", perms),
+            Self::Access(why) => write!(f, "{}", why),
+            Self::Store(why) => write!(f, "{}", why),
+            Self::Io(why) => write!(f, "{}", why),
+        }
+    }
+}
+
+impl From<StoreError<io::Error>> for CliError {
+    fn from(why: StoreError<io::Error>) -> Self {
+        Self::Store(why)
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(why: io::Error) -> Self {
+        Self::Io(why)
+    }
+}
+
+impl From<AccessError> for CliError {
+    fn from(why: AccessError) -> Self {
+        Self::Access(why)
+    }
+}
+
+fn parse_id(id: Option<String>) -> Result<Uid, CliError> {
+    let id = id.ok_or(CliError::Usage)?;
+    Uid::from_hex(&id).ok_or(CliError::InvalidId(id))
+}
+
+/// Parse permission names separated by `|` or `,`.
+fn parse_permissions(perms: Option<String>) -> Result<Permissions, CliError> {
+    let perms = perms.ok_or(CliError::Usage)?;
+    perms
+        .parse()
+        .map_err(|_| CliError::InvalidPermissions(perms))
+}
+
+/// Run the command line with the given arguments, excluding the program name.
+pub fn run(args: impl IntoIterator<Item = String>) -> ExitCode {
+    match dispatch(args.into_iter().collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Usage) => {
+            eprintln!("{}", USAGE);
+            ExitCode::from(2)
+        }
+        Err(why) => {
+            eprintln!("error: {}", why);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn dispatch(mut args: Vec<String>) -> Result<(), CliError> {
+    let mut dir = PathBuf::from(DEFAULT_STORE);
+    if args.first().map(String::as_str) == Some("--store") {
+        let _ = args.remove(0);
+        dir = PathBuf::from(args.first().ok_or(CliError::Usage)?);
+        let _ = args.remove(0);
+    }
+
+    let mut args = args.into_iter();
+    if args.next().as_deref() != Some("nfc") {
+        return Err(CliError::Usage);
+    }
+
+    let mut store = FileStore::open(dir)?;
+    let mut nfc = NfcService::<System>::empty();
+    nfc.restore(&mut store)?;
+
+    match args.next().as_deref() {
+        Some("list") => {
+            for card in nfc.iter() {
+                let revoked = nfc.revocations().is_revoked(card.id());
+                println!(
+                    "{}\t{:?}\t{:?}{}",
+                    card.id(),
+                    card.permissions(),
+                    card.position(),
+                    if revoked { "\trevoked" } else { "" }
+                );
+            }
+            Ok(())
+        }
+        Some("grant") => {
+            let id = parse_id(args.next())?;
+            let perms = parse_permissions(args.next())?;
+            nfc.grant(id, perms)?;
+            Ok(nfc.persist(&mut store)?)
+        }
+        Some("revoke") => {
+            let id = parse_id(args.next())?;
+            if !nfc.revoke(id) {
+                println!("{} is unknown or already revoked", id);
+            }
+            Ok(nfc.persist(&mut store)?)
+        }
+        Some("provision") => match args.next() {
+            Some(port) => {
+                let port = OpenOptions::new().read(true).write(true).open(port)?;
+                provision(&mut nfc, &mut store, &port, &port)
+            }
+            None => provision(&mut nfc, &mut store, io::stdin(), io::stdout()),
+        },
+        _ => Err(CliError::Usage),
+    }
+}
+
+/// Serve provisioning requests until the input reaches end of file, persisting after each one.
+fn provision(
+    nfc: &mut NfcService<System>,
+    store: &mut FileStore,
+    mut input: impl Read,
+    mut output: impl Write,
+) -> Result<(), CliError> {
+    let mut decoder = FrameDecoder::new();
+    let mut buf = [0; 256];
+    loop {
+        let read = input.read(&mut buf)?;
+        if read == 0 {
+            return Ok(());
+        }
+
+        decoder.push(&buf[..read]);
+        while let Some(request) = decoder.next_frame() {
+            let response = nfc.provision(&request);
+            nfc.persist(store)?;
+            // Responses always fit in a frame.
+            if let Ok(response) = response.encode() {
+                output.write_all(&response)?;
+            }
+        }
+        output.flush()?;
+    }
+}
@@ -0,0 +1,91 @@
+use core::cell::{Cell, RefCell};
+
+use crate::Timestamp;
+
+/// A source of the current time.
+pub trait Clock {
+    /// The current time in seconds since the unix epoch.
+    fn now(&self) -> Timestamp;
+}
+
+/// A bare timestamp is a clock frozen at that moment.
+impl Clock for Timestamp {
+    #[inline]
+    fn now(&self) -> Timestamp {
+        *self
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    #[inline]
+    fn now(&self) -> Timestamp {
+        (**self).now()
+    }
+}
+
+/// The wall clock of the host, backed by [`std::time::SystemTime`].
+#[cfg(feature = "std")]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs())
+    }
+}
+
+/// A real-time clock peripheral, i.e. a DS3231 on an I2C bus.
+#[allow(unused)]
+pub trait Rtc {
+    type Error;
+
+    /// Read the current time in seconds since the unix epoch.
+    fn read_seconds(&mut self) -> Result<Timestamp, Self::Error>;
+}
+
+/// A [`Clock`] driven by an [`Rtc`].
+///
+/// If the peripheral fails to answer, the last time it reported is used instead.
+#[allow(dead_code)]
+#[derive(Debug)]
+pub struct RtcClock<R> {
+    rtc: RefCell<R>,
+    last: Cell<Timestamp>,
+}
+
+#[allow(dead_code)]
+impl<R: Rtc> RtcClock<R> {
+    #[inline]
+    pub const fn new(rtc: R) -> Self {
+        Self {
+            rtc: RefCell::new(rtc),
+            last: Cell::new(0),
+        }
+    }
+
+    /// Release the peripheral.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.rtc.into_inner()
+    }
+}
+
+impl<R: Rtc> Clock for RtcClock<R> {
+    fn now(&self) -> Timestamp {
+        let read = self
+            .rtc
+            .try_borrow_mut()
+            .ok()
+            .and_then(|mut rtc| rtc.read_seconds().ok());
+        match read {
+            Some(now) => {
+                self.last.set(now);
+                now
+            }
+            None => self.last.get(),
+        }
+    }
+}
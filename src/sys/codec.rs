@@ -0,0 +1,124 @@
+//! The format cards and persisted records are encoded with.
+//!
+//! Payloads are JSON by default. The `postcard` feature swaps it for postcard's compact
+//! binary format so small flash parts don't have to carry `serde_json`, the HTTP and
+//! MQTT frontends keep speaking JSON through the `json` feature either way.
+//!
+//! With the `miniz` feature, card payloads are DEFLATE compressed when that makes them
+//! smaller, see [`DEFLATE`]. Compressed payloads are recognized either way, builds without
+//! the feature reject them with [`ConversionError::Compressed`].
+use alloc::{borrow::Cow, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{ConversionError, EncodeError};
+
+#[cfg(not(any(feature = "json", feature = "postcard")))]
+compile_error!("either the `json` or the `postcard` feature must be enabled");
+
+/// The header byte of compressed payloads, followed by a raw DEFLATE stream.
+///
+/// Uncompressed payloads never start with it, JSON starts with `{` and postcard
+/// cards with their schema version.
+pub const DEFLATE: u8 = 0xDF;
+
+/// The most bytes a compressed payload inflates to, so a garbled stream can't exhaust memory.
+#[cfg(feature = "miniz")]
+pub const MAX_INFLATED: usize = 16 * 1024;
+
+/// Compress a payload behind [`DEFLATE`] if that makes it smaller.
+#[cfg(feature = "miniz")]
+fn deflate(bytes: &[u8]) -> Option<Vec<u8>> {
+    let compressed = miniz_oxide::deflate::compress_to_vec(bytes, 9);
+    if compressed.len() + 1 >= bytes.len() {
+        return None;
+    }
+
+    let mut payload = Vec::with_capacity(compressed.len() + 1);
+    payload.push(DEFLATE);
+    payload.extend_from_slice(&compressed);
+    Some(payload)
+}
+
+/// Compress an encoded payload if that makes it smaller.
+#[inline]
+pub(crate) fn compress(bytes: Vec<u8>) -> Vec<u8> {
+    #[cfg(feature = "miniz")]
+    if let Some(compressed) = deflate(&bytes) {
+        return compressed;
+    }
+    bytes
+}
+
+/// Compress the payload encoded in the first `len` bytes of `buf` in place if that makes
+/// it smaller, returning its new length.
+#[inline]
+pub(crate) fn compress_into(buf: &mut [u8], len: usize) -> usize {
+    #[cfg(feature = "miniz")]
+    if let Some(compressed) = deflate(&buf[..len]) {
+        buf[..compressed.len()].copy_from_slice(&compressed);
+        return compressed.len();
+    }
+    let _ = buf;
+    len
+}
+
+/// Inflate a payload if it's compressed, `target` names its type in errors.
+pub(crate) fn decompress<'a>(
+    target: &'static str,
+    bytes: &'a [u8],
+) -> Result<Cow<'a, [u8]>, ConversionError> {
+    match bytes.split_first() {
+        #[cfg(feature = "miniz")]
+        Some((&DEFLATE, stream)) => {
+            miniz_oxide::inflate::decompress_to_vec_with_limit(stream, MAX_INFLATED)
+                .map(Cow::Owned)
+                .map_err(|_| ConversionError::Compressed { target })
+        }
+        #[cfg(not(feature = "miniz"))]
+        Some((&DEFLATE, _)) => Err(ConversionError::Compressed { target }),
+        _ => Ok(Cow::Borrowed(bytes)),
+    }
+}
+
+/// Encode a value.
+#[cfg(not(feature = "postcard"))]
+pub(crate) fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, EncodeError> {
+    serde_json::to_vec(value).map_err(|_| EncodeError::Serialize)
+}
+
+/// Encode a value.
+#[cfg(feature = "postcard")]
+pub(crate) fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, EncodeError> {
+    postcard::to_allocvec(value).map_err(|_| EncodeError::Serialize)
+}
+
+/// Decode a value, `target` names its type in errors.
+#[cfg(not(feature = "postcard"))]
+pub(crate) fn from_slice<'a, T: Deserialize<'a>>(
+    target: &'static str,
+    bytes: &'a [u8],
+) -> Result<T, ConversionError> {
+    serde_json::from_slice(bytes).map_err(|why| ConversionError::deserialize(target, &why, bytes))
+}
+
+/// Decode a value, `target` names its type in errors.
+#[cfg(feature = "postcard")]
+pub(crate) fn from_slice<'a, T: Deserialize<'a>>(
+    target: &'static str,
+    bytes: &'a [u8],
+) -> Result<T, ConversionError> {
+    use crate::errors::Category;
+
+    // Postcard doesn't report where it failed, only keep the start of the input.
+    postcard::from_bytes(bytes).map_err(|why| ConversionError::Deserialize {
+        target,
+        category: match why {
+            postcard::Error::DeserializeUnexpectedEnd => Category::Eof,
+            _ => Category::Data,
+        },
+        line: 0,
+        column: 0,
+        offset: 0,
+        excerpt: bytes[..bytes.len().min(ConversionError::EXCERPT)].to_vec(),
+    })
+}
@@ -0,0 +1,25 @@
+/// CRC-16/CCITT-FALSE, as used by the framed serial protocols.
+#[inline]
+pub const fn crc16(bytes: &[u8]) -> u16 {
+    crc16_ccitt(0xffff, bytes)
+}
+
+/// CRC-16 with the CCITT polynomial, starting from `init`.
+pub const fn crc16_ccitt(init: u16, bytes: &[u8]) -> u16 {
+    let mut crc = init;
+    let mut i = 0;
+    while i < bytes.len() {
+        crc ^= (bytes[i] as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        i += 1;
+    }
+    crc
+}
@@ -0,0 +1,61 @@
+//! Delta writes, rewriting only the pages of a tag whose bytes changed.
+//!
+//! Tags wear with every page written and each page takes a round trip, so a card whose
+//! permissions changed is written as the pages that differ from the payload already on
+//! its tag, when the kernel writes pages, see [`Kernel::PAGE_SIZE`].
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{errors::KernelError, Card, Kernel, MAX_PAYLOAD};
+
+/// The pages that differ between two payloads of the same length, as runs of consecutive
+/// page indexes.
+///
+/// `None` if the lengths differ, every page after the first change would shift.
+pub fn changed_pages(old: &[u8], new: &[u8], page_size: usize) -> Option<Vec<Range<usize>>> {
+    if old.len() != new.len() || page_size == 0 {
+        return None;
+    }
+
+    let mut runs: Vec<Range<usize>> = Vec::new();
+    let pages = old.chunks(page_size).zip(new.chunks(page_size));
+    for (page, _) in pages.enumerate().filter(|(_, (old, new))| old != new) {
+        match runs.last_mut() {
+            Some(run) if run.end == page => run.end += 1,
+            _ => runs.push(page..page + 1),
+        }
+    }
+    Some(runs)
+}
+
+/// Write the pages of a card's payload that differ from the one on its tag.
+///
+/// Returns `false` without writing anything if the kernel doesn't write pages, or the
+/// tag's payload can't be diffed against, i.e. it changed length. The whole payload needs
+/// writing then.
+pub(crate) fn write_delta<K: Kernel>(
+    kernel: &mut K,
+    card: &Card,
+    bytes: &[u8],
+) -> Result<bool, KernelError> {
+    let Some(page_size) = K::PAGE_SIZE.filter(|size| *size > 0) else {
+        return Ok(false);
+    };
+    let mut old = [0; MAX_PAYLOAD];
+    let Ok(len) = kernel.read_into(card.id, &mut old) else {
+        return Ok(false);
+    };
+    let Some(runs) = old
+        .get(..len)
+        .and_then(|old| changed_pages(old, bytes, page_size))
+    else {
+        return Ok(false);
+    };
+
+    for run in runs {
+        let start = run.start * page_size;
+        let end = (run.end * page_size).min(bytes.len());
+        kernel.write_pages(card, run.start, &bytes[start..end])?;
+    }
+    Ok(true)
+}
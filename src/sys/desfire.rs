@@ -0,0 +1,210 @@
+//! A MIFARE DESFire application and file layer built on top of APDUs.
+//!
+//! Native DESFire commands are wrapped in ISO 7816-4 APDUs, files are accessed
+//! in plain communication mode.
+use alloc::vec::Vec;
+use rand::RngCore;
+
+use crate::{
+    apdu::{Command, Response},
+    errors::{ApduError, DesfireError},
+    Kernel,
+};
+
+/// The AES block size.
+pub const BLOCK: usize = 16;
+
+const CLA: u8 = 0x90;
+const OPERATION_OK: u8 = 0x00;
+const ADDITIONAL_FRAME: u8 = 0xaf;
+
+const SELECT_APPLICATION: u8 = 0x5a;
+const AUTHENTICATE_AES: u8 = 0xaa;
+const GET_APPLICATION_IDS: u8 = 0x6a;
+const GET_FILE_IDS: u8 = 0x6f;
+const READ_DATA: u8 = 0xbd;
+const WRITE_DATA: u8 = 0x3d;
+
+/// The 24 bit identifier of a DESFire application.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Aid(u32);
+
+#[allow(dead_code)]
+impl Aid {
+    /// The PICC level application.
+    pub const PICC: Aid = Aid(0);
+
+    /// Create a new Aid, only the lower 24 bits are used.
+    #[inline]
+    pub const fn new(aid: u32) -> Self {
+        Self(aid & 0x00ff_ffff)
+    }
+
+    #[inline]
+    pub const fn get(&self) -> u32 {
+        self.0
+    }
+
+    #[inline]
+    const fn to_le_bytes(self) -> [u8; 3] {
+        let [a, b, c, _] = self.0.to_le_bytes();
+        [a, b, c]
+    }
+}
+
+/// The AES-128 block cipher a [`Desfire`] session authenticates with.
+///
+/// Kept as a trait so the cipher can come from software or a secure element.
+pub trait Cipher {
+    /// Encrypt `data` in place in CBC mode, leaving the last block in `iv`.
+    fn encrypt(&self, iv: &mut [u8; BLOCK], data: &mut [u8]);
+    /// Decrypt `data` in place in CBC mode, leaving the last ciphertext block in `iv`.
+    fn decrypt(&self, iv: &mut [u8; BLOCK], data: &mut [u8]);
+}
+
+/// A DESFire tag in a reader's field.
+pub struct Desfire<'a, K: Kernel + ?Sized> {
+    kernel: &'a mut K,
+    session: Option<[u8; BLOCK]>,
+}
+
+#[allow(dead_code)]
+impl<'a, K: Kernel + ?Sized> Desfire<'a, K> {
+    /// Talk to the DESFire tag in the kernel's field.
+    #[inline]
+    pub fn new(kernel: &'a mut K) -> Self {
+        Self {
+            kernel,
+            session: None,
+        }
+    }
+
+    /// Whether a session was established by [`Desfire::authenticate`].
+    #[inline]
+    pub const fn is_authenticated(&self) -> bool {
+        self.session.is_some()
+    }
+
+    /// Select an application, this drops any authenticated session.
+    pub fn select_application(&mut self, aid: Aid) -> Result<(), DesfireError> {
+        self.session = None;
+        self.command(SELECT_APPLICATION, &aid.to_le_bytes())
+            .map(|_| ())
+    }
+
+    /// The identifiers of the applications on the tag.
+    pub fn application_ids(&mut self) -> Result<Vec<Aid>, DesfireError> {
+        let data = self.command(GET_APPLICATION_IDS, &[])?;
+        Ok(data
+            .chunks_exact(3)
+            .map(|aid| Aid::new(u32::from_le_bytes([aid[0], aid[1], aid[2], 0])))
+            .collect())
+    }
+
+    /// The identifiers of the files within the selected application.
+    pub fn file_ids(&mut self) -> Result<Vec<u8>, DesfireError> {
+        self.command(GET_FILE_IDS, &[])
+    }
+
+    /// Mutually authenticate with a key of the selected application.
+    pub fn authenticate<C: Cipher, R: RngCore>(
+        &mut self,
+        key_no: u8,
+        cipher: &C,
+        rng: &mut R,
+    ) -> Result<(), DesfireError> {
+        self.session = None;
+
+        let challenge = self.exchange(AUTHENTICATE_AES, &[key_no])?;
+        if challenge.status().sw2() != ADDITIONAL_FRAME {
+            return Err(DesfireError::Status(challenge.status().sw2()));
+        }
+        let mut rnd_b: [u8; BLOCK] = challenge
+            .data()
+            .try_into()
+            .map_err(|_| DesfireError::Authentication)?;
+        let mut iv = [0; BLOCK];
+        cipher.decrypt(&mut iv, &mut rnd_b);
+
+        let mut rnd_a = [0; BLOCK];
+        rng.fill_bytes(&mut rnd_a);
+
+        let mut token = [0; BLOCK * 2];
+        token[..BLOCK].copy_from_slice(&rnd_a);
+        token[BLOCK..].copy_from_slice(&rnd_b);
+        token[BLOCK..].rotate_left(1);
+        cipher.encrypt(&mut iv, &mut token);
+
+        let mut proof: [u8; BLOCK] = self
+            .command(ADDITIONAL_FRAME, &token)?
+            .try_into()
+            .map_err(|_| DesfireError::Authentication)?;
+        cipher.decrypt(&mut iv, &mut proof);
+        proof.rotate_right(1);
+        if proof != rnd_a {
+            return Err(DesfireError::Authentication);
+        }
+
+        let mut session = [0; BLOCK];
+        session[0..4].copy_from_slice(&rnd_a[0..4]);
+        session[4..8].copy_from_slice(&rnd_b[0..4]);
+        session[8..12].copy_from_slice(&rnd_a[12..16]);
+        session[12..16].copy_from_slice(&rnd_b[12..16]);
+        self.session = Some(session);
+        Ok(())
+    }
+
+    /// Read `len` bytes of a data file from `offset`, `0` reads the whole file.
+    pub fn read_file(&mut self, file: u8, offset: u32, len: u32) -> Result<Vec<u8>, DesfireError> {
+        let mut params = [0; 7];
+        params[0] = file;
+        params[1..4].copy_from_slice(&offset.to_le_bytes()[..3]);
+        params[4..7].copy_from_slice(&len.to_le_bytes()[..3]);
+        self.command(READ_DATA, &params)
+    }
+
+    /// Write `data` to a data file at `offset`.
+    pub fn write_file(&mut self, file: u8, offset: u32, data: &[u8]) -> Result<(), DesfireError> {
+        let mut params = Vec::with_capacity(7 + data.len());
+        params.push(file);
+        params.extend_from_slice(&offset.to_le_bytes()[..3]);
+        params.extend_from_slice(&(data.len() as u32).to_le_bytes()[..3]);
+        params.extend_from_slice(data);
+        self.command(WRITE_DATA, &params).map(|_| ())
+    }
+
+    /// Send a native command, following additional frames until the tag is done.
+    fn command(&mut self, ins: u8, data: &[u8]) -> Result<Vec<u8>, DesfireError> {
+        let mut response = self.exchange(ins, data)?;
+        let mut out = Vec::new();
+        loop {
+            match response.status().sw2() {
+                OPERATION_OK => {
+                    out.extend_from_slice(response.data());
+                    return Ok(out);
+                }
+                ADDITIONAL_FRAME if ins != AUTHENTICATE_AES => {
+                    out.extend_from_slice(response.data());
+                    response = self.exchange(ADDITIONAL_FRAME, &[])?;
+                }
+                status => return Err(DesfireError::Status(status)),
+            }
+        }
+    }
+
+    fn exchange(&mut self, ins: u8, data: &[u8]) -> Result<Response, DesfireError> {
+        let mut command = Command::new(CLA, ins, 0x00, 0x00).le(256);
+        if !data.is_empty() {
+            command = command.data(data)?;
+        }
+        let bytes = self
+            .kernel
+            .transceive(&command.encode())
+            .map_err(|_| DesfireError::Kernel)?;
+        let response = Response::parse(&bytes)?;
+        if response.status().sw1() != 0x91 {
+            return Err(DesfireError::Apdu(ApduError::Status(response.status().0)));
+        }
+        Ok(response)
+    }
+}
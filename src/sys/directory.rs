@@ -0,0 +1,206 @@
+//! Reconciling card roles against an organization's directory, i.e. LDAP or SCIM.
+//!
+//! Cards are matched to directory entries by their holder's employee number, and
+//! directory groups are mapped to the roles cards are assigned.
+use alloc::{collections::btree_map::BTreeMap, string::String, vec::Vec};
+use core::fmt;
+
+use crate::{
+    role::{RoleId, RoleSet},
+    Kernel, NfcService, Timestamp, Uid,
+};
+
+#[cfg(all(feature = "std", feature = "json"))]
+mod scim;
+
+#[cfg(all(feature = "std", feature = "json"))]
+#[allow(unused_imports)]
+pub use scim::{ScimDirectory, ScimError};
+
+/// A person as the directory knows them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryEntry {
+    pub employee: u32,
+    /// The names of the groups this person is a member of.
+    pub groups: Vec<String>,
+    /// Whether the account is enabled, disabled accounts get their cards revoked.
+    pub active: bool,
+}
+
+/// A source of [`DirectoryEntry`]s, see [`ScimDirectory`] for SCIM servers.
+pub trait Directory {
+    type Error: fmt::Debug;
+
+    /// Fetch every entry of the directory.
+    fn entries(&mut self) -> Result<Vec<DirectoryEntry>, Self::Error>;
+}
+
+/// Which role each directory group grants.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GroupMapping {
+    groups: BTreeMap<String, RoleId>,
+}
+
+#[allow(dead_code)]
+impl GroupMapping {
+    /// Create a new empty `GroupMapping`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            groups: BTreeMap::new(),
+        }
+    }
+
+    /// Grant members of `group` a role, returning the role it granted before.
+    pub fn map(&mut self, group: impl Into<String>, role: RoleId) -> Option<RoleId> {
+        self.groups.insert(group.into(), role)
+    }
+
+    /// Stop granting a role to the members of `group`.
+    pub fn unmap(&mut self, group: &str) -> Option<RoleId> {
+        self.groups.remove(group)
+    }
+
+    /// The roles granted to members of `groups`.
+    pub fn roles<S: AsRef<str>>(&self, groups: &[S]) -> RoleSet {
+        let mut roles = RoleSet::empty();
+        for role in groups.iter().filter_map(|g| self.groups.get(g.as_ref())) {
+            let _ = roles.insert(*role);
+        }
+        roles
+    }
+
+    /// Every role managed through the directory.
+    pub fn managed(&self) -> RoleSet {
+        let mut roles = RoleSet::empty();
+        for role in self.groups.values() {
+            let _ = roles.insert(*role);
+        }
+        roles
+    }
+}
+
+/// What [`NfcService::reconcile`] changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reconciliation {
+    /// The cards whose roles changed.
+    pub updated: Vec<Uid>,
+    /// The cards revoked as their holder left the directory or was disabled.
+    pub revoked: Vec<Uid>,
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Bring the roles of cards in line with their holders' directory groups.
+    ///
+    /// Only the roles in `mapping` are managed, roles assigned by hand are left alone.
+    /// Cards of holders missing from the directory or disabled in it are revoked, cards
+    /// without an employee number aren't touched.
+    pub fn reconcile(
+        &mut self,
+        entries: &[DirectoryEntry],
+        mapping: &GroupMapping,
+    ) -> Reconciliation {
+        let entries = entries
+            .iter()
+            .map(|entry| (entry.employee, entry))
+            .collect::<BTreeMap<_, _>>();
+        let managed = mapping.managed();
+        let holders = self
+            .cards
+            .values()
+            .filter_map(|card| Some((card.id, card.roles, card.holder.employee()?)))
+            .collect::<Vec<_>>();
+
+        let mut reconciliation = Reconciliation::default();
+        for (id, roles, employee) in holders {
+            let Some(entry) = entries.get(&employee).filter(|entry| entry.active) else {
+                if self.revoke(id) {
+                    reconciliation.revoked.push(id);
+                }
+                continue;
+            };
+
+            let wanted = mapping.roles(&entry.groups);
+            let mut changed = false;
+            for role in managed.iter() {
+                let result = match (roles.contains(role), wanted.contains(role)) {
+                    (false, true) => self.assign_role(id, role),
+                    (true, false) => self.unassign_role(id, role),
+                    _ => continue,
+                };
+                changed |= result.is_ok();
+            }
+            if changed {
+                reconciliation.updated.push(id);
+            }
+        }
+        reconciliation
+    }
+}
+
+/// Reconciles a service against a directory every `interval` seconds.
+#[derive(Debug)]
+pub struct DirectorySync<D> {
+    directory: D,
+    mapping: GroupMapping,
+    interval: u64,
+    last: Option<Timestamp>,
+}
+
+#[allow(dead_code)]
+impl<D: Directory> DirectorySync<D> {
+    /// Create a new DirectorySync mapping groups to roles with `mapping`.
+    #[inline]
+    pub const fn new(directory: D, mapping: GroupMapping, interval: u64) -> Self {
+        Self {
+            directory,
+            mapping,
+            interval,
+            last: None,
+        }
+    }
+
+    #[inline]
+    pub const fn mapping(&self) -> &GroupMapping {
+        &self.mapping
+    }
+
+    #[inline]
+    pub fn mapping_mut(&mut self) -> &mut GroupMapping {
+        &mut self.mapping
+    }
+
+    /// Give the directory back.
+    #[inline]
+    pub fn into_inner(self) -> D {
+        self.directory
+    }
+
+    /// Reconcile if `interval` elapsed since the last attempt, returning `None` otherwise.
+    pub fn poll<K: Kernel>(
+        &mut self,
+        nfc: &mut NfcService<K>,
+    ) -> Option<Result<Reconciliation, D::Error>> {
+        let now = nfc.now();
+        match self.last {
+            Some(at) if now.saturating_sub(at) < self.interval => None,
+            _ => Some(self.reconcile(nfc)),
+        }
+    }
+
+    /// Fetch the directory and reconcile the service against it.
+    ///
+    /// Nothing is changed if the directory can't be fetched.
+    pub fn reconcile<K: Kernel>(
+        &mut self,
+        nfc: &mut NfcService<K>,
+    ) -> Result<Reconciliation, D::Error> {
+        self.last = Some(nfc.now());
+        let entries = self.directory.entries()?;
+        Ok(nfc.reconcile(&entries, &self.mapping))
+    }
+}
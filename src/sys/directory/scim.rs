@@ -0,0 +1,116 @@
+extern crate std;
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+use std::io;
+
+use serde::Deserialize;
+
+use super::{Directory, DirectoryEntry};
+
+#[derive(Deserialize)]
+struct ListResponse {
+    #[serde(rename = "Resources", default)]
+    resources: Vec<User>,
+}
+
+#[derive(Deserialize)]
+struct User {
+    #[serde(default = "active")]
+    active: bool,
+    #[serde(default)]
+    groups: Vec<Group>,
+    /// The enterprise user extension, which carries the employee number.
+    #[serde(rename = "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User")]
+    enterprise: Option<Enterprise>,
+}
+
+#[derive(Deserialize)]
+struct Group {
+    display: String,
+}
+
+#[derive(Deserialize)]
+struct Enterprise {
+    #[serde(rename = "employeeNumber")]
+    employee_number: Option<String>,
+}
+
+const fn active() -> bool {
+    true
+}
+
+/// Errors returned by [`ScimDirectory`].
+#[derive(Debug)]
+pub enum ScimError {
+    /// Fetching the users failed.
+    Io(io::Error),
+    /// The response isn't a SCIM list of users.
+    Malformed(serde_json::Error),
+}
+
+impl fmt::Display for ScimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "IoError({})", err),
+            Self::Malformed(err) => write!(f, "Malformed({})", err),
+        }
+    }
+}
+
+impl core::error::Error for ScimError {}
+
+/// A [`Directory`] reading the users of a SCIM 2.0 server.
+///
+/// `fetch` returns the body of a `GET /Users` list response, over whatever HTTP client
+/// the application uses. Users are matched by the employee number of the enterprise
+/// user extension, users without one are skipped.
+pub struct ScimDirectory<F> {
+    fetch: F,
+}
+
+#[allow(dead_code)]
+impl<F> ScimDirectory<F>
+where
+    F: FnMut() -> io::Result<Vec<u8>>,
+{
+    #[inline]
+    pub const fn new(fetch: F) -> Self {
+        Self { fetch }
+    }
+
+    /// Parse the body of a SCIM list response of users.
+    pub fn parse(body: &[u8]) -> Result<Vec<DirectoryEntry>, ScimError> {
+        let list: ListResponse = serde_json::from_slice(body).map_err(ScimError::Malformed)?;
+        Ok(list
+            .resources
+            .into_iter()
+            .filter_map(|user| {
+                let employee = user.enterprise?.employee_number?.trim().parse().ok()?;
+                Some(DirectoryEntry {
+                    employee,
+                    groups: user.groups.into_iter().map(|g| g.display).collect(),
+                    active: user.active,
+                })
+            })
+            .collect())
+    }
+}
+
+impl<F> fmt::Debug for ScimDirectory<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScimDirectory").finish_non_exhaustive()
+    }
+}
+
+impl<F> Directory for ScimDirectory<F>
+where
+    F: FnMut() -> io::Result<Vec<u8>>,
+{
+    type Error = ScimError;
+
+    fn entries(&mut self) -> Result<Vec<DirectoryEntry>, Self::Error> {
+        let body = (self.fetch)().map_err(ScimError::Io)?;
+        Self::parse(&body)
+    }
+}
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Permissions;
+
+/// The identifier of a door, or any other access point.
+pub type DoorId = u16;
+
+/// Something a card can be presented to in order to get through.
+pub trait AccessPoint {
+    /// The identifier of this access point.
+    fn id(&self) -> DoorId;
+    /// The permissions a card needs to get through.
+    fn required(&self) -> Permissions;
+    /// Whether [`Permissions::ADMIN`] bypasses this access point.
+    ///
+    /// [`Permissions::SUPER_ADMIN`] always does.
+    fn admin_bypass(&self) -> bool {
+        true
+    }
+}
+
+/// A physical door controlled by the service.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Door {
+    id: DoorId,
+    required: Permissions,
+    restricted: bool,
+    /// The seconds a second card has to follow the first one within, if two are required.
+    #[serde(default)]
+    two_person: Option<u64>,
+}
+
+#[allow(dead_code)]
+impl Door {
+    /// Create a new Door which requires [`Permissions::OPEN_DOORS`].
+    #[inline]
+    pub const fn new(id: DoorId) -> Self {
+        Self::requiring(id, Permissions::OPEN_DOORS)
+    }
+
+    /// Create a new Door which requires specific permissions.
+    #[inline]
+    pub const fn requiring(id: DoorId, required: Permissions) -> Self {
+        Self {
+            id,
+            required,
+            restricted: false,
+            two_person: None,
+        }
+    }
+
+    /// Require other permissions to open this Door, keeping its other settings.
+    #[inline]
+    pub const fn with_required(mut self, required: Permissions) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Make this Door restricted. Only [`Permissions::SUPER_ADMIN`] bypasses it.
+    #[inline]
+    pub const fn restricted(mut self) -> Self {
+        self.restricted = true;
+        self
+    }
+
+    #[inline]
+    pub const fn is_restricted(&self) -> bool {
+        self.restricted
+    }
+
+    /// Require two distinct authorized cards, the second presented within `window` seconds
+    /// of the first, before this Door opens.
+    #[inline]
+    pub const fn two_person(mut self, window: u64) -> Self {
+        self.two_person = Some(window);
+        self
+    }
+
+    /// The window of the two-person rule, `None` if one card is enough.
+    #[inline]
+    pub const fn two_person_window(&self) -> Option<u64> {
+        self.two_person
+    }
+}
+
+impl AccessPoint for Door {
+    #[inline]
+    fn id(&self) -> DoorId {
+        self.id
+    }
+
+    #[inline]
+    fn required(&self) -> Permissions {
+        self.required
+    }
+
+    #[inline]
+    fn admin_bypass(&self) -> bool {
+        !self.restricted
+    }
+}
@@ -0,0 +1,50 @@
+//! The two-person rule, doors that only open for two distinct cards presented in turn.
+use crate::{
+    door::DoorId, errors::AccessError, policy::DenyReason, Card, Kernel, NfcService, Timestamp,
+};
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Apply the two-person rule of a door to a card the policy authorized.
+    ///
+    /// The first card is held until the door's window passes, a distinct second card
+    /// within it opens the door. Presenting the first card again restarts the window.
+    pub(crate) fn second_card(
+        &mut self,
+        card: &Card,
+        door_id: DoorId,
+        now: Timestamp,
+    ) -> Result<(), AccessError> {
+        let Some(window) = self
+            .policy
+            .door(door_id)
+            .and_then(|door| door.two_person_window())
+        else {
+            return Ok(());
+        };
+
+        match self.first_cards.get(&door_id) {
+            Some(&(first, at)) if first != card.id && now.saturating_sub(at) <= window => {
+                let _ = self.first_cards.remove(&door_id);
+                Ok(())
+            }
+            _ => {
+                let _ = self.first_cards.insert(door_id, (card.id, now));
+                Err(AccessError::Denied {
+                    door: door_id,
+                    reason: DenyReason::AwaitingSecondCard {
+                        until: now.saturating_add(window),
+                    },
+                })
+            }
+        }
+    }
+
+    /// Forget the card a two-person door is waiting on, i.e. when an operator cancels.
+    pub fn cancel_second_card(&mut self, door_id: DoorId) -> bool {
+        self.first_cards.remove(&door_id).is_some()
+    }
+}
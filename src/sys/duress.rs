@@ -0,0 +1,54 @@
+//! Duress credentials, which open doors as usual while silently raising an alarm.
+use crate::{errors::AccessError, Card, Kernel, NfcService, Uid};
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Mark a registered card as a duress credential of its own.
+    ///
+    /// Every access granted to it emits [`DuressAlarm`](crate::events::NfcEvent::DuressAlarm).
+    pub fn mark_duress(&mut self, card_id: Uid) -> Result<(), AccessError> {
+        if !self.cards.contains_key(&card_id) {
+            return Err(AccessError::Unknown(card_id));
+        }
+        let _ = self.duress.insert(card_id, card_id);
+        Ok(())
+    }
+
+    /// Register `credential` as a secondary duress credential of a card.
+    ///
+    /// The credential is enrolled with the card's permissions and roles, presenting it
+    /// grants access as the card would and emits
+    /// [`DuressAlarm`](crate::events::NfcEvent::DuressAlarm) for the card.
+    pub fn add_duress_credential(
+        &mut self,
+        card_id: Uid,
+        credential: Uid,
+    ) -> Result<Card, AccessError> {
+        let Some(card) = self.cards.get(&card_id) else {
+            return Err(AccessError::Unknown(card_id));
+        };
+
+        let mut duress = *card;
+        duress.id = credential;
+        duress.counter = 0;
+        self.put(duress);
+        let _ = self.duress.insert(credential, card_id);
+        Ok(duress)
+    }
+
+    /// Stop treating a credential as a duress one, returning the card it stood for.
+    ///
+    /// Secondary credentials stay registered, unbind them to stop them granting access.
+    pub fn unmark_duress(&mut self, credential: &Uid) -> Option<Uid> {
+        self.duress.remove(credential)
+    }
+
+    /// The card a duress credential stands for, `None` if it isn't one.
+    #[inline]
+    pub fn duress_of(&self, credential: &Uid) -> Option<Uid> {
+        self.duress.get(credential).copied()
+    }
+}
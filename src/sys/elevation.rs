@@ -0,0 +1,122 @@
+//! Temporary permission elevations, reverted once their time is up.
+use alloc::{collections::btree_map::Entry, vec::Vec};
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit::{AuditAction, Origin},
+    errors::AccessError,
+    Kernel, NfcService, Permissions, Timestamp, Uid,
+};
+
+/// The permissions a card held before it was elevated and until when.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Elevation {
+    /// The card's permissions before it was elevated.
+    pub original: Permissions,
+    /// The permissions the elevation added, which are taken back.
+    pub added: Permissions,
+    /// When the elevation gets reverted.
+    pub until: Timestamp,
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Grant permissions to a registered card for a while, reverting them once `duration`
+    /// elapsed on the service's clock.
+    ///
+    /// Only the permissions the card didn't hold before are taken back, changes made to its
+    /// other permissions in the meantime are kept. Elevating a card again adds to its
+    /// elevation and moves its end.
+    pub fn grant_temporary(
+        &mut self,
+        card_id: Uid,
+        perms: Permissions,
+        duration: Duration,
+    ) -> Result<Elevation, AccessError> {
+        let now = self.now();
+        let until = now.saturating_add(duration.as_secs());
+        let result = match self.cards.get_mut(&card_id) {
+            Some(card) => {
+                let old = card.permissions;
+                let elevation = self
+                    .elevations
+                    .entry(card_id)
+                    .and_modify(|elevation| {
+                        elevation.added.insert(perms.difference(old));
+                        elevation.until = until;
+                    })
+                    .or_insert(Elevation {
+                        original: old,
+                        added: perms.difference(old),
+                        until,
+                    });
+                card.permissions.insert(perms);
+                self.history.record(card_id, now, old, card.permissions);
+                Ok(*elevation)
+            }
+            None => Err(AccessError::Unknown(card_id)),
+        };
+        self.log(
+            card_id,
+            now,
+            Origin::Admin,
+            AuditAction::Elevate(perms),
+            result.map(|_| ()),
+        );
+        if result.is_ok() {
+            self.registry_changed();
+        }
+        result
+    }
+
+    /// The elevation of a card, if it's elevated.
+    #[inline]
+    pub fn elevation(&self, card_id: Uid) -> Option<&Elevation> {
+        self.elevations.get(&card_id)
+    }
+
+    /// Revert every elevation whose time is up, returning how many were.
+    ///
+    /// Elevated cards are reverted as they're presented too, call this periodically to
+    /// keep the registry current in between.
+    pub fn revert_elevations(&mut self) -> usize {
+        let now = self.now();
+        let due: Vec<Uid> = self
+            .elevations
+            .iter()
+            .filter(|(_, elevation)| elevation.until <= now)
+            .map(|(id, _)| *id)
+            .collect();
+        for card_id in &due {
+            self.revert_elevation(*card_id, Origin::Admin, now);
+        }
+        due.len()
+    }
+
+    /// Revert the elevation of a card if its time is up, recording it in the audit log.
+    pub(crate) fn revert_elevation(&mut self, card_id: Uid, origin: Origin, now: Timestamp) {
+        let elevation = match self.elevations.entry(card_id) {
+            Entry::Occupied(elevation) if elevation.get().until <= now => elevation.remove(),
+            _ => return,
+        };
+        let Some(card) = self.cards.get_mut(&card_id) else {
+            return;
+        };
+
+        let old = card.permissions;
+        card.permissions.remove(elevation.added);
+        self.history.record(card_id, now, old, card.permissions);
+        self.log(
+            card_id,
+            now,
+            origin,
+            AuditAction::Revert(elevation.added.intersection(old)),
+            Ok(()),
+        );
+        self.registry_changed();
+    }
+}
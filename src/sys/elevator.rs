@@ -0,0 +1,239 @@
+//! Elevator floor control, where a card is granted the floors it may select rather than a door.
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit::{AuditAction, Origin},
+    door::{AccessPoint, DoorId},
+    errors::AccessError,
+    transaction::Traced,
+    zone::ZoneId,
+    Card, Kernel, NfcService, Permissions, ReaderId,
+};
+
+/// A floor served by an [`Elevator`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Floor {
+    /// The number shown in the car, basements are negative.
+    number: i8,
+    required: Permissions,
+    zone: Option<ZoneId>,
+}
+
+#[allow(dead_code)]
+impl Floor {
+    /// Create a new Floor which requires specific permissions to be selected.
+    #[inline]
+    pub const fn new(number: i8, required: Permissions) -> Self {
+        Self {
+            number,
+            required,
+            zone: None,
+        }
+    }
+
+    /// Place this Floor in a zone, cards admitted through the zone may select it too.
+    #[inline]
+    pub const fn in_zone(mut self, zone: ZoneId) -> Self {
+        self.zone = Some(zone);
+        self
+    }
+
+    #[inline]
+    pub const fn number(&self) -> i8 {
+        self.number
+    }
+
+    #[inline]
+    pub const fn required(&self) -> Permissions {
+        self.required
+    }
+
+    #[inline]
+    pub const fn zone(&self) -> Option<ZoneId> {
+        self.zone
+    }
+}
+
+/// The floors of an [`Elevator`] a card may select, by their index in the car.
+///
+/// Each bit maps to a floor in the order they were added, i.e. to the relays of a
+/// floor control panel.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[serde(transparent)]
+pub struct FloorSet(u64);
+
+#[allow(dead_code)]
+impl FloorSet {
+    /// An empty set.
+    #[inline]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Every floor of an elevator.
+    #[inline]
+    pub fn all(elevator: &Elevator) -> Self {
+        match elevator.floors.len() {
+            Elevator::MAX_FLOORS => Self(u64::MAX),
+            len => Self((1 << len) - 1),
+        }
+    }
+
+    #[inline]
+    pub fn insert(&mut self, index: usize) {
+        if index < Elevator::MAX_FLOORS {
+            self.0 |= 1 << index;
+        }
+    }
+
+    #[inline]
+    pub const fn contains(&self, index: usize) -> bool {
+        index < Elevator::MAX_FLOORS && self.0 & (1 << index) != 0
+    }
+
+    #[inline]
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// The raw bits of this set, one per floor index.
+    #[inline]
+    pub const fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// An iterator over the indexes in this set in ascending order.
+    #[inline]
+    pub fn iter(self) -> impl Iterator<Item = usize> {
+        (0..Elevator::MAX_FLOORS).filter(move |index| self.contains(*index))
+    }
+}
+
+/// An elevator car with a reader, gating which floors can be selected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Elevator {
+    id: DoorId,
+    required: Permissions,
+    restricted: bool,
+    floors: Vec<Floor>,
+}
+
+#[allow(dead_code)]
+impl Elevator {
+    /// The most floors an elevator can serve.
+    pub const MAX_FLOORS: usize = u64::BITS as usize;
+
+    /// Create a new Elevator without any floors which requires [`Permissions::REGULAR`]
+    /// to be used at all.
+    #[inline]
+    pub const fn new(id: DoorId) -> Self {
+        Self {
+            id,
+            required: Permissions::REGULAR,
+            restricted: false,
+            floors: Vec::new(),
+        }
+    }
+
+    /// Require specific permissions to use this Elevator at all.
+    #[inline]
+    pub const fn requiring(mut self, required: Permissions) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Make this Elevator restricted. Only [`Permissions::SUPER_ADMIN`] gets every floor.
+    #[inline]
+    pub const fn restricted(mut self) -> Self {
+        self.restricted = true;
+        self
+    }
+
+    /// Serve a floor, returning its index or `None` if the elevator is full.
+    pub fn add_floor(&mut self, floor: Floor) -> Option<usize> {
+        if self.floors.len() >= Self::MAX_FLOORS {
+            return None;
+        }
+        self.floors.push(floor);
+        Some(self.floors.len() - 1)
+    }
+
+    #[inline]
+    pub fn floors(&self) -> &[Floor] {
+        &self.floors
+    }
+
+    /// The floor at an index of a [`FloorSet`].
+    #[inline]
+    pub fn floor(&self, index: usize) -> Option<&Floor> {
+        self.floors.get(index)
+    }
+}
+
+impl AccessPoint for Elevator {
+    #[inline]
+    fn id(&self) -> DoorId {
+        self.id
+    }
+
+    #[inline]
+    fn required(&self) -> Permissions {
+        self.required
+    }
+
+    #[inline]
+    fn admin_bypass(&self) -> bool {
+        !self.restricted
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Install an elevator, replacing any elevator with the same id.
+    #[inline]
+    pub fn install_elevator(&mut self, elevator: Elevator) -> Option<Elevator> {
+        self.policy.install_elevator(elevator)
+    }
+
+    /// Authorize a card presented to an elevator's reader and decide which floors it may select.
+    pub fn select_floors(
+        &mut self,
+        reader: ReaderId,
+        payload: &Card,
+        elevator_id: DoorId,
+    ) -> Result<FloorSet, Traced<AccessError>> {
+        self.transact_traced(reader, payload.id, |this| {
+            let now = this.now();
+            let result = this.admit(reader, payload, now).and_then(|card| {
+                let floors = this
+                    .policy
+                    .floors(&card, elevator_id, now)
+                    .map_err(|reason| AccessError::Denied {
+                        door: elevator_id,
+                        reason,
+                    })?;
+                Ok((this.take_use(reader, card, now), floors))
+            });
+            let card = result.map(|(card, _)| card);
+            this.report(reader, payload.id, Some(elevator_id), &card, now);
+            this.log(
+                payload.id,
+                now,
+                Origin::Reader(reader),
+                AuditAction::Open(elevator_id),
+                card.map(|_| ()),
+            );
+            result.map(|(_, floors)| floors)
+        })
+    }
+}
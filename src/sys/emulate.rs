@@ -0,0 +1,174 @@
+//! Card emulation, presenting a [`Card`] to another reader as an ISO 14443-4 tag.
+//!
+//! The emulated tag holds a single application selected by [`AID`], whose payload is
+//! the card's serialized bytes read with READ BINARY.
+use alloc::vec::Vec;
+
+use crate::{
+    apdu::{Command, Response, StatusWord},
+    audit::{AuditAction, Origin},
+    errors::{AccessError, EmulationError, KernelError},
+    transaction::Traced,
+    Card, Kernel, NfcService, ReaderId, Uid,
+};
+
+/// The proprietary application identifier of an emulated card.
+pub const AID: [u8; 7] = [0xf0, b'L', b'O', b'W', b'A', 0x00, 0x01];
+
+const SELECT: u8 = 0xa4;
+const READ_BINARY: u8 = 0xb0;
+
+/// The largest offset a short READ BINARY can address.
+const MAX_OFFSET: usize = 0x7fff;
+
+/// A [`Kernel`] whose hardware can act as a tag towards another reader.
+#[allow(unused)]
+pub trait TargetKernel: Kernel {
+    /// Start presenting a tag with `uid` to readers coming into the field.
+    fn init_target(&mut self, uid: Uid) -> Result<(), KernelError>;
+
+    /// Wait for the next command APDU of the remote reader.
+    ///
+    /// Fails with [`KernelError::NoCard`] once the remote reader left the field.
+    fn target_receive(&mut self) -> Result<Vec<u8>, KernelError>;
+
+    /// Answer the last command received with a response APDU.
+    fn target_send(&mut self, response: &[u8]) -> Result<(), KernelError>;
+
+    /// Stop presenting the tag.
+    fn release_target(&mut self) -> Result<(), KernelError>;
+}
+
+/// A card presented through a [`TargetKernel`].
+pub struct Emulator<'a, K: TargetKernel + ?Sized> {
+    kernel: &'a mut K,
+    id: Uid,
+    payload: Vec<u8>,
+    selected: bool,
+}
+
+#[allow(dead_code)]
+impl<'a, K: TargetKernel + ?Sized> Emulator<'a, K> {
+    /// Create a new Emulator presenting `card`.
+    pub fn new(kernel: &'a mut K, card: &Card) -> Result<Self, EmulationError> {
+        let payload = card.try_to_bytes().map_err(EmulationError::Encode)?;
+        if payload.len() > MAX_OFFSET {
+            return Err(EmulationError::TooLarge(payload.len()));
+        }
+        Ok(Self {
+            kernel,
+            id: card.id,
+            payload,
+            selected: false,
+        })
+    }
+
+    /// Answer a single command APDU as the emulated tag would.
+    pub fn respond(&mut self, command: &[u8]) -> Response {
+        let status = |status| Response::new(Vec::new(), status);
+        let Ok(command) = Command::parse(command) else {
+            return status(StatusWord::WRONG_LENGTH);
+        };
+        if command.cla() != 0x00 {
+            return status(StatusWord::CLA_NOT_SUPPORTED);
+        }
+
+        match command.ins() {
+            SELECT if command.p1() == 0x04 => {
+                self.selected = command.payload() == AID;
+                if self.selected {
+                    status(StatusWord::SUCCESS)
+                } else {
+                    status(StatusWord::FILE_NOT_FOUND)
+                }
+            }
+            READ_BINARY if !self.selected => status(StatusWord::CONDITIONS_NOT_SATISFIED),
+            READ_BINARY => {
+                let offset = u16::from_be_bytes([command.p1() & 0x7f, command.p2()]) as usize;
+                let Some(rest) = self.payload.get(offset..) else {
+                    return status(StatusWord::WRONG_OFFSET);
+                };
+                let len = command.expected().unwrap_or(256) as usize;
+                Response::new(rest[..len.min(rest.len())].into(), StatusWord::SUCCESS)
+            }
+            _ => status(StatusWord::INS_NOT_SUPPORTED),
+        }
+    }
+
+    /// Answer the next command of the remote reader. Returns `false` once it left the field.
+    pub fn step(&mut self) -> Result<bool, EmulationError> {
+        let command = match self.kernel.target_receive() {
+            Ok(command) => command,
+            Err(KernelError::NoCard) => return Ok(false),
+            Err(why) => return Err(why.into()),
+        };
+        let response = self.respond(&command).encode();
+        self.kernel.target_send(&response)?;
+        Ok(true)
+    }
+
+    /// Present the card until a remote reader came and left the field.
+    pub fn run(&mut self) -> Result<(), EmulationError> {
+        self.selected = false;
+        self.kernel.init_target(self.id)?;
+        let mut result = Ok(true);
+        while let Ok(true) = result {
+            result = self.step();
+        }
+        self.kernel.release_target()?;
+        result.map(|_| ())
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Read a card emulated by another device in a reader's field and authorize it.
+    ///
+    /// Failing to fetch the card isn't logged, there's no card to attribute it to.
+    pub fn read_emulated(&mut self, reader: ReaderId) -> Result<Card, Traced<AccessError>> {
+        let now = self.now();
+        let card = match self.fetch_emulated(reader) {
+            Ok(card) => card,
+            Err(why) => return Err(self.trace(reader, why)),
+        };
+        self.transact_traced(reader, card.id, |this| {
+            let result = this.decide(reader, &card, now);
+            this.report(reader, card.id, None, &result, now);
+            this.log(
+                card.id,
+                now,
+                Origin::Reader(reader),
+                AuditAction::Read,
+                result.map(|_| ()),
+            );
+            result
+        })
+    }
+
+    fn fetch_emulated(&mut self, reader: ReaderId) -> Result<Card, AccessError> {
+        let select = Command::select(&AID).map_err(|_| AccessError::Kernel)?;
+        self.exchange(reader, &select)?
+            .success()
+            .map_err(|_| AccessError::Kernel)?;
+
+        let mut payload = Vec::new();
+        loop {
+            let [p1, p2] = (payload.len() as u16).to_be_bytes();
+            let read = Command::new(0x00, READ_BINARY, p1, p2).le(256);
+            let chunk = self
+                .exchange(reader, &read)?
+                .success()
+                .map_err(|_| AccessError::Kernel)?
+                .into_data();
+            let done = chunk.len() < 256 || payload.len() + chunk.len() > MAX_OFFSET;
+            payload.extend_from_slice(&chunk);
+            if done {
+                break;
+            }
+        }
+        Card::from_bytes(&payload).map_err(|_| AccessError::Kernel)
+    }
+}
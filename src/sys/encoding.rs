@@ -0,0 +1,259 @@
+//! A queue of cards waiting to be encoded, i.e. by a desktop encoder or a card printer.
+//!
+//! Admins enqueue jobs minting a card from a template for a holder, the reader the
+//! encoder is attached as then works through them one blank tag at a time.
+use alloc::collections::btree_map::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit::{AuditAction, Origin},
+    errors::AccessError,
+    events::NfcEvent,
+    holder::Holder,
+    logging::kernel_error,
+    template::CardTemplate,
+    write_verified, Kernel, NfcService, ReaderId, Timestamp, Uid,
+};
+
+/// A handle returned by [`NfcService::enqueue_encoding`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[serde(transparent)]
+pub struct JobId(u32);
+
+#[allow(dead_code)]
+impl JobId {
+    #[inline]
+    pub const fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+/// Where an [`EncodingJob`] is at.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobStatus {
+    /// Waiting for its turn.
+    Queued,
+    /// Failed before and waiting for another attempt.
+    Retrying { attempts: u8, last: AccessError },
+    /// Encoded onto the tag and registered with this UID.
+    Encoded(Uid),
+    /// Failed every attempt.
+    Failed(AccessError),
+}
+
+#[allow(dead_code)]
+impl JobStatus {
+    /// Whether the job is done, encoded or not.
+    #[inline]
+    pub const fn is_finished(&self) -> bool {
+        matches!(self, Self::Encoded(..) | Self::Failed(..))
+    }
+}
+
+/// A card waiting to be encoded.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncodingJob {
+    pub id: JobId,
+    pub template: CardTemplate,
+    pub holder: Holder,
+    pub status: JobStatus,
+    /// When the job was enqueued.
+    pub at: Timestamp,
+}
+
+/// The encoding jobs of a service, oldest first.
+///
+/// Finished jobs are kept for their status until they're cleared.
+#[derive(Debug, Clone)]
+pub struct EncodingQueue {
+    jobs: BTreeMap<JobId, EncodingJob>,
+    next: u32,
+    attempts: u8,
+}
+
+impl Default for EncodingQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
+impl EncodingQueue {
+    /// The attempts a job gets before it fails, unless configured otherwise.
+    pub const DEFAULT_ATTEMPTS: u8 = 3;
+
+    /// Create a new empty `EncodingQueue`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            jobs: BTreeMap::new(),
+            next: 0,
+            attempts: Self::DEFAULT_ATTEMPTS,
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, id: JobId) -> Option<&EncodingJob> {
+        self.jobs.get(&id)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// All the jobs, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &EncodingJob> + '_ {
+        self.jobs.values()
+    }
+
+    /// The jobs still waiting to be encoded, oldest first.
+    pub fn pending(&self) -> impl Iterator<Item = &EncodingJob> + '_ {
+        self.jobs.values().filter(|job| !job.status.is_finished())
+    }
+
+    /// The attempts a job gets before it fails.
+    #[inline]
+    pub const fn attempts(&self) -> u8 {
+        self.attempts
+    }
+
+    /// Drop the finished jobs, returning how many there were.
+    pub fn clear_finished(&mut self) -> usize {
+        let len = self.jobs.len();
+        self.jobs.retain(|_, job| !job.status.is_finished());
+        len - self.jobs.len()
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Queue a card to be minted from a template for a holder and encoded.
+    pub fn enqueue_encoding(&mut self, template: CardTemplate, holder: Holder) -> JobId {
+        let at = self.now();
+        let id = JobId(self.encoding.next);
+        self.encoding.next = self.encoding.next.wrapping_add(1);
+        let _ = self.encoding.jobs.insert(
+            id,
+            EncodingJob {
+                id,
+                template,
+                holder,
+                status: JobStatus::Queued,
+                at,
+            },
+        );
+        id
+    }
+
+    /// An immutable reference to the encoding jobs.
+    #[inline]
+    pub fn encoding_jobs(&self) -> &EncodingQueue {
+        &self.encoding
+    }
+
+    /// Remove a job, whether it's finished or not.
+    pub fn cancel_encoding(&mut self, job: JobId) -> Option<EncodingJob> {
+        self.encoding.jobs.remove(&job)
+    }
+
+    /// Drop the finished jobs, returning how many there were.
+    #[inline]
+    pub fn clear_encoded(&mut self) -> usize {
+        self.encoding.clear_finished()
+    }
+
+    /// Set the attempts a job gets before it fails, at least one.
+    #[inline]
+    pub fn set_encoding_attempts(&mut self, attempts: u8) {
+        self.encoding.attempts = attempts.max(1);
+    }
+
+    /// Encode the oldest pending job onto the blank tag in a reader's field.
+    ///
+    /// Every write is verified by reading the tag back. A failed job is retried by the next
+    /// call until it runs out of attempts. Returns `None` without attempting anything if
+    /// no job is pending, the reader isn't attached or there's no blank tag in its field,
+    /// tags of registered cards are never overwritten.
+    pub fn encode_next(&mut self, reader: ReaderId) -> Option<(JobId, JobStatus)> {
+        let job = *self.encoding.pending().next()?;
+        let id = match self.readers.get_mut(&reader)?.sense() {
+            Ok(Some(id)) if !self.cards.contains_key(&id) => id,
+            Ok(..) => return None,
+            Err(why) => {
+                let reason = kernel_error(reader, why);
+                return Some((job.id, self.encoding_failed(reader, job, reason)));
+            }
+        };
+
+        self.transact(reader, id, |this| this.encode(reader, job, id))
+    }
+
+    /// Encode a job onto the blank tag with this UID.
+    fn encode(
+        &mut self,
+        reader: ReaderId,
+        job: EncodingJob,
+        id: Uid,
+    ) -> Option<(JobId, JobStatus)> {
+        let now = self.now();
+        let card = job.template.mint(id, now).with_holder(job.holder);
+        let kernel = self.readers.get_mut(&reader)?;
+        let result = write_verified(kernel, reader, &card);
+        self.log(id, now, Origin::Reader(reader), AuditAction::Write, result);
+
+        let Err(reason) = result else {
+            self.put(card);
+            self.emit(NfcEvent::CardEncoded {
+                reader,
+                job: job.id,
+                id,
+            });
+            let status = JobStatus::Encoded(id);
+            if let Some(queued) = self.encoding.jobs.get_mut(&job.id) {
+                queued.status = status;
+            }
+            return Some((job.id, status));
+        };
+        Some((job.id, self.encoding_failed(reader, job, reason)))
+    }
+
+    /// Count a failed attempt of a job, failing it for good once it's out of attempts.
+    fn encoding_failed(
+        &mut self,
+        reader: ReaderId,
+        job: EncodingJob,
+        reason: AccessError,
+    ) -> JobStatus {
+        let attempts = match job.status {
+            JobStatus::Retrying { attempts, .. } => attempts.saturating_add(1),
+            _ => 1,
+        };
+        let status = if attempts >= self.encoding.attempts {
+            self.emit(NfcEvent::EncodingFailed {
+                reader,
+                job: job.id,
+                reason,
+            });
+            JobStatus::Failed(reason)
+        } else {
+            JobStatus::Retrying {
+                attempts,
+                last: reason,
+            }
+        };
+        if let Some(queued) = self.encoding.jobs.get_mut(&job.id) {
+            queued.status = status;
+        }
+        status
+    }
+}
@@ -0,0 +1,178 @@
+//! Bulk enrollment of employees from a CSV list, i.e. for an initial deployment.
+//!
+//! Every row holds an employee's number, name, position and role, in that order:
+//!
+//! ```text
+//! id,name,position,role
+//! 812,"Doe, Jane",Manager,Security
+//! 813,John Roe,Coordinator,
+//! ```
+//!
+//! A header row starting with `id`, blank lines and `#` comments are skipped. Fields may be
+//! quoted, with `""` for a quote inside one. The role may be left empty.
+use alloc::{string::String, vec::Vec};
+use std::io::{self, BufRead};
+
+use crate::{
+    errors::EnrollError, holder::Holder, role::RoleId, template::CardTemplate, Card, Kernel,
+    NfcService, Position,
+};
+
+/// A row that failed validation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RowError {
+    /// The line of the row, starting at 1.
+    pub line: usize,
+    pub error: EnrollError,
+}
+
+/// What [`NfcService::import_csv`] enrolled.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Enrollment {
+    /// The cards issued, in the order of their rows.
+    pub issued: Vec<Card>,
+    /// The rows skipped, in order.
+    pub errors: Vec<RowError>,
+}
+
+/// A validated row.
+struct Row {
+    employee: u32,
+    name: String,
+    position: Position,
+    role: Option<RoleId>,
+}
+
+/// Split a line into its fields, unquoting them.
+fn fields(line: &str) -> Result<Vec<String>, EnrollError> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                let _ = chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (false, '"') if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            (false, ',') => fields.push(core::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(EnrollError::UnclosedQuote);
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+/// Parse a position by its name, ignoring case.
+fn position(name: &str) -> Option<Position> {
+    match name.to_ascii_lowercase().as_str() {
+        "manager" => Some(Position::Manager),
+        "director" => Some(Position::Director),
+        "coordinator" => Some(Position::Coordinator),
+        _ => None,
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Enroll the employees of a CSV list, minting a card from `template` for each.
+    ///
+    /// Each card takes its holder's position and role from the row, on top of the
+    /// template's, and is granted the position's defaults, see
+    /// [`NfcService::position_defaults`]. Rows failing validation are skipped and reported, the other rows are
+    /// still enrolled. Fails only if the list can't be read, keeping the cards issued so far.
+    pub fn import_csv<R: BufRead>(
+        &mut self,
+        reader: R,
+        template: &CardTemplate,
+    ) -> io::Result<Enrollment> {
+        let mut enrollment = Enrollment::default();
+        let mut header = true;
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let row = fields(line).and_then(|fields| {
+                if core::mem::take(&mut header) && fields[0].trim().eq_ignore_ascii_case("id") {
+                    return Ok(None);
+                }
+                self.validate(&fields).map(Some)
+            });
+
+            match row {
+                Ok(Some(row)) => enrollment.issued.push(self.enroll(row, template)),
+                Ok(None) => {}
+                Err(error) => enrollment.errors.push(RowError {
+                    line: index + 1,
+                    error,
+                }),
+            }
+        }
+        Ok(enrollment)
+    }
+
+    fn validate(&self, fields: &[String]) -> Result<Row, EnrollError> {
+        let [employee, name, position_name, role] = fields else {
+            return Err(EnrollError::Columns {
+                found: fields.len(),
+            });
+        };
+
+        let employee = employee
+            .trim()
+            .parse()
+            .map_err(|_| EnrollError::InvalidEmployee)?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(EnrollError::MissingName);
+        }
+        let position = position(position_name.trim()).ok_or(EnrollError::UnknownPosition)?;
+        let role = match role.trim() {
+            "" => None,
+            role => match self.policy.roles().find(role) {
+                Some((id, _)) => Some(id),
+                None => return Err(EnrollError::UnknownRole),
+            },
+        };
+        if self.find_employee(employee).is_some() {
+            return Err(EnrollError::Enrolled(employee));
+        }
+
+        Ok(Row {
+            employee,
+            name: name.into(),
+            position,
+            role,
+        })
+    }
+
+    fn enroll(&mut self, row: Row, template: &CardTemplate) -> Card {
+        let holder = Holder::EMPTY
+            .with_name(&row.name)
+            .with_employee(row.employee);
+        let mut card = template
+            .mint(self.free_uid(), self.now())
+            .with_position(row.position)
+            .with_holder(holder);
+        card.permissions
+            .insert(self.position_defaults.get(row.position));
+        if let Some(role) = row.role {
+            let _ = card.roles.insert(role);
+        }
+        self.put(card);
+        card
+    }
+}
@@ -1,40 +1,916 @@
+use alloc::{string::String, vec::Vec};
 use core::fmt;
 
-#[derive(Debug, Copy, Clone)]
-pub struct ConversionError<'a> {
-    pub message: &'a str,
-    pub bytes: &'a [u8],
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    door::DoorId, escalation::RequestId, policy::DenyReason, role::RoleId, schema::SchemaVersion,
+    session::SessionState, store::StoreError, zone::ZoneId, ReaderId, Uid,
+};
+
+/// The kind of failure the deserializer ran into.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Category {
+    /// The input isn't syntactically valid.
+    Syntax,
+    /// The input is valid but doesn't match the expected type.
+    Data,
+    /// The input ended unexpectedly.
+    Eof,
+    /// Reading the input failed.
+    Io,
 }
 
-impl<'a> ConversionError<'a> {
-    pub const fn new(message: &'a str, bytes: &'a [u8]) -> Self {
-        Self { message, bytes }
+#[cfg(feature = "json")]
+impl From<serde_json::error::Category> for Category {
+    #[inline]
+    fn from(category: serde_json::error::Category) -> Self {
+        match category {
+            serde_json::error::Category::Syntax => Self::Syntax,
+            serde_json::error::Category::Data => Self::Data,
+            serde_json::error::Category::Eof => Self::Eof,
+            serde_json::error::Category::Io => Self::Io,
+        }
     }
 }
 
-impl fmt::Display for ConversionError<'_> {
+/// Errors encountered while converting values to and from bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The bytes couldn't be deserialized into `target`.
+    Deserialize {
+        target: &'static str,
+        category: Category,
+        line: usize,
+        column: usize,
+        /// The byte offset the error was found at.
+        offset: usize,
+        /// A copy of at most [`ConversionError::EXCERPT`] bytes around `offset`.
+        excerpt: Vec<u8>,
+    },
+    /// The `target` value couldn't be serialized.
+    Serialize { target: &'static str },
+    /// The bytes were written with a schema `version` that can't be upgraded.
+    Schema {
+        target: &'static str,
+        version: SchemaVersion,
+    },
+    /// The bytes are compressed and don't inflate, or this build can't inflate them
+    /// without the `miniz` feature.
+    Compressed { target: &'static str },
+    /// The checksum of the bytes doesn't match, i.e. bits flipped over the air.
+    Corrupted {
+        target: &'static str,
+        expected: u16,
+        /// The checksum the bytes carry, `None` if they're too short to carry one.
+        found: Option<u16>,
+    },
+}
+
+#[allow(dead_code)]
+impl ConversionError {
+    /// The most bytes of the input kept by [`ConversionError::Deserialize`].
+    pub const EXCERPT: usize = 32;
+
+    /// Record a failure to deserialize JSON `bytes` into `target`.
+    #[cfg(feature = "json")]
+    pub fn deserialize(target: &'static str, error: &serde_json::Error, bytes: &[u8]) -> Self {
+        let (line, column) = (error.line(), error.column());
+        let line_start = match line {
+            0 | 1 => Some(0),
+            n => bytes
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| **b == b'\n')
+                .nth(n - 2)
+                .map(|(i, _)| i + 1),
+        };
+        let offset = line_start.map_or(bytes.len(), |start| {
+            (start + column.saturating_sub(1)).min(bytes.len())
+        });
+
+        let start = offset.saturating_sub(Self::EXCERPT / 2);
+        let end = (start + Self::EXCERPT).min(bytes.len());
+        Self::Deserialize {
+            target,
+            category: error.classify().into(),
+            line,
+            column,
+            offset,
+            excerpt: bytes[start..end].to_vec(),
+        }
+    }
+
+    /// Record a failure to serialize `target`.
+    #[inline]
+    pub const fn serialize(target: &'static str) -> Self {
+        Self::Serialize { target }
+    }
+
+    /// The type that failed to convert.
+    #[inline]
+    pub const fn target(&self) -> &'static str {
+        match self {
+            Self::Deserialize { target, .. }
+            | Self::Serialize { target }
+            | Self::Schema { target, .. }
+            | Self::Compressed { target }
+            | Self::Corrupted { target, .. } => target,
+        }
+    }
+}
+
+impl fmt::Display for ConversionError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        self.message.fmt(f)
+        match self {
+            Self::Deserialize {
+                target,
+                category,
+                line,
+                column,
+                ..
+            } => write!(
+                f,
+                "DeserializeError(target: {}, category: {:?}, line: {}, column: {})",
+                target, category, line, column
+            ),
+            Self::Serialize { target } => write!(f, "SerializeError(target: {})", target),
+            Self::Schema { target, version } => {
+                write!(f, "SchemaError(target: {}, version: {})", target, version)
+            }
+            Self::Compressed { target } => write!(f, "CompressedError(target: {})", target),
+            Self::Corrupted {
+                target,
+                expected,
+                found: Some(found),
+            } => write!(
+                f,
+                "Corrupted(target: {}, expected: {}, found: {})",
+                target, expected, found
+            ),
+            Self::Corrupted {
+                target,
+                expected,
+                found: None,
+            } => write!(f, "Corrupted(target: {}, expected: {})", target, expected),
+        }
+    }
+}
+
+impl core::error::Error for ConversionError {}
+
+/// Errors reported by a [`Kernel`](crate::Kernel) driving the reader hardware.
+#[non_exhaustive]
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum KernelError {
+    /// Reading from the tag failed with a device specific status.
+    Read { status: u16 },
+    /// Writing to the tag failed with a device specific status.
+    Write { status: u16 },
+    /// The tag or reader didn't answer in time.
+    Timeout,
+    /// No tag is present in the field.
+    NoCard,
+    /// A frame was received with a bad CRC.
+    Crc,
+    /// The tag refused the presented key.
+    Auth,
+    /// The link to the reader failed with a device specific status.
+    Transport { status: u16 },
+    /// The kernel doesn't support the requested operation.
+    Unsupported(&'static str),
+}
+
+#[allow(dead_code)]
+impl KernelError {
+    /// A stable numeric code identifying the kind of error.
+    #[inline]
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::Read { .. } => 1,
+            Self::Write { .. } => 2,
+            Self::Timeout => 3,
+            Self::NoCard => 4,
+            Self::Crc => 5,
+            Self::Auth => 6,
+            Self::Transport { .. } => 7,
+            Self::Unsupported(..) => 8,
+        }
+    }
+
+    /// The device specific status of the error, if any.
+    #[inline]
+    pub const fn status(&self) -> Option<u16> {
+        match *self {
+            Self::Read { status } | Self::Write { status } | Self::Transport { status } => {
+                Some(status)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether trying again may succeed, i.e. the tag was moved back into the field.
+    #[inline]
+    pub const fn is_transient(&self) -> bool {
+        !matches!(self, Self::Auth | Self::Unsupported(..))
+    }
+}
+
+impl fmt::Display for KernelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Read { status } => write!(f, "ReadError(status: {})", status),
+            Self::Write { status } => write!(f, "WriteError(status: {})", status),
+            Self::Timeout => write!(f, "Timeout"),
+            Self::NoCard => write!(f, "NoCard"),
+            Self::Crc => write!(f, "CrcError"),
+            Self::Auth => write!(f, "AuthenticationError"),
+            Self::Transport { status } => write!(f, "TransportError(status: {})", status),
+            Self::Unsupported(operation) => write!(f, "Unsupported(operation: {})", operation),
+        }
+    }
+}
+
+impl core::error::Error for KernelError {}
+
+/// The errors of every attempt at a read or write of a tag, see
+/// [`RetryPolicy`](crate::retry::RetryPolicy).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryError {
+    /// The error of each attempt in order, never empty.
+    pub attempts: Vec<KernelError>,
+}
+
+#[allow(dead_code)]
+impl RetryError {
+    /// The error the last attempt failed with.
+    #[inline]
+    pub fn last(&self) -> KernelError {
+        self.attempts[self.attempts.len() - 1]
+    }
+}
+
+impl fmt::Display for RetryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RetryError(attempts: [")?;
+        for (i, why) in self.attempts.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", why)?;
+        }
+        write!(f, "])")
+    }
+}
+
+impl core::error::Error for RetryError {}
+
+/// An error reading or writing a tag, with the attempts made if it failed at the kernel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagError {
+    pub reason: AccessError,
+    /// The attempts of the read or write, if the kernel failed it.
+    pub retry: Option<RetryError>,
+}
+
+impl From<AccessError> for TagError {
+    #[inline]
+    fn from(reason: AccessError) -> Self {
+        Self {
+            reason,
+            retry: None,
+        }
+    }
+}
+
+impl From<TagError> for AccessError {
+    #[inline]
+    fn from(why: TagError) -> Self {
+        why.reason
+    }
+}
+
+impl fmt::Display for TagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.retry {
+            Some(ref retry) => write!(f, "TagError(reason: {}, retry: {})", self.reason, retry),
+            None => write!(f, "TagError(reason: {})", self.reason),
+        }
+    }
+}
+
+impl core::error::Error for TagError {}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AccessError {
+    /// The card isn't registered with the service.
+    Unknown(Uid),
+    /// The card was revoked.
+    Revoked(Uid),
+    /// The card carried a stale counter, most likely a cloned payload.
+    Replayed { expected: u32, presented: u32 },
+    /// The card expired.
+    Expired { valid_until: u64 },
+    /// The access policy refused to open a door for the card.
+    Denied { door: DoorId, reason: DenyReason },
+    /// The role isn't defined.
+    UnknownRole(RoleId),
+    /// The card or reader is locked out after repeated denials.
+    LockedOut { until: u64 },
+    /// No reader with this id is attached to the service.
+    UnknownReader(ReaderId),
+    /// The kernel failed to read or write the card.
+    Kernel,
+    /// The tag failed the challenge-response handshake.
+    Unauthenticated(Uid),
+    /// The card has no accesses left.
+    UsedUp(Uid),
+    /// The card already holds as many permission expiry times as it can.
+    TooManyExpiries(Uid),
+    /// The tag didn't read back as written, it may be left partially written.
+    WriteVerifyFailed(Uid),
+    /// The card isn't revoked.
+    NotRevoked(Uid),
+    /// The response doesn't fit the transport, i.e. too many audit entries for a frame.
+    TooLarge,
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Unknown(id) => write!(f, "UnknownCard(id: {})", id),
+            Self::Revoked(id) => write!(f, "RevokedCard(id: {})", id),
+            Self::Replayed {
+                expected,
+                presented,
+            } => write!(
+                f,
+                "ReplayedCard(expected: {}, presented: {})",
+                expected, presented
+            ),
+            Self::Expired { valid_until } => {
+                write!(f, "ExpiredCard(valid_until: {})", valid_until)
+            }
+            Self::Denied { door, reason } => {
+                write!(f, "AccessDenied(door: {}, reason: {:?})", door, reason)
+            }
+            Self::UnknownRole(id) => write!(f, "UnknownRole(id: {})", id),
+            Self::LockedOut { until } => write!(f, "LockedOut(until: {})", until),
+            Self::UnknownReader(id) => write!(f, "UnknownReader(id: {})", id),
+            Self::Kernel => write!(f, "KernelError"),
+            Self::Unauthenticated(id) => write!(f, "Unauthenticated(id: {})", id),
+            Self::UsedUp(id) => write!(f, "UsedUp(id: {})", id),
+            Self::TooManyExpiries(id) => write!(f, "TooManyExpiries(id: {})", id),
+            Self::WriteVerifyFailed(id) => write!(f, "WriteVerifyFailed(id: {})", id),
+            Self::NotRevoked(id) => write!(f, "NotRevoked(id: {})", id),
+            Self::TooLarge => write!(f, "TooLarge"),
+        }
+    }
+}
+
+/// Errors encountered while decoding NDEF messages.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NdefError {
+    /// The payload ended in the middle of a record.
+    Truncated,
+    /// A text or type field wasn't valid UTF-8.
+    InvalidUtf8,
+    /// The payload uses a feature that isn't supported.
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for NdefError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Truncated => write!(f, "Truncated"),
+            Self::InvalidUtf8 => write!(f, "InvalidUtf8"),
+            Self::Unsupported(what) => write!(f, "Unsupported({})", what),
+        }
+    }
+}
+
+/// Errors encountered while building or exchanging APDUs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ApduError {
+    /// The response was shorter than its status word.
+    Truncated,
+    /// The command data exceeds what an APDU can carry.
+    TooLong,
+    /// The command's length fields don't match its data.
+    Length,
+    /// The tag answered with a non successful status word.
+    Status(u16),
+}
+
+impl fmt::Display for ApduError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Truncated => write!(f, "Truncated"),
+            Self::TooLong => write!(f, "TooLong"),
+            Self::Length => write!(f, "Length"),
+            Self::Status(sw) => write!(f, "Status({:04X})", sw),
+        }
+    }
+}
+
+/// Errors encountered while talking to DESFire tags.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DesfireError {
+    /// The kernel failed to exchange the command.
+    Kernel,
+    /// The response APDU was malformed.
+    Apdu(ApduError),
+    /// The tag answered with a DESFire error status.
+    Status(u8),
+    /// The tag failed to prove it knows the key.
+    Authentication,
+}
+
+impl From<ApduError> for DesfireError {
+    fn from(err: ApduError) -> Self {
+        Self::Apdu(err)
+    }
+}
+
+impl fmt::Display for DesfireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Kernel => write!(f, "KernelError"),
+            Self::Apdu(err) => write!(f, "ApduError({})", err),
+            Self::Status(status) => write!(f, "Status({:02X})", status),
+            Self::Authentication => write!(f, "AuthenticationFailed"),
+        }
+    }
+}
+
+/// Errors encountered while emulating a card.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EmulationError {
+    /// The kernel failed to present the card or exchange with the remote reader.
+    Kernel(KernelError),
+    /// The card's payload couldn't be encoded.
+    Encode(EncodeError),
+    /// The card's payload is larger than READ BINARY can address.
+    TooLarge(usize),
+}
+
+impl From<KernelError> for EmulationError {
+    fn from(err: KernelError) -> Self {
+        Self::Kernel(err)
+    }
+}
+
+impl fmt::Display for EmulationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Kernel(err) => write!(f, "KernelError({:?})", err),
+            Self::Encode(err) => write!(f, "EncodeError({})", err),
+            Self::TooLarge(len) => write!(f, "TooLarge(len: {})", len),
+        }
+    }
+}
+
+impl core::error::Error for EmulationError {}
+
+/// Errors encountered while accessing a region of a card's memory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    /// The layout has no region with the given name.
+    UnknownRegion,
+    /// The data is larger than the region.
+    TooLarge { len: usize, capacity: usize },
+    /// The kernel failed to read or write the region's pages.
+    Kernel(KernelError),
+}
+
+impl From<KernelError> for LayoutError {
+    fn from(err: KernelError) -> Self {
+        Self::Kernel(err)
+    }
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::UnknownRegion => write!(f, "UnknownRegion"),
+            Self::TooLarge { len, capacity } => {
+                write!(f, "TooLarge(len: {}, capacity: {})", len, capacity)
+            }
+            Self::Kernel(err) => write!(f, "KernelError({:?})", err),
+        }
+    }
+}
+
+impl core::error::Error for LayoutError {}
+
+/// Errors returned by the steps of a [`Session`](crate::session::Session).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SessionError {
+    /// The step isn't allowed in the session's state, i.e. a write before authenticating.
+    OutOfOrder {
+        state: SessionState,
+        operation: &'static str,
+    },
+    /// The step failed, ending the session.
+    Access(AccessError),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::OutOfOrder { state, operation } => {
+                write!(
+                    f,
+                    "OutOfOrder(state: {:?}, operation: {})",
+                    state, operation
+                )
+            }
+            Self::Access(err) => write!(f, "AccessError({})", err),
+        }
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-pub enum KernelError<'a> {
-    Write { message: &'a str, code: u16 },
-    Read { message: &'a str, code: u16 },
-    None,
+impl core::error::Error for SessionError {}
+
+/// Errors encountered while talking to FeliCa cards.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FelicaError {
+    /// The kernel failed to exchange the frame.
+    Kernel,
+    /// The response frame was malformed or from another card.
+    Malformed,
+    /// A single command can address between 1 and 15 blocks.
+    TooManyBlocks,
+    /// The card answered with non zero status flags.
+    Status(u8, u8),
 }
 
-impl fmt::Display for KernelError<'_> {
+impl fmt::Display for FelicaError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
-            Self::Read { message, code } => {
-                write!(f, "ReadError(message: {}, code: {})", message, code)
+            Self::Kernel => write!(f, "KernelError"),
+            Self::Malformed => write!(f, "Malformed"),
+            Self::TooManyBlocks => write!(f, "TooManyBlocks"),
+            Self::Status(flag1, flag2) => write!(f, "Status({:02X}, {:02X})", flag1, flag2),
+        }
+    }
+}
+
+/// Errors encountered while decoding serial protocol frames.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FrameError {
+    /// More bytes are needed to complete the frame.
+    Incomplete,
+    /// The bytes don't start with a start of frame marker.
+    Sync,
+    /// The frame's length is out of range.
+    Length(usize),
+    /// The frame's checksum doesn't match its contents.
+    Crc,
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Incomplete => write!(f, "Incomplete"),
+            Self::Sync => write!(f, "Sync"),
+            Self::Length(len) => write!(f, "Length({})", len),
+            Self::Crc => write!(f, "Crc"),
+        }
+    }
+}
+
+/// Errors encountered while decoding OSDP packets.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OsdpError {
+    /// More bytes are needed to complete the packet.
+    Incomplete,
+    /// The bytes don't start with a start of message marker.
+    Sync,
+    /// The packet's length is out of range.
+    Length(usize),
+    /// The packet's CRC or checksum doesn't match its contents.
+    Checksum,
+    /// The packet uses the secure channel, which isn't supported.
+    SecureChannel,
+}
+
+impl fmt::Display for OsdpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Incomplete => write!(f, "Incomplete"),
+            Self::Sync => write!(f, "Sync"),
+            Self::Length(len) => write!(f, "Length({})", len),
+            Self::Checksum => write!(f, "Checksum"),
+            Self::SecureChannel => write!(f, "SecureChannel"),
+        }
+    }
+}
+
+/// Errors encountered while encoding a value into bytes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The output buffer can't hold the encoded value.
+    BufferTooSmall { capacity: usize },
+    /// The value couldn't be serialized.
+    Serialize,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::BufferTooSmall { capacity } => {
+                write!(f, "BufferTooSmall(capacity: {})", capacity)
             }
-            Self::Write { message, code } => {
-                write!(f, "WriteError(message: {}, code: {})", message, code)
+            Self::Serialize => write!(f, "Serialize"),
+        }
+    }
+}
+
+impl core::error::Error for EncodeError {}
+
+/// Errors encountered while decoding the fixed wire representation of a Card.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WireError {
+    /// The bytes don't start with the expected format tag.
+    Format(u8),
+    /// The checksum doesn't match the contents.
+    Checksum { expected: u16, found: u16 },
+    /// The UID length isn't one of 4, 7 or 10 bytes.
+    Uid(u8),
+    /// The permissions contain unknown bits.
+    Permissions(u32),
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Format(tag) => write!(f, "Format(tag: {})", tag),
+            Self::Checksum { expected, found } => {
+                write!(f, "Checksum(expected: {}, found: {})", expected, found)
             }
-            Self::None => write!(f, "None"),
+            Self::Uid(len) => write!(f, "Uid(len: {})", len),
+            Self::Permissions(bits) => write!(f, "Permissions(bits: {})", bits),
         }
     }
 }
+
+impl core::error::Error for WireError {}
+
+/// Errors encountered while updating a reader's firmware.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FirmwareError {
+    /// No reader with this id is attached to the service.
+    UnknownReader(ReaderId),
+    /// The image to write is empty.
+    EmptyImage,
+    /// The reader failed to report its version or accept the image.
+    Kernel(KernelError),
+}
+
+impl fmt::Display for FirmwareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::UnknownReader(reader) => write!(f, "UnknownReader(reader: {})", reader),
+            Self::EmptyImage => write!(f, "EmptyImage"),
+            Self::Kernel(why) => write!(f, "Kernel({})", why),
+        }
+    }
+}
+
+impl core::error::Error for FirmwareError {}
+
+/// Errors encountered while writing a batch of cards.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BatchError {
+    /// The batch was refused before any tag was written.
+    Refused(AccessError),
+    /// Writing a card failed, `restored` tells whether the tags written before it were rolled back.
+    Failed {
+        id: Uid,
+        reason: AccessError,
+        restored: bool,
+    },
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Refused(reason) => write!(f, "Refused(reason: {})", reason),
+            Self::Failed {
+                id,
+                reason,
+                restored,
+            } => write!(
+                f,
+                "Failed(id: {}, reason: {}, restored: {})",
+                id, reason, restored
+            ),
+        }
+    }
+}
+
+/// Errors encountered while parsing [`Permissions`](crate::Permissions) from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermissionsError {
+    /// A name isn't one of the permission flags.
+    UnknownFlag(String),
+}
+
+impl fmt::Display for PermissionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFlag(name) => write!(f, "UnknownFlag(name: {})", name),
+        }
+    }
+}
+
+impl core::error::Error for PermissionsError {}
+
+/// Errors returned when parsing holidays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HolidayError {
+    /// A holiday isn't a valid `YYYY-MM-DD` or `MM-DD` date.
+    InvalidDate(String),
+}
+
+impl fmt::Display for HolidayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidDate(date) => write!(f, "InvalidDate(date: {})", date),
+        }
+    }
+}
+
+impl core::error::Error for HolidayError {}
+
+/// Errors returned when arranging zones.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ZoneError {
+    /// No zone with this id.
+    UnknownZone(ZoneId),
+    /// A zone would be within a zone that isn't of a higher kind.
+    InvalidParent { zone: ZoneId, parent: ZoneId },
+}
+
+impl fmt::Display for ZoneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::UnknownZone(id) => write!(f, "UnknownZone(id: {})", id),
+            Self::InvalidParent { zone, parent } => {
+                write!(f, "InvalidParent(zone: {}, parent: {})", zone, parent)
+            }
+        }
+    }
+}
+
+impl core::error::Error for ZoneError {}
+
+/// Errors returned when acting on an escalation request.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EscalationError {
+    /// No pending request with this id.
+    UnknownRequest(RequestId),
+    /// The approved permissions couldn't be granted.
+    Access(AccessError),
+}
+
+impl fmt::Display for EscalationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::UnknownRequest(id) => write!(f, "UnknownRequest(id: {})", id.get()),
+            Self::Access(reason) => write!(f, "Access(reason: {})", reason),
+        }
+    }
+}
+
+impl core::error::Error for EscalationError {}
+
+/// Errors returned by journaled writes and their recovery.
+#[derive(Debug)]
+pub enum JournalError<E> {
+    /// The journal couldn't be read or written.
+    Store(StoreError<E>),
+    /// The write was refused or failed, `pending` tells whether it's left in the journal.
+    Access { reason: AccessError, pending: bool },
+}
+
+impl<E> From<StoreError<E>> for JournalError<E> {
+    #[inline]
+    fn from(why: StoreError<E>) -> Self {
+        Self::Store(why)
+    }
+}
+
+impl<E: fmt::Debug> fmt::Display for JournalError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Store(why) => write!(f, "Store({})", why),
+            Self::Access { reason, pending } => {
+                write!(f, "Access(reason: {}, pending: {})", reason, pending)
+            }
+        }
+    }
+}
+
+impl<E: fmt::Debug> core::error::Error for JournalError<E> {}
+
+/// Errors returned by a [`SyncClient`](crate::sync::SyncClient), wrapping its transport's.
+#[derive(Debug)]
+pub enum SyncError<E> {
+    /// Pushing the buffered audit entries failed, they're kept for the next sync.
+    Push(E),
+    /// Pulling the server's registry failed.
+    Pull(E),
+}
+
+impl<E: fmt::Debug> fmt::Display for SyncError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Push(err) => write!(f, "Push({:?})", err),
+            Self::Pull(err) => write!(f, "Pull({:?})", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug> core::error::Error for SyncError<E> {}
+
+/// Errors encountered while importing a registry export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    /// The export is too short to hold a header.
+    Truncated,
+    /// The export doesn't start with the expected magic.
+    Magic,
+    /// The export was written in an unsupported format version.
+    Version(u16),
+    /// The body's checksum doesn't match its contents.
+    Checksum { expected: u16, found: u16 },
+    /// The body couldn't be deserialized.
+    Malformed(ConversionError),
+    /// A card is assigned a role the export doesn't define.
+    UnknownRole(RoleId),
+    /// There's no room left to define the imported roles.
+    RolesFull,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "Truncated"),
+            Self::Magic => write!(f, "Magic"),
+            Self::Version(version) => write!(f, "Version({})", version),
+            Self::Checksum { expected, found } => {
+                write!(f, "Checksum(expected: {}, found: {})", expected, found)
+            }
+            Self::Malformed(why) => write!(f, "Malformed({})", why),
+            Self::UnknownRole(role) => write!(f, "UnknownRole(role: {})", role),
+            Self::RolesFull => write!(f, "RolesFull"),
+        }
+    }
+}
+
+impl core::error::Error for ImportError {}
+
+/// Errors found validating a row of an enrollment CSV, see
+/// [`NfcService::import_csv`](crate::NfcService::import_csv).
+#[cfg(feature = "std")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EnrollError {
+    /// The row doesn't have the id, name, position and role columns.
+    Columns {
+        found: usize,
+    },
+    /// A quoted field isn't closed.
+    UnclosedQuote,
+    /// The id isn't an employee number.
+    InvalidEmployee,
+    MissingName,
+    /// The position isn't Manager, Director or Coordinator.
+    UnknownPosition,
+    /// No role with the name is defined.
+    UnknownRole,
+    /// The employee already has a card, or an earlier row enrolled them.
+    Enrolled(u32),
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for EnrollError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Columns { found } => write!(f, "Columns(found: {})", found),
+            Self::UnclosedQuote => write!(f, "UnclosedQuote"),
+            Self::InvalidEmployee => write!(f, "InvalidEmployee"),
+            Self::MissingName => write!(f, "MissingName"),
+            Self::UnknownPosition => write!(f, "UnknownPosition"),
+            Self::UnknownRole => write!(f, "UnknownRole"),
+            Self::Enrolled(employee) => write!(f, "Enrolled(employee: {})", employee),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for EnrollError {}
@@ -0,0 +1,189 @@
+//! Cards requesting elevated permissions, queued until an admin approves or denies them.
+use alloc::collections::btree_map::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit::{AuditAction, Origin},
+    errors::{AccessError, EscalationError},
+    events::NfcEvent,
+    Kernel, NfcService, Permissions, ReaderId, Timestamp, Uid,
+};
+
+/// A handle returned by [`NfcService::request_escalation`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[serde(transparent)]
+pub struct RequestId(u32);
+
+#[allow(dead_code)]
+impl RequestId {
+    #[inline]
+    pub const fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+/// A card's request for additional permissions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Escalation {
+    pub id: RequestId,
+    /// The card asking for the permissions.
+    pub card: Uid,
+    /// The reader the card was presented to when asking.
+    pub reader: ReaderId,
+    pub permissions: Permissions,
+    /// When the request was made.
+    pub at: Timestamp,
+}
+
+/// The escalation requests waiting for an admin, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct EscalationQueue {
+    pending: BTreeMap<RequestId, Escalation>,
+    next: u32,
+}
+
+#[allow(dead_code)]
+impl EscalationQueue {
+    /// Create a new empty `EscalationQueue`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            pending: BTreeMap::new(),
+            next: 0,
+        }
+    }
+
+    fn push(
+        &mut self,
+        card: Uid,
+        reader: ReaderId,
+        permissions: Permissions,
+        at: Timestamp,
+    ) -> RequestId {
+        let id = RequestId(self.next);
+        self.next = self.next.wrapping_add(1);
+        let _ = self.pending.insert(
+            id,
+            Escalation {
+                id,
+                card,
+                reader,
+                permissions,
+                at,
+            },
+        );
+        id
+    }
+
+    #[inline]
+    pub fn get(&self, id: RequestId) -> Option<&Escalation> {
+        self.pending.get(&id)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// The pending requests, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &Escalation> + '_ {
+        self.pending.values()
+    }
+
+    /// The pending requests of a card.
+    pub fn by_card(&self, card_id: Uid) -> impl Iterator<Item = &Escalation> + '_ {
+        self.pending.values().filter(move |e| e.card == card_id)
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Queue a request from a card presented to a reader for additional permissions.
+    ///
+    /// Revoked and unregistered cards can't ask, [`NfcEvent::EscalationRequested`] is emitted
+    /// for the admins to act on otherwise.
+    pub fn request_escalation(
+        &mut self,
+        reader: ReaderId,
+        card_id: Uid,
+        perms: Permissions,
+    ) -> Result<RequestId, AccessError> {
+        let now = self.now();
+        let result = if !self.readers.contains_key(&reader) {
+            Err(AccessError::UnknownReader(reader))
+        } else if self.revoked.is_revoked(card_id) {
+            Err(AccessError::Revoked(card_id))
+        } else if !self.cards.contains_key(&card_id) {
+            Err(AccessError::Unknown(card_id))
+        } else {
+            Ok(self.escalations.push(card_id, reader, perms, now))
+        };
+
+        self.log(
+            card_id,
+            now,
+            Origin::Reader(reader),
+            AuditAction::RequestEscalation(perms),
+            result.map(|_| ()),
+        );
+        if let Ok(request) = result {
+            self.emit(NfcEvent::EscalationRequested {
+                reader,
+                id: card_id,
+                request,
+                permissions: perms,
+            });
+        }
+        result
+    }
+
+    /// An immutable reference to the escalation requests waiting for an admin.
+    #[inline]
+    pub fn escalations(&self) -> &EscalationQueue {
+        &self.escalations
+    }
+
+    /// Approve a request, granting the card its requested permissions.
+    ///
+    /// The request is consumed even if the grant fails, i.e. the card was unbound since.
+    pub fn approve_escalation(
+        &mut self,
+        request: RequestId,
+    ) -> Result<Escalation, EscalationError> {
+        let escalation = self
+            .escalations
+            .pending
+            .remove(&request)
+            .ok_or(EscalationError::UnknownRequest(request))?;
+        self.grant(escalation.card, escalation.permissions)
+            .map_err(EscalationError::Access)?;
+        Ok(escalation)
+    }
+
+    /// Deny a request, leaving the card's permissions untouched.
+    pub fn deny_escalation(&mut self, request: RequestId) -> Result<Escalation, EscalationError> {
+        let escalation = self
+            .escalations
+            .pending
+            .remove(&request)
+            .ok_or(EscalationError::UnknownRequest(request))?;
+        let now = self.now();
+        self.log(
+            escalation.card,
+            now,
+            Origin::Admin,
+            AuditAction::DenyEscalation(escalation.permissions),
+            Ok(()),
+        );
+        Ok(escalation)
+    }
+}
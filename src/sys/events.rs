@@ -0,0 +1,347 @@
+use alloc::boxed::Box;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    door::DoorId, encoding::JobId, errors::AccessError, escalation::RequestId,
+    lockout::LockoutTarget, tamper::Tamper, tour::RouteId, transaction::TransactionId, Permissions,
+    ReaderId, Timestamp, Uid,
+};
+
+/// Events emitted by the NFC service while processing cards.
+///
+/// Events caused by a card at a reader are tagged with the reader they originated from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum NfcEvent {
+    /// A tag was detected in a reader's field.
+    CardDetected { reader: ReaderId, id: Uid },
+    /// A card was registered with the service.
+    CardEnrolled { id: Uid },
+    /// A card was removed from the service.
+    CardRemoved { id: Uid },
+    /// A card was evicted to keep the registry within its capacity.
+    CardEvicted { id: Uid },
+    /// A presented card was granted access, through a door if one was asked for.
+    AccessGranted {
+        reader: ReaderId,
+        id: Uid,
+        door: Option<DoorId>,
+        /// The accesses the card has left, if they're limited.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        remaining: Option<u32>,
+    },
+    /// An authorized card was presented to a two-person door, which waits for a second
+    /// card until `until`.
+    AwaitingSecondCard {
+        reader: ReaderId,
+        id: Uid,
+        door: DoorId,
+        until: u64,
+    },
+    /// A presented card was denied access.
+    AccessDenied {
+        reader: ReaderId,
+        id: Uid,
+        door: Option<DoorId>,
+        reason: AccessError,
+    },
+    /// Writing a card to its tag failed.
+    WriteFailed {
+        reader: ReaderId,
+        id: Uid,
+        reason: AccessError,
+    },
+    /// A card or reader got locked out after repeated denials.
+    Lockout {
+        reader: ReaderId,
+        target: LockoutTarget,
+        until: u64,
+    },
+    /// A card was presented with a counter behind the one on record.
+    ///
+    /// The genuine card always carries the latest counter, so this is most
+    /// likely a payload copied from an earlier read.
+    ClonedCard {
+        reader: ReaderId,
+        id: Uid,
+        expected: u32,
+        presented: u32,
+    },
+    /// A revoked card was presented.
+    CardRevoked { reader: ReaderId, id: Uid },
+    /// An expired card was presented.
+    CardExpired {
+        reader: ReaderId,
+        id: Uid,
+        valid_until: u64,
+    },
+    /// An expired card was granted access within the service's expiry grace period.
+    CardInGrace {
+        reader: ReaderId,
+        id: Uid,
+        valid_until: u64,
+    },
+    /// A card asked for additional permissions, waiting for an admin to act on it.
+    EscalationRequested {
+        reader: ReaderId,
+        id: Uid,
+        request: RequestId,
+        permissions: Permissions,
+    },
+    /// A duress credential was presented and granted access as usual, `id` is the card
+    /// it stands for.
+    DuressAlarm {
+        reader: ReaderId,
+        id: Uid,
+        credential: Uid,
+    },
+    /// An encoding job was written to a blank tag and the card registered.
+    CardEncoded {
+        reader: ReaderId,
+        job: JobId,
+        id: Uid,
+    },
+    /// A guard tapped a checkpoint other than the next one of their patrol.
+    CheckpointOutOfOrder {
+        reader: ReaderId,
+        id: Uid,
+        expected: ReaderId,
+    },
+    /// A checkpoint of a guard's patrol was skipped or not tapped by `due`.
+    CheckpointMissed { reader: ReaderId, id: Uid, due: u64 },
+    /// A guard's patrol went past its last checkpoint.
+    PatrolCompleted { id: Uid, route: RouteId },
+    /// A reader was tampered with, its doors are locked down.
+    TamperDetected { reader: ReaderId, tamper: Tamper },
+    /// An encoding job failed every attempt.
+    EncodingFailed {
+        reader: ReaderId,
+        job: JobId,
+        reason: AccessError,
+    },
+    /// A fire alarm was asserted, its doors are emergency unlocked.
+    FireAlarmRaised,
+    /// All fire alarms cleared, the doors they unlocked are reverted.
+    FireAlarmCleared,
+}
+
+#[allow(dead_code)]
+impl NfcEvent {
+    /// The reader this event originated from, `None` for administrative events.
+    pub const fn reader(&self) -> Option<ReaderId> {
+        match *self {
+            Self::CardEnrolled { .. }
+            | Self::CardRemoved { .. }
+            | Self::CardEvicted { .. }
+            | Self::PatrolCompleted { .. }
+            | Self::FireAlarmRaised
+            | Self::FireAlarmCleared => None,
+            Self::CardDetected { reader, .. }
+            | Self::AccessGranted { reader, .. }
+            | Self::AccessDenied { reader, .. }
+            | Self::AwaitingSecondCard { reader, .. }
+            | Self::WriteFailed { reader, .. }
+            | Self::Lockout { reader, .. }
+            | Self::ClonedCard { reader, .. }
+            | Self::CardRevoked { reader, .. }
+            | Self::CardExpired { reader, .. }
+            | Self::CardInGrace { reader, .. }
+            | Self::EscalationRequested { reader, .. }
+            | Self::DuressAlarm { reader, .. }
+            | Self::CardEncoded { reader, .. }
+            | Self::CheckpointOutOfOrder { reader, .. }
+            | Self::CheckpointMissed { reader, .. }
+            | Self::TamperDetected { reader, .. }
+            | Self::EncodingFailed { reader, .. } => Some(reader),
+        }
+    }
+
+    /// Whether this event reports an access decision, which is reported as an
+    /// [`AccessEvent`] as well.
+    #[inline]
+    pub const fn is_access(&self) -> bool {
+        matches!(self, Self::AccessGranted { .. } | Self::AccessDenied { .. })
+    }
+
+    /// The name of this event, as used when serializing it.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::CardDetected { .. } => "card_detected",
+            Self::CardEnrolled { .. } => "card_enrolled",
+            Self::CardRemoved { .. } => "card_removed",
+            Self::CardEvicted { .. } => "card_evicted",
+            Self::AccessGranted { .. } => "access_granted",
+            Self::AccessDenied { .. } => "access_denied",
+            Self::AwaitingSecondCard { .. } => "awaiting_second_card",
+            Self::WriteFailed { .. } => "write_failed",
+            Self::Lockout { .. } => "lockout",
+            Self::ClonedCard { .. } => "cloned_card",
+            Self::CardRevoked { .. } => "card_revoked",
+            Self::CardExpired { .. } => "card_expired",
+            Self::CardInGrace { .. } => "card_in_grace",
+            Self::EscalationRequested { .. } => "escalation_requested",
+            Self::DuressAlarm { .. } => "duress_alarm",
+            Self::CardEncoded { .. } => "card_encoded",
+            Self::CheckpointOutOfOrder { .. } => "checkpoint_out_of_order",
+            Self::CheckpointMissed { .. } => "checkpoint_missed",
+            Self::PatrolCompleted { .. } => "patrol_completed",
+            Self::TamperDetected { .. } => "tamper_detected",
+            Self::EncodingFailed { .. } => "encoding_failed",
+            Self::FireAlarmRaised => "fire_alarm_raised",
+            Self::FireAlarmCleared => "fire_alarm_cleared",
+        }
+    }
+
+    /// The card this event is about, `None` for reader lockouts, tampers, failed encodings
+    /// and alarms.
+    pub const fn card(&self) -> Option<Uid> {
+        match *self {
+            Self::Lockout {
+                target: LockoutTarget::Card(id),
+                ..
+            }
+            | Self::CardDetected { id, .. }
+            | Self::CardEnrolled { id }
+            | Self::CardRemoved { id }
+            | Self::CardEvicted { id }
+            | Self::AccessGranted { id, .. }
+            | Self::AccessDenied { id, .. }
+            | Self::AwaitingSecondCard { id, .. }
+            | Self::WriteFailed { id, .. }
+            | Self::ClonedCard { id, .. }
+            | Self::CardRevoked { id, .. }
+            | Self::CardExpired { id, .. }
+            | Self::CardInGrace { id, .. }
+            | Self::EscalationRequested { id, .. }
+            | Self::DuressAlarm { id, .. }
+            | Self::CardEncoded { id, .. }
+            | Self::CheckpointOutOfOrder { id, .. }
+            | Self::CheckpointMissed { id, .. }
+            | Self::PatrolCompleted { id, .. } => Some(id),
+            Self::Lockout {
+                target: LockoutTarget::Reader(..),
+                ..
+            }
+            | Self::TamperDetected { .. }
+            | Self::EncodingFailed { .. }
+            | Self::FireAlarmRaised
+            | Self::FireAlarmCleared => None,
+        }
+    }
+}
+
+/// Whether an [`AccessEvent`] granted access.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[serde(rename_all = "snake_case")]
+pub enum AccessDecision {
+    Granted,
+    Denied,
+}
+
+/// An access decision made for a card presented to a reader.
+///
+/// This is the one shape access decisions are reported in, to subscribers through
+/// [`Subscriber::on_access`] and from the audit log through [`AuditLog::access_events`].
+/// It serializes as `{"event":"access","tx_id":..,"uid":..}`.
+///
+/// [`AuditLog::access_events`]: crate::audit::AuditLog::access_events
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[serde(tag = "event", rename = "access")]
+pub struct AccessEvent {
+    /// The transaction the decision was made in.
+    pub tx_id: Option<TransactionId>,
+    pub uid: Uid,
+    pub reader: ReaderId,
+    pub decision: AccessDecision,
+    /// Why access was denied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<AccessError>,
+    pub timestamp: Timestamp,
+}
+
+#[allow(dead_code)]
+impl AccessEvent {
+    /// The access event of the outcome of a decision.
+    pub fn new<T>(
+        tx_id: Option<TransactionId>,
+        uid: Uid,
+        reader: ReaderId,
+        result: &Result<T, AccessError>,
+        timestamp: Timestamp,
+    ) -> Self {
+        let (decision, reason) = match *result {
+            Ok(..) => (AccessDecision::Granted, None),
+            Err(reason) => (AccessDecision::Denied, Some(reason)),
+        };
+        Self {
+            tx_id,
+            uid,
+            reader,
+            decision,
+            reason,
+            timestamp,
+        }
+    }
+
+    #[inline]
+    pub const fn is_granted(&self) -> bool {
+        matches!(self.decision, AccessDecision::Granted)
+    }
+}
+
+/// An [`NfcEvent`] along with the transaction it's part of.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TracedEvent {
+    /// The transaction of the reader the event originated from, if it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<TransactionId>,
+    #[serde(flatten)]
+    pub event: NfcEvent,
+}
+
+/// Something that gets notified of every [`NfcEvent`] as it's emitted.
+///
+/// Implemented for closures, i.e. `service.subscribe(|event: &NfcEvent| ..)`.
+pub trait Subscriber: Send {
+    fn on_event(&mut self, event: &NfcEvent);
+
+    /// Get notified of an event along with its transaction.
+    ///
+    /// Subscribers correlating events implement this, it defaults to [`Subscriber::on_event`].
+    #[inline]
+    fn on_traced(&mut self, event: &TracedEvent) {
+        self.on_event(&event.event)
+    }
+
+    /// Get notified of every access decision, alongside the [`NfcEvent::AccessGranted`]
+    /// or [`NfcEvent::AccessDenied`] it's emitted with.
+    ///
+    /// Subscribers ignore these by default.
+    #[inline]
+    fn on_access(&mut self, event: &AccessEvent) {
+        let _ = event;
+    }
+}
+
+impl<F> Subscriber for F
+where
+    F: FnMut(&NfcEvent) + Send,
+{
+    #[inline]
+    fn on_event(&mut self, event: &NfcEvent) {
+        self(event)
+    }
+}
+
+/// A handle returned by [`NfcService::subscribe`](crate::NfcService::subscribe).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SubscriptionId(pub(crate) u32);
+
+/// The boxed subscribers of a service.
+pub(crate) type Subscribers =
+    alloc::collections::btree_map::BTreeMap<SubscriptionId, Box<dyn Subscriber>>;
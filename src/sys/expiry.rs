@@ -0,0 +1,168 @@
+//! Permissions granted for a limited time, i.e. [`Permissions::OPEN_DOORS`] for a contractor.
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit::{AuditAction, Origin},
+    errors::AccessError,
+    Card, Kernel, NfcService, Permissions, Timestamp, Uid,
+};
+
+/// The most distinct expiry times a single card can hold.
+pub const MAX_EXPIRIES: usize = 4;
+
+/// When individual permissions of a card expire.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct PermissionExpiry {
+    entries: [Option<(Permissions, Timestamp)>; MAX_EXPIRIES],
+}
+
+#[allow(dead_code)]
+impl PermissionExpiry {
+    /// No permission expires on its own.
+    pub const NONE: Self = Self {
+        entries: [None; MAX_EXPIRIES],
+    };
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.iter().all(Option::is_none)
+    }
+
+    /// Expire `perms` at `at`, replacing any expiry they had.
+    ///
+    /// Returns `false` if [`MAX_EXPIRIES`] other expiry times are already held.
+    pub fn set(&mut self, perms: Permissions, at: Timestamp) -> bool {
+        self.clear(perms);
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .flatten()
+            .find(|(_, until)| *until == at)
+        {
+            entry.0.insert(perms);
+            return true;
+        }
+        match self.entries.iter_mut().find(|entry| entry.is_none()) {
+            Some(slot) => {
+                *slot = Some((perms, at));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop `perms` from expiring.
+    pub fn clear(&mut self, perms: Permissions) {
+        for slot in self.entries.iter_mut() {
+            if let Some((held, _)) = slot {
+                held.remove(perms);
+                if held.is_empty() {
+                    *slot = None;
+                }
+            }
+        }
+    }
+
+    /// When `perm` expires, if it does.
+    pub fn expires(&self, perm: Permissions) -> Option<Timestamp> {
+        self.iter()
+            .filter(|(perms, _)| perms.intersects(perm))
+            .map(|(_, at)| at)
+            .min()
+    }
+
+    /// The permissions that expired at `now`.
+    pub fn expired(&self, now: Timestamp) -> Permissions {
+        self.iter()
+            .filter(|(_, at)| now >= *at)
+            .fold(Permissions::empty(), |expired, (perms, _)| expired | perms)
+    }
+
+    /// The permissions with an expiry and when they expire.
+    pub fn iter(&self) -> impl Iterator<Item = (Permissions, Timestamp)> + '_ {
+        self.entries.iter().flatten().copied()
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Grant permissions to a registered card until `until`.
+    ///
+    /// Permissions the card already held indefinitely now expire as well.
+    pub fn grant_until(
+        &mut self,
+        card_id: Uid,
+        perms: Permissions,
+        until: Timestamp,
+    ) -> Result<(), AccessError> {
+        let now = self.now();
+        let result = match self.cards.get_mut(&card_id) {
+            Some(card) => {
+                if card.expiry.set(perms, until) {
+                    let old = card.permissions;
+                    card.permissions.insert(perms);
+                    self.history.record(card_id, now, old, card.permissions);
+                    Ok(())
+                } else {
+                    Err(AccessError::TooManyExpiries(card_id))
+                }
+            }
+            None => Err(AccessError::Unknown(card_id)),
+        };
+        self.log(
+            card_id,
+            now,
+            Origin::Admin,
+            AuditAction::Grant(perms),
+            result,
+        );
+        if result.is_ok() {
+            self.registry_changed();
+        }
+        result
+    }
+
+    /// Drop the expired permissions of a registered card, recording them in the audit log.
+    pub(crate) fn expire_permissions(&mut self, card_id: Uid, origin: Origin, now: Timestamp) {
+        let Some(card) = self.cards.get_mut(&card_id) else {
+            return;
+        };
+        let expired = card.expiry.expired(now);
+        if expired.is_empty() {
+            return;
+        }
+
+        let old = card.permissions;
+        card.permissions.remove(expired);
+        card.expiry.clear(expired);
+        self.history.record(card_id, now, old, card.permissions);
+        self.log(
+            card_id,
+            now,
+            origin,
+            AuditAction::Expire(expired.intersection(old)),
+            Ok(()),
+        );
+    }
+}
+
+#[allow(dead_code)]
+impl Card {
+    /// Expire some of this Card's permissions at `at`, see [`PermissionExpiry::set`].
+    #[inline]
+    pub fn with_expiring(mut self, perms: Permissions, at: Timestamp) -> Self {
+        if self.expiry.set(perms, at) {
+            self.permissions.insert(perms);
+        }
+        self
+    }
+
+    /// When this Card's individual permissions expire.
+    #[inline]
+    pub const fn permission_expiry(&self) -> &PermissionExpiry {
+        &self.expiry
+    }
+}
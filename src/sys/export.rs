@@ -0,0 +1,146 @@
+//! A portable export of the card registry, for migrating between installations.
+//!
+//! An export is laid out as `MAGIC | version: u16 | crc: u16 | body`, with the body
+//! holding the cards, roles and revocations in the [`codec`](crate::codec) format and the
+//! CRC-16 covering the body.
+use alloc::{collections::btree_map::BTreeMap, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    codec,
+    crc::crc16,
+    errors::{ConversionError, ImportError},
+    revocation::RevocationList,
+    role::{Role, RoleId, RoleSet},
+    Card, Kernel, NfcService,
+};
+
+/// Marks the start of an export.
+pub const MAGIC: [u8; 4] = *b"LWEX";
+
+/// The version of the export format written by this build.
+pub const VERSION: u16 = 1;
+
+const HEADER: usize = MAGIC.len() + 4;
+
+/// Lay out a body behind a header of `magic`, `version` and the body's CRC.
+pub(crate) fn seal(magic: [u8; 4], version: u16, body: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER + body.len());
+    bytes.extend_from_slice(&magic);
+    bytes.extend_from_slice(&version.to_le_bytes());
+    bytes.extend_from_slice(&crc16(body).to_le_bytes());
+    bytes.extend_from_slice(body);
+    bytes
+}
+
+/// Validate the header of bytes laid out by [`seal`], returning their body.
+pub(crate) fn unseal(magic: [u8; 4], version: u16, bytes: &[u8]) -> Result<&[u8], ImportError> {
+    let (header, body) = bytes
+        .split_at_checked(HEADER)
+        .ok_or(ImportError::Truncated)?;
+    if header[..magic.len()] != magic {
+        return Err(ImportError::Magic);
+    }
+    let found_version = u16::from_le_bytes([header[4], header[5]]);
+    if found_version != version {
+        return Err(ImportError::Version(found_version));
+    }
+    let expected = u16::from_le_bytes([header[6], header[7]]);
+    let found = crc16(body);
+    if expected != found {
+        return Err(ImportError::Checksum { expected, found });
+    }
+    Ok(body)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Body {
+    cards: Vec<Card>,
+    roles: Vec<(RoleId, Role)>,
+    revocations: RevocationList,
+}
+
+/// What an import changed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ImportSummary {
+    pub cards_added: usize,
+    pub cards_replaced: usize,
+    pub roles_defined: usize,
+    pub revocations: usize,
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Export the registered cards, the roles and the revocation list.
+    pub fn export(&self) -> Result<Vec<u8>, ConversionError> {
+        let body = Body {
+            cards: self.cards.values().copied().collect(),
+            roles: self
+                .policy
+                .roles()
+                .iter()
+                .map(|(id, role)| (id, role.clone()))
+                .collect(),
+            revocations: self.revoked.clone(),
+        };
+        let body = codec::to_vec(&body).map_err(|_| ConversionError::serialize("Export"))?;
+        Ok(seal(MAGIC, VERSION, &body))
+    }
+
+    /// Validate an export and merge it into this service.
+    ///
+    /// Roles are matched by name, the ones missing here get defined and the imported
+    /// cards are remapped onto the local role ids. Cards already registered with the
+    /// same id are replaced and revocations are added to the local ones.
+    /// Nothing is changed if the export fails to validate.
+    pub fn import(&mut self, bytes: &[u8]) -> Result<ImportSummary, ImportError> {
+        let body = unseal(MAGIC, VERSION, bytes)?;
+        let body: Body = codec::from_slice("Export", body).map_err(ImportError::Malformed)?;
+
+        // Stage the roles on a copy so a failed import leaves the registry alone.
+        let mut roles = self.policy.roles().clone();
+        let mut mapping = BTreeMap::new();
+        let mut summary = ImportSummary::default();
+        for (id, role) in body.roles {
+            let local = match roles.find(role.name()) {
+                Some((local, _)) => local,
+                None => {
+                    summary.roles_defined += 1;
+                    roles.define(role).ok_or(ImportError::RolesFull)?
+                }
+            };
+            let _ = mapping.insert(id, local);
+        }
+
+        let mut cards = Vec::with_capacity(body.cards.len());
+        for mut card in body.cards {
+            let mut remapped = RoleSet::empty();
+            for role in card.roles.iter() {
+                let local = mapping.get(&role).ok_or(ImportError::UnknownRole(role))?;
+                let _ = remapped.insert(*local);
+            }
+            card.roles = remapped;
+            cards.push(card);
+        }
+
+        *self.policy.roles_mut() = roles;
+        for card in cards {
+            match self.cards.insert(card.id, card) {
+                Some(..) => summary.cards_replaced += 1,
+                None => summary.cards_added += 1,
+            }
+        }
+        for id in body.revocations.iter() {
+            if self.revoked.revoke(id) {
+                summary.revocations += 1;
+            }
+        }
+        self.evict();
+        self.registry_changed();
+        Ok(summary)
+    }
+}
@@ -0,0 +1,156 @@
+//! FeliCa polling and reads/writes of services without encryption.
+use alloc::vec::Vec;
+
+use crate::{
+    errors::{FelicaError, KernelError},
+    Kernel,
+};
+
+/// The size of a single FeliCa block in bytes.
+pub const BLOCK_SIZE: usize = 16;
+
+/// The wildcard system code, any card in the field answers it.
+#[allow(dead_code)]
+pub const ANY_SYSTEM: u16 = 0xffff;
+
+const POLLING: u8 = 0x00;
+const READ_WITHOUT_ENCRYPTION: u8 = 0x06;
+const WRITE_WITHOUT_ENCRYPTION: u8 = 0x08;
+
+/// The maximum number of blocks a single read or write can address.
+const MAX_BLOCKS: usize = 15;
+
+/// The manufacture identifier of a FeliCa card.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Idm(pub [u8; 8]);
+
+/// The manufacture parameters of a FeliCa card.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Pmm(pub [u8; 8]);
+
+/// A [`Kernel`] able to exchange raw FeliCa frames.
+#[allow(unused)]
+pub trait FelicaKernel: Kernel {
+    /// Send a command frame, without its length byte, and return the response frame.
+    fn felica_exchange(&mut self, frame: &[u8]) -> Result<Vec<u8>, KernelError>;
+}
+
+/// A FeliCa card in a reader's field.
+pub struct Felica<'a, K: FelicaKernel + ?Sized> {
+    kernel: &'a mut K,
+}
+
+#[allow(dead_code)]
+impl<'a, K: FelicaKernel + ?Sized> Felica<'a, K> {
+    #[inline]
+    pub fn new(kernel: &'a mut K) -> Self {
+        Self { kernel }
+    }
+
+    /// Poll for a card of a system. Returns `None` if no card answered.
+    pub fn poll(&mut self, system_code: u16) -> Result<Option<(Idm, Pmm)>, FelicaError> {
+        let [hi, lo] = system_code.to_be_bytes();
+        // No request data and a single time slot.
+        let response = match self.kernel.felica_exchange(&[POLLING, hi, lo, 0x00, 0x00]) {
+            Ok(response) => response,
+            Err(..) => return Ok(None),
+        };
+
+        match response.as_slice() {
+            [0x01, rest @ ..] if rest.len() >= 16 => {
+                let mut idm = [0; 8];
+                let mut pmm = [0; 8];
+                idm.copy_from_slice(&rest[..8]);
+                pmm.copy_from_slice(&rest[8..16]);
+                Ok(Some((Idm(idm), Pmm(pmm))))
+            }
+            _ => Err(FelicaError::Malformed),
+        }
+    }
+
+    /// Read blocks of a service which doesn't require encryption.
+    pub fn read(
+        &mut self,
+        idm: Idm,
+        service: u16,
+        blocks: &[u16],
+    ) -> Result<Vec<[u8; BLOCK_SIZE]>, FelicaError> {
+        let frame = Self::frame(READ_WITHOUT_ENCRYPTION, idm, service, blocks)?;
+        let response = self.transact(&frame, READ_WITHOUT_ENCRYPTION + 1, idm)?;
+
+        let (&count, data) = response.split_first().ok_or(FelicaError::Malformed)?;
+        if count as usize != blocks.len() || data.len() < blocks.len() * BLOCK_SIZE {
+            return Err(FelicaError::Malformed);
+        }
+        Ok(data
+            .chunks_exact(BLOCK_SIZE)
+            .take(blocks.len())
+            .map(|chunk| {
+                let mut block = [0; BLOCK_SIZE];
+                block.copy_from_slice(chunk);
+                block
+            })
+            .collect())
+    }
+
+    /// Write blocks of a service which doesn't require encryption.
+    pub fn write(
+        &mut self,
+        idm: Idm,
+        service: u16,
+        blocks: &[(u16, [u8; BLOCK_SIZE])],
+    ) -> Result<(), FelicaError> {
+        let numbers = blocks.iter().map(|(n, _)| *n).collect::<Vec<_>>();
+        let mut frame = Self::frame(WRITE_WITHOUT_ENCRYPTION, idm, service, &numbers)?;
+        for (_, data) in blocks {
+            frame.extend_from_slice(data);
+        }
+        self.transact(&frame, WRITE_WITHOUT_ENCRYPTION + 1, idm)
+            .map(|_| ())
+    }
+
+    fn frame(code: u8, idm: Idm, service: u16, blocks: &[u16]) -> Result<Vec<u8>, FelicaError> {
+        if blocks.is_empty() || blocks.len() > MAX_BLOCKS {
+            return Err(FelicaError::TooManyBlocks);
+        }
+
+        let mut frame = Vec::with_capacity(13 + blocks.len() * 3);
+        frame.push(code);
+        frame.extend_from_slice(&idm.0);
+        // A single service, its code is little endian.
+        frame.push(1);
+        frame.extend_from_slice(&service.to_le_bytes());
+        frame.push(blocks.len() as u8);
+        for &block in blocks {
+            if block <= 0xff {
+                // A 2 byte block list element for the first service.
+                frame.extend_from_slice(&[0x80, block as u8]);
+            } else {
+                frame.push(0x00);
+                frame.extend_from_slice(&block.to_le_bytes());
+            }
+        }
+        Ok(frame)
+    }
+
+    /// Exchange a frame and strip the response header, returning what follows the status flags.
+    fn transact(&mut self, frame: &[u8], expected: u8, idm: Idm) -> Result<Vec<u8>, FelicaError> {
+        let response = self
+            .kernel
+            .felica_exchange(frame)
+            .map_err(|_| FelicaError::Kernel)?;
+
+        match response.as_slice() {
+            [code, rest @ ..] if *code == expected && rest.len() >= 10 => {
+                if rest[..8] != idm.0 {
+                    return Err(FelicaError::Malformed);
+                }
+                match (rest[8], rest[9]) {
+                    (0x00, _) => Ok(rest[10..].into()),
+                    (flag1, flag2) => Err(FelicaError::Status(flag1, flag2)),
+                }
+            }
+            _ => Err(FelicaError::Malformed),
+        }
+    }
+}
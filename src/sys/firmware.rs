@@ -0,0 +1,96 @@
+//! Upgrading the firmware of the readers attached to a service.
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::FirmwareError, Kernel, NfcService, ReaderId};
+
+/// The firmware version a reader reports.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct FirmwareVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+}
+
+#[allow(dead_code)]
+impl FirmwareVersion {
+    #[inline]
+    pub const fn new(major: u8, minor: u8, patch: u8) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl fmt::Display for FirmwareVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// How far along a firmware update is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of image bytes written so far.
+    pub written: usize,
+    /// The size of the whole image.
+    pub total: usize,
+}
+
+#[allow(dead_code)]
+impl Progress {
+    /// The progress as a percentage.
+    #[inline]
+    pub const fn percent(&self) -> u8 {
+        match self.total {
+            0 => 100,
+            total => (self.written * 100 / total) as u8,
+        }
+    }
+
+    #[inline]
+    pub const fn is_done(&self) -> bool {
+        self.written >= self.total
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// The firmware version of a reader.
+    pub fn firmware_version(&mut self, reader: ReaderId) -> Result<FirmwareVersion, FirmwareError> {
+        self.readers
+            .get_mut(&reader)
+            .ok_or(FirmwareError::UnknownReader(reader))?
+            .firmware_version()
+            .map_err(FirmwareError::Kernel)
+    }
+
+    /// Upgrade the firmware of a reader to `image`, returning the version it reports afterwards.
+    ///
+    /// `progress` is called after every chunk written.
+    pub fn update_firmware<F>(
+        &mut self,
+        reader: ReaderId,
+        image: &[u8],
+        mut progress: F,
+    ) -> Result<FirmwareVersion, FirmwareError>
+    where
+        F: FnMut(Progress),
+    {
+        if image.is_empty() {
+            return Err(FirmwareError::EmptyImage);
+        }
+
+        self.readers
+            .get_mut(&reader)
+            .ok_or(FirmwareError::UnknownReader(reader))?
+            .update_firmware(image, &mut progress)
+            .map_err(FirmwareError::Kernel)
+    }
+}
@@ -0,0 +1,141 @@
+//! An [`NfcService`](crate::NfcService) variant with a fixed capacity registry.
+//!
+//! Nothing in this module allocates, the registry lives inline in the service
+//! so it can be placed in a `static` on targets without an allocator. Tags are read as
+//! postcard payloads without allocating, see [`FixedNfcService::read`].
+use heapless::{index_map::FnvIndexMap, index_set::FnvIndexSet};
+
+use crate::{errors::AccessError, read_card, Card, Kernel, Timestamp, Uid};
+
+/// A basic NFC service holding up to `N` cards, `N` must be a power of two.
+///
+/// Unlike [`NfcService`](crate::NfcService) it drives a single reader and
+/// keeps no events or audit log, every decision is reported through its result.
+pub struct FixedNfcService<K, const N: usize>
+where
+    K: Kernel,
+{
+    cards: FnvIndexMap<Uid, Card, N>,
+    revoked: FnvIndexSet<Uid, N>,
+    system: K,
+}
+
+impl<K, const N: usize> core::fmt::Debug for FixedNfcService<K, N>
+where
+    K: Kernel,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FixedService")
+            .field("cards", &self.cards.len())
+            .field("capacity", &N)
+            .field("revoked", &self.revoked.len())
+            .finish()
+    }
+}
+
+#[allow(dead_code)]
+impl<K, const N: usize> FixedNfcService<K, N>
+where
+    K: Kernel,
+{
+    /// Create a new FixedNfcService with a system provider.
+    #[must_use]
+    #[inline]
+    pub const fn new_in(system: K) -> Self {
+        Self {
+            cards: FnvIndexMap::new(),
+            revoked: FnvIndexSet::new(),
+            system,
+        }
+    }
+
+    /// Return a reference to the kernel of this service.
+    #[inline]
+    pub const fn kernel(&self) -> &K {
+        &self.system
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// The maximum number of cards this service can hold.
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Register a card, replacing the one with the same id.
+    ///
+    /// Returns the card back if the registry is full.
+    // Boxing the rejected card would allocate, which this registry exists to avoid.
+    #[allow(clippy::result_large_err)]
+    pub fn put(&mut self, card: Card) -> Result<Option<Card>, Card> {
+        self.cards.insert(card.id(), card).map_err(|(_, card)| card)
+    }
+
+    pub fn unbind(&mut self, card_id: &Uid) -> Option<Card> {
+        self.cards.remove(card_id)
+    }
+
+    pub fn get(&self, card_id: &Uid) -> Option<&Card> {
+        self.cards.get(card_id)
+    }
+
+    pub fn contains(&self, card_id: &Uid) -> bool {
+        self.cards.contains_key(card_id)
+    }
+
+    /// An iterator over the registered cards.
+    pub fn iter(&self) -> impl Iterator<Item = &Card> + '_ {
+        self.cards.values()
+    }
+
+    /// Revoke a card. Returns `Err` if the revocation list is full.
+    pub fn revoke(&mut self, card_id: Uid) -> Result<bool, Uid> {
+        self.revoked.insert(card_id)
+    }
+
+    /// Lift the revocation of a card. Returns `false` if it wasn't revoked.
+    pub fn reinstate(&mut self, card_id: &Uid) -> bool {
+        self.revoked.remove(card_id)
+    }
+
+    pub fn is_revoked(&self, card_id: &Uid) -> bool {
+        self.revoked.contains(card_id)
+    }
+
+    /// Authorize a card payload that was presented to the reader at `now`.
+    ///
+    /// The same checks as [`NfcService::authorize`](crate::NfcService::authorize) apply,
+    /// expired cards aren't given any grace period.
+    pub fn authorize(&mut self, payload: &Card, now: Timestamp) -> Result<Card, AccessError> {
+        let id = payload.id();
+        if self.revoked.contains(&id) {
+            return Err(AccessError::Revoked(id));
+        }
+
+        match self.cards.get_mut(&id) {
+            Some(card) => card.admit(payload, now, 0).map(|_| card.take_use()),
+            None => Err(AccessError::Unknown(id)),
+        }
+    }
+
+    /// Read a card through the kernel and authorize it at `now`.
+    ///
+    /// The payload is read into a buffer on the stack, decoding it only allocates if it's
+    /// JSON or compressed.
+    pub fn read(&mut self, card_id: Uid, now: Timestamp) -> Result<Card, AccessError> {
+        let payload = match read_card(&mut self.system, card_id) {
+            Ok(card) => card,
+            Err(..) => return Err(AccessError::Kernel),
+        };
+        self.authorize(&payload, now)
+    }
+}
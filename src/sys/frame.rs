@@ -0,0 +1,120 @@
+//! The framing shared by the serial protocols.
+//!
+//! Every frame is laid out as `SOF | len: u16 | command: u8 | payload | crc: u16`,
+//! multi-byte fields are little endian, `len` counts the command and payload and
+//! the CRC-16 covers everything between `SOF` and itself.
+use alloc::vec::Vec;
+
+use crate::{crc::crc16, errors::FrameError};
+
+/// Marks the start of a frame.
+pub const SOF: u8 = 0x7e;
+
+/// The largest payload a frame can carry.
+pub const MAX_PAYLOAD: usize = 1024;
+
+/// Responses carry their request's command with this bit set.
+pub const RESPONSE: u8 = 0x80;
+
+/// A single protocol frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub command: u8,
+    pub payload: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl Frame {
+    #[inline]
+    pub fn new(command: u8, payload: impl Into<Vec<u8>>) -> Self {
+        Self {
+            command,
+            payload: payload.into(),
+        }
+    }
+
+    /// Encode this Frame ready to be sent over the wire.
+    ///
+    /// Fails with [`FrameError::Length`] if the payload is larger than [`MAX_PAYLOAD`].
+    pub fn encode(&self) -> Result<Vec<u8>, FrameError> {
+        let len = self.payload.len() + 1;
+        if len > MAX_PAYLOAD + 1 {
+            return Err(FrameError::Length(len));
+        }
+
+        let mut bytes = Vec::with_capacity(self.payload.len() + 6);
+        bytes.push(SOF);
+        bytes.extend_from_slice(&(len as u16).to_le_bytes());
+        bytes.push(self.command);
+        bytes.extend_from_slice(&self.payload);
+        let crc = crc16(&bytes[1..]);
+        bytes.extend_from_slice(&crc.to_le_bytes());
+        Ok(bytes)
+    }
+
+    /// Decode the frame at the start of `bytes`, returning it and the number of bytes it spans.
+    pub fn decode(bytes: &[u8]) -> Result<(Self, usize), FrameError> {
+        match bytes {
+            [SOF, lo, hi, ..] => {
+                let len = u16::from_le_bytes([*lo, *hi]) as usize;
+                if len == 0 || len > MAX_PAYLOAD + 1 {
+                    return Err(FrameError::Length(len));
+                }
+
+                let end = 3 + len;
+                if bytes.len() < end + 2 {
+                    return Err(FrameError::Incomplete);
+                }
+                let crc = u16::from_le_bytes([bytes[end], bytes[end + 1]]);
+                if crc != crc16(&bytes[1..end]) {
+                    return Err(FrameError::Crc);
+                }
+                Ok((Self::new(bytes[3], &bytes[4..end]), end + 2))
+            }
+            [SOF, ..] | [] => Err(FrameError::Incomplete),
+            _ => Err(FrameError::Sync),
+        }
+    }
+}
+
+/// Reassembles frames from a stream of bytes, i.e. read from a UART.
+#[derive(Debug, Clone, Default)]
+pub struct FrameDecoder {
+    buf: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl FrameDecoder {
+    #[inline]
+    pub const fn new() -> Self {
+        Self { buf: Vec::new() }
+    }
+
+    /// Feed received bytes to the decoder.
+    #[inline]
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Take the next complete frame out of the received bytes.
+    ///
+    /// Garbage and corrupted frames are skipped, resynchronizing on the next `SOF`.
+    pub fn next_frame(&mut self) -> Option<Frame> {
+        loop {
+            match Frame::decode(&self.buf) {
+                Ok((frame, len)) => {
+                    let _ = self.buf.drain(..len);
+                    return Some(frame);
+                }
+                Err(FrameError::Incomplete) => return None,
+                Err(..) => {
+                    let skip = self.buf[1..]
+                        .iter()
+                        .position(|b| *b == SOF)
+                        .map_or(self.buf.len(), |i| i + 1);
+                    let _ = self.buf.drain(..skip);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Door groups, i.e. "All perimeter doors" or "Server rooms".
+//!
+//! Unlike zones, groups don't nest and a door can be a member of any number of them. Access
+//! granted to a group opens every door that's a member at the time a card is presented, so
+//! adding or removing doors never touches the cards.
+use alloc::{
+    collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+    string::String,
+    vec::Vec,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{door::DoorId, role::RoleId, Card, Permissions};
+
+/// The identifier of a [`DoorGroup`].
+pub type GroupId = u16;
+
+/// Who a [`DoorGroup`] admits through its doors.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupGrant {
+    /// Cards assigned this role.
+    Role(RoleId),
+    /// Cards holding all of these permissions.
+    Permissions(Permissions),
+}
+
+/// A named set of doors sharing the same grants.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DoorGroup {
+    name: String,
+    doors: BTreeSet<DoorId>,
+    grants: Vec<GroupGrant>,
+}
+
+#[allow(dead_code)]
+impl DoorGroup {
+    /// Create a new DoorGroup without any doors or grants.
+    #[inline]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            doors: BTreeSet::new(),
+            grants: Vec::new(),
+        }
+    }
+
+    /// Make a door a member of this DoorGroup.
+    #[inline]
+    pub fn door(mut self, door_id: DoorId) -> Self {
+        let _ = self.doors.insert(door_id);
+        self
+    }
+
+    /// Admit cards matching `grant` through all the doors of this DoorGroup.
+    #[inline]
+    pub fn grant(mut self, grant: GroupGrant) -> Self {
+        self.grants.push(grant);
+        self
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// An iterator over the doors of this DoorGroup.
+    pub fn doors(&self) -> impl Iterator<Item = DoorId> + '_ {
+        self.doors.iter().copied()
+    }
+
+    #[inline]
+    pub fn contains(&self, door_id: DoorId) -> bool {
+        self.doors.contains(&door_id)
+    }
+
+    #[inline]
+    pub fn grants(&self) -> &[GroupGrant] {
+        &self.grants
+    }
+
+    /// Check whether this DoorGroup admits a card with `perms`.
+    fn admits(&self, card: &Card, perms: Permissions) -> bool {
+        self.grants.iter().any(|grant| match *grant {
+            GroupGrant::Role(role) => card.roles().contains(role),
+            GroupGrant::Permissions(granted) => !granted.is_empty() && perms.contains(granted),
+        })
+    }
+}
+
+/// The door groups of a site.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DoorGroups {
+    groups: BTreeMap<GroupId, DoorGroup>,
+}
+
+#[allow(dead_code)]
+impl DoorGroups {
+    /// Create a new `DoorGroups` without any groups.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            groups: BTreeMap::new(),
+        }
+    }
+
+    /// Add a group, replacing any group with the same id.
+    pub fn add(&mut self, id: GroupId, group: DoorGroup) -> Option<DoorGroup> {
+        self.groups.insert(id, group)
+    }
+
+    /// Remove a group, taking back its grants on all of its doors.
+    pub fn remove(&mut self, id: GroupId) -> Option<DoorGroup> {
+        self.groups.remove(&id)
+    }
+
+    #[inline]
+    pub fn get(&self, id: GroupId) -> Option<&DoorGroup> {
+        self.groups.get(&id)
+    }
+
+    /// An iterator over all the groups.
+    pub fn iter(&self) -> impl Iterator<Item = (GroupId, &DoorGroup)> + '_ {
+        self.groups.iter().map(|(id, group)| (*id, group))
+    }
+
+    /// Make a door a member of a group. Returns `false` if the group doesn't exist.
+    pub fn join(&mut self, id: GroupId, door_id: DoorId) -> bool {
+        let Some(group) = self.groups.get_mut(&id) else {
+            return false;
+        };
+        let _ = group.doors.insert(door_id);
+        true
+    }
+
+    /// Take a door out of a group. Returns `false` if it wasn't a member.
+    pub fn leave(&mut self, id: GroupId, door_id: DoorId) -> bool {
+        self.groups
+            .get_mut(&id)
+            .is_some_and(|group| group.doors.remove(&door_id))
+    }
+
+    /// Take a door out of all the groups it's a member of.
+    pub fn leave_all(&mut self, door_id: DoorId) {
+        for group in self.groups.values_mut() {
+            let _ = group.doors.remove(&door_id);
+        }
+    }
+
+    /// Admit cards matching `grant` through the doors of a group.
+    /// Returns `false` if the group doesn't exist.
+    pub fn grant(&mut self, id: GroupId, grant: GroupGrant) -> bool {
+        let Some(group) = self.groups.get_mut(&id) else {
+            return false;
+        };
+        if !group.grants.contains(&grant) {
+            group.grants.push(grant);
+        }
+        true
+    }
+
+    /// Take back a grant of a group. Returns `false` if it wasn't granted there.
+    pub fn revoke(&mut self, id: GroupId, grant: GroupGrant) -> bool {
+        let Some(group) = self.groups.get_mut(&id) else {
+            return false;
+        };
+        let len = group.grants.len();
+        group.grants.retain(|granted| *granted != grant);
+        group.grants.len() != len
+    }
+
+    /// An iterator over the groups a door is a member of.
+    pub fn groups_of(&self, door_id: DoorId) -> impl Iterator<Item = GroupId> + '_ {
+        self.iter()
+            .filter(move |(_, group)| group.contains(door_id))
+            .map(|(id, _)| id)
+    }
+
+    /// Check whether a group the door is a member of admits a card with `perms`.
+    pub fn admits(&self, card: &Card, perms: Permissions, door_id: DoorId) -> bool {
+        self.groups
+            .values()
+            .any(|group| group.contains(door_id) && group.admits(card, perms))
+    }
+}
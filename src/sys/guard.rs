@@ -0,0 +1,145 @@
+//! Modifying a registered card and writing it back through a guard.
+//!
+//! A [`CardGuard`] derefs to a copy of the card and notes when it's borrowed mutably. A
+//! modified card is written back to the service and its tag by [`CardGuard::commit`], or
+//! when the guard is dropped, unless it's thrown away by [`CardGuard::discard`].
+use core::ops::{Deref, DerefMut};
+
+use crate::{
+    errors::{AccessError, TagError},
+    transaction::Traced,
+    Card, Kernel, NfcService, ReaderId, Uid,
+};
+
+/// A registered card borrowed for modification, see [`NfcService::card_mut`].
+///
+/// Errors writing back on drop are only reported through the audit log and
+/// [`NfcEvent::WriteFailed`](crate::events::NfcEvent::WriteFailed), commit explicitly to
+/// handle them.
+#[derive(Debug)]
+pub struct CardGuard<'a, K>
+where
+    K: Kernel,
+{
+    nfc: &'a mut NfcService<K>,
+    reader: ReaderId,
+    card: Card,
+    modified: bool,
+    /// Whether the guard was committed or discarded already.
+    done: bool,
+}
+
+#[allow(dead_code)]
+impl<K> CardGuard<'_, K>
+where
+    K: Kernel,
+{
+    /// The reader the card is written back through.
+    #[inline]
+    pub const fn reader(&self) -> ReaderId {
+        self.reader
+    }
+
+    /// Whether the card was borrowed mutably since the guard was created.
+    #[inline]
+    pub const fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// Write the card back to the service and its tag if it was modified.
+    ///
+    /// The tag is written as by [`NfcService::write`], bumping the card's counter. The
+    /// service keeps the card as it was if the write fails.
+    pub fn commit(mut self) -> Result<(), Traced<TagError>> {
+        self.done = true;
+        self.write_back()
+    }
+
+    /// Throw away the modifications, leaving the service and the tag as they were.
+    #[inline]
+    pub fn discard(mut self) {
+        self.done = true;
+    }
+
+    fn write_back(&mut self) -> Result<(), Traced<TagError>> {
+        if !self.modified {
+            return Ok(());
+        }
+        let id = self.card.id;
+        let Some(registered) = self.nfc.cards.get_mut(&id) else {
+            return Err(self.nfc.trace(self.reader, AccessError::Unknown(id).into()));
+        };
+
+        let old = core::mem::replace(registered, self.card);
+        let result = self.nfc.write(self.reader, id);
+        if result.is_err() {
+            if let Some(registered) = self.nfc.cards.get_mut(&id) {
+                *registered = old;
+            }
+        }
+        self.modified = false;
+        result
+    }
+}
+
+impl<K> Deref for CardGuard<'_, K>
+where
+    K: Kernel,
+{
+    type Target = Card;
+
+    #[inline]
+    fn deref(&self) -> &Card {
+        &self.card
+    }
+}
+
+impl<K> DerefMut for CardGuard<'_, K>
+where
+    K: Kernel,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Card {
+        self.modified = true;
+        &mut self.card
+    }
+}
+
+impl<K> Drop for CardGuard<'_, K>
+where
+    K: Kernel,
+{
+    fn drop(&mut self) {
+        if !self.done {
+            let _ = self.write_back();
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Borrow a registered card for modification, writing it back through a reader.
+    pub fn card_mut(
+        &mut self,
+        reader: ReaderId,
+        card_id: Uid,
+    ) -> Result<CardGuard<'_, K>, AccessError> {
+        if !self.readers.contains_key(&reader) {
+            return Err(AccessError::UnknownReader(reader));
+        }
+        let card = *self
+            .cards
+            .get(&card_id)
+            .ok_or(AccessError::Unknown(card_id))?;
+        Ok(CardGuard {
+            nfc: self,
+            reader,
+            card,
+            modified: false,
+            done: false,
+        })
+    }
+}
@@ -0,0 +1,102 @@
+//! Reader self-tests, for monitoring deployments.
+use alloc::collections::btree_map::BTreeMap;
+
+use crate::{errors::KernelError, firmware::FirmwareVersion, Kernel, NfcService, ReaderId};
+
+/// The outcome of a single check.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Check {
+    Pass,
+    Fail(KernelError),
+    /// The reader can't run this check.
+    #[default]
+    Skipped,
+}
+
+#[allow(dead_code)]
+impl Check {
+    /// Turn the result of a check into its outcome, treating unsupported operations as skipped.
+    #[inline]
+    pub fn from_result<T>(result: Result<T, KernelError>) -> Self {
+        match result {
+            Ok(..) => Self::Pass,
+            Err(KernelError::Unsupported(..)) => Self::Skipped,
+            Err(why) => Self::Fail(why),
+        }
+    }
+
+    #[inline]
+    pub const fn is_failed(&self) -> bool {
+        matches!(self, Self::Fail(..))
+    }
+}
+
+/// The result of a reader's self-test.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct HealthReport {
+    /// Whether the reader can drive its RF field.
+    pub rf_field: Check,
+    /// Whether the reader's EEPROM reads back correctly.
+    pub eeprom: Check,
+    /// The firmware the reader runs, if it reports it.
+    pub firmware: Option<FirmwareVersion>,
+}
+
+#[allow(dead_code)]
+impl HealthReport {
+    /// Check none of the checks failed.
+    #[inline]
+    pub const fn is_healthy(&self) -> bool {
+        !self.rf_field.is_failed() && !self.eeprom.is_failed()
+    }
+}
+
+/// The self-test results of every reader attached to a service.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Health {
+    readers: BTreeMap<ReaderId, HealthReport>,
+}
+
+#[allow(dead_code)]
+impl Health {
+    /// The report of a single reader.
+    #[inline]
+    pub fn reader(&self, reader: ReaderId) -> Option<&HealthReport> {
+        self.readers.get(&reader)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (ReaderId, &HealthReport)> + '_ {
+        self.readers.iter().map(|(id, report)| (*id, report))
+    }
+
+    /// The readers that failed any of their checks.
+    pub fn failing(&self) -> impl Iterator<Item = ReaderId> + '_ {
+        self.iter()
+            .filter(|(_, report)| !report.is_healthy())
+            .map(|(id, _)| id)
+    }
+
+    /// Check every reader is healthy.
+    #[inline]
+    pub fn is_healthy(&self) -> bool {
+        self.failing().next().is_none()
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Run the self-test of every attached reader.
+    pub fn health(&mut self) -> Health {
+        Health {
+            readers: self
+                .readers
+                .iter_mut()
+                .map(|(id, kernel)| (*id, kernel.self_test()))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! A record of how card permissions changed over time.
+use alloc::{collections::btree_map::BTreeMap, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Permissions, Timestamp, Uid};
+
+/// The permissions a change granted and revoked, see [`Permissions::diff`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct PermissionDiff {
+    pub granted: Permissions,
+    pub revoked: Permissions,
+}
+
+#[allow(dead_code)]
+impl PermissionDiff {
+    /// Check nothing was granted or revoked.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.granted.is_empty() && self.revoked.is_empty()
+    }
+}
+
+/// A single change of a card's permissions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionChange {
+    pub at: Timestamp,
+    pub old: Permissions,
+    pub new: Permissions,
+}
+
+#[allow(dead_code)]
+impl PermissionChange {
+    #[inline]
+    pub const fn diff(&self) -> PermissionDiff {
+        Permissions::diff(self.old, self.new)
+    }
+}
+
+/// The permission changes of every card, oldest first.
+///
+/// Changes are kept after a card is unbound so past access can still be answered for.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PermissionHistory {
+    changes: BTreeMap<Uid, Vec<PermissionChange>>,
+}
+
+#[allow(dead_code)]
+impl PermissionHistory {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            changes: BTreeMap::new(),
+        }
+    }
+
+    /// Record a card's permissions changing from `old` to `new` at `at`, unless they're equal.
+    pub(crate) fn record(&mut self, id: Uid, at: Timestamp, old: Permissions, new: Permissions) {
+        if old != new {
+            self.changes
+                .entry(id)
+                .or_default()
+                .push(PermissionChange { at, old, new });
+        }
+    }
+
+    /// The changes of a card's permissions, oldest first.
+    #[inline]
+    pub fn of(&self, id: Uid) -> &[PermissionChange] {
+        self.changes.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// The cards whose permissions ever changed.
+    #[inline]
+    pub fn cards(&self) -> impl Iterator<Item = Uid> + '_ {
+        self.changes.keys().copied()
+    }
+
+    /// The permissions a card held at `at`, given the ones it holds now.
+    pub fn at(&self, id: Uid, at: Timestamp, current: Permissions) -> Permissions {
+        let changes = self.of(id);
+        match changes.iter().rev().find(|change| change.at <= at) {
+            Some(change) => change.new,
+            None => changes.first().map_or(current, |change| change.old),
+        }
+    }
+
+    /// Check if a card held all of `perms` at any moment between `from` and `until`.
+    pub fn held(
+        &self,
+        id: Uid,
+        perms: Permissions,
+        from: Timestamp,
+        until: Timestamp,
+        current: Permissions,
+    ) -> bool {
+        self.at(id, from, current).contains(perms)
+            || self
+                .of(id)
+                .iter()
+                .filter(|change| change.at > from && change.at <= until)
+                .any(|change| change.new.contains(perms))
+    }
+}
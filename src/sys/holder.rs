@@ -0,0 +1,121 @@
+//! Metadata about the person a Card is issued to.
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The identifier of a department within the organization.
+pub type DepartmentId = u16;
+
+/// Who a Card is issued to.
+///
+/// The holder's name is only kept as a hash so it can be matched without being stored,
+/// both [`fmt::Display`] and [`fmt::Debug`] redact the name hash and employee number.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Holder {
+    #[serde(default)]
+    #[cfg_attr(
+        not(feature = "postcard"),
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    name_hash: Option<u64>,
+    #[serde(default)]
+    #[cfg_attr(
+        not(feature = "postcard"),
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    department: Option<DepartmentId>,
+    #[serde(default)]
+    #[cfg_attr(
+        not(feature = "postcard"),
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    employee: Option<u32>,
+}
+
+#[allow(dead_code)]
+impl Holder {
+    /// A holder nothing is known about.
+    pub const EMPTY: Self = Self {
+        name_hash: None,
+        department: None,
+        employee: None,
+    };
+
+    /// Hash a holder's name, ignoring case and surrounding whitespace.
+    pub fn hash_name(name: &str) -> u64 {
+        // FNV-1a, stable across builds and platforms.
+        name.trim().bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+            (hash ^ b.to_ascii_lowercase() as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+
+    /// Set the holder's name, only its hash is kept.
+    #[inline]
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name_hash = Some(Self::hash_name(name));
+        self
+    }
+
+    #[inline]
+    pub const fn with_department(mut self, department: DepartmentId) -> Self {
+        self.department = Some(department);
+        self
+    }
+
+    #[inline]
+    pub const fn with_employee(mut self, employee: u32) -> Self {
+        self.employee = Some(employee);
+        self
+    }
+
+    #[inline]
+    pub const fn name_hash(&self) -> Option<u64> {
+        self.name_hash
+    }
+
+    #[inline]
+    pub const fn department(&self) -> Option<DepartmentId> {
+        self.department
+    }
+
+    #[inline]
+    pub const fn employee(&self) -> Option<u32> {
+        self.employee
+    }
+
+    /// Check if this holder carries the given name.
+    #[inline]
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name_hash == Some(Self::hash_name(name))
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.name_hash.is_none() && self.department.is_none() && self.employee.is_none()
+    }
+}
+
+/// Redacts all but the last two digits of an employee number.
+struct Redacted(u32);
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "****{:02}", self.0 % 100)
+    }
+}
+
+impl fmt::Debug for Holder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Holder")
+            .field("name_hash", &self.name_hash.map(|_| "<redacted>"))
+            .field("department", &self.department)
+            .field("employee", &self.employee.map(Redacted))
+            .finish()
+    }
+}
+
+impl fmt::Display for Holder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
@@ -0,0 +1,300 @@
+//! An HTTP management API for administering a service remotely.
+//!
+//! Every request has to carry `Authorization: Bearer <token>`. The routes are:
+//!
+//! - `GET /cards`, `GET /cards/{id}`, `PUT /cards/{id}` and `DELETE /cards/{id}`
+//! - `POST /cards/{id}/grant` and `PUT /cards/{id}/permissions`, with a `{"permissions": ..}` body
+//! - `POST /cards/{id}/revoke` and `POST /cards/{id}/reinstate`
+//! - `GET /audit`, filtered by the `card`, `from`, `until` and `failures` query parameters
+//!   and paged by `offset` and `limit`
+//! - `POST /messages`, with a JSON [`Request`] body answered by a JSON [`Response`]
+//!
+//! The card and audit routes are shorthands for the [`Request`]s they map to.
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use std::io::{self, Read};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::AccessError,
+    message::{tokens_match, AuditQuery, Request, Response},
+    Card, Kernel, NfcService, Permissions, Uid,
+};
+
+/// The largest request body the API reads, in bytes.
+pub const MAX_BODY: u64 = 64 * 1024;
+
+/// The HTTP methods the API routes on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Put,
+    Post,
+    Delete,
+    Other,
+}
+
+/// A request to the management API, independent of the HTTP server in use.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiRequest<'a> {
+    pub method: Method,
+    /// The request target, including the query string.
+    pub url: &'a str,
+    /// The value of the `Authorization` header, if any.
+    pub authorization: Option<&'a str>,
+    pub body: &'a [u8],
+}
+
+/// A JSON response from the management API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl ApiResponse {
+    fn json<T: Serialize + ?Sized>(status: u16, value: &T) -> Self {
+        Self {
+            status,
+            body: serde_json::to_vec(value).unwrap_or_default(),
+        }
+    }
+
+    fn error(status: u16, error: impl ToString) -> Self {
+        #[derive(Serialize)]
+        struct Error {
+            error: String,
+        }
+
+        Self::json(
+            status,
+            &Error {
+                error: error.to_string(),
+            },
+        )
+    }
+
+    fn access(why: AccessError) -> Self {
+        match why {
+            AccessError::Unknown(..) => Self::error(404, why),
+            _ => Self::error(409, why),
+        }
+    }
+
+    /// The REST shape of a [`Response`].
+    fn from_response(response: Response) -> Self {
+        match response {
+            Response::Card(card) => Self::json(200, &card),
+            Response::Done => Self::empty(),
+            Response::Audit(entries) => Self::json(200, &entries),
+            Response::Refused(why) => Self::access(why),
+        }
+    }
+
+    const fn empty() -> Self {
+        Self {
+            status: 204,
+            body: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PermissionsBody {
+    permissions: Permissions,
+}
+
+/// The HTTP management API of a service.
+#[derive(Debug, Clone)]
+pub struct ManagementApi {
+    token: String,
+}
+
+#[allow(dead_code)]
+impl ManagementApi {
+    /// Create the API, only accepting requests carrying `token`.
+    ///
+    /// Returns `None` if the token is empty, it would let in requests without one.
+    #[inline]
+    pub fn new(token: impl Into<String>) -> Option<Self> {
+        let token = token.into();
+        (!token.is_empty()).then_some(Self { token })
+    }
+
+    /// Handle a single request.
+    pub fn handle<K: Kernel>(
+        &self,
+        nfc: &mut NfcService<K>,
+        request: &ApiRequest<'_>,
+    ) -> ApiResponse {
+        let authorized = request
+            .authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|token| tokens_match(token.as_bytes(), self.token.as_bytes()));
+        if !authorized {
+            return ApiResponse::error(401, "unauthorized");
+        }
+
+        let (path, query) = request.url.split_once('?').unwrap_or((request.url, ""));
+        let segments = path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>();
+        match (request.method, &segments[..]) {
+            (Method::Get, ["cards"]) => ApiResponse::json(200, &nfc.iter().collect::<Vec<_>>()),
+            (method, ["cards", id, rest @ ..]) => match Uid::from_hex(id) {
+                Some(id) => Self::card(nfc, method, id, rest, request.body),
+                None => ApiResponse::error(400, "invalid card id"),
+            },
+            (Method::Get, ["audit"]) => Self::audit(nfc, query),
+            (Method::Post, ["messages"]) => match serde_json::from_slice(request.body) {
+                Ok(message) => {
+                    let response = nfc.handle(message);
+                    let status = match &response {
+                        Response::Refused(AccessError::Unknown(..)) => 404,
+                        Response::Refused(..) => 409,
+                        _ => 200,
+                    };
+                    ApiResponse::json(status, &response)
+                }
+                Err(why) => ApiResponse::error(400, why),
+            },
+            (_, ["cards"] | ["audit"] | ["messages"]) => {
+                ApiResponse::error(405, "method not allowed")
+            }
+            _ => ApiResponse::error(404, "not found"),
+        }
+    }
+
+    fn card<K: Kernel>(
+        nfc: &mut NfcService<K>,
+        method: Method,
+        id: Uid,
+        rest: &[&str],
+        body: &[u8],
+    ) -> ApiResponse {
+        let permissions =
+            || serde_json::from_slice::<PermissionsBody>(body).map(|body| body.permissions);
+
+        match (method, rest) {
+            (Method::Get, []) => ApiResponse::from_response(nfc.handle(Request::ReadCard { id })),
+            (Method::Put, []) => match Card::from_json(body) {
+                Ok(card) if card.id() == id => {
+                    ApiResponse::from_response(nfc.handle(Request::WriteCard { card }))
+                }
+                Ok(..) => ApiResponse::error(400, "card id doesn't match the path"),
+                Err(why) => ApiResponse::error(400, why),
+            },
+            (Method::Delete, []) => match nfc.remove(id) {
+                Ok(..) => ApiResponse::empty(),
+                Err(why) => ApiResponse::access(why),
+            },
+            (Method::Post, ["grant"]) => match permissions() {
+                Ok(permissions) => {
+                    ApiResponse::from_response(nfc.handle(Request::Grant { id, permissions }))
+                }
+                Err(why) => ApiResponse::error(400, why),
+            },
+            (Method::Put, ["permissions"]) => match permissions() {
+                Ok(perms) => match nfc.set_permissions(id, perms) {
+                    Ok(()) => ApiResponse::empty(),
+                    Err(why) => ApiResponse::access(why),
+                },
+                Err(why) => ApiResponse::error(400, why),
+            },
+            (Method::Post, ["revoke"]) => {
+                ApiResponse::from_response(nfc.handle(Request::Revoke { id }))
+            }
+            (Method::Post, ["reinstate"]) => match nfc.reinstate(id) {
+                true => ApiResponse::empty(),
+                false if !nfc.contains(&id) => ApiResponse::access(AccessError::Unknown(id)),
+                false => ApiResponse::access(AccessError::NotRevoked(id)),
+            },
+            (_, [] | ["grant"] | ["permissions"] | ["revoke"] | ["reinstate"]) => {
+                ApiResponse::error(405, "method not allowed")
+            }
+            _ => ApiResponse::error(404, "not found"),
+        }
+    }
+
+    fn audit<K: Kernel>(nfc: &mut NfcService<K>, query: &str) -> ApiResponse {
+        let mut filter = AuditQuery::default();
+        for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+            let valid = match key {
+                "card" => Uid::from_hex(value)
+                    .map(|id| filter.card = Some(id))
+                    .is_some(),
+                "from" => value.parse().map(|at| filter.from = at).is_ok(),
+                "until" => value.parse().map(|at| filter.until = at).is_ok(),
+                "failures" => value.parse().map(|only| filter.failures = only).is_ok(),
+                "offset" => value.parse().map(|offset| filter.offset = offset).is_ok(),
+                "limit" => value
+                    .parse()
+                    .map(|limit| filter.limit = Some(limit))
+                    .is_ok(),
+                _ => true,
+            };
+            if !valid {
+                return ApiResponse::error(400, alloc::format!("invalid `{}`", key));
+            }
+        }
+
+        ApiResponse::from_response(nfc.handle(Request::QueryAudit(filter)))
+    }
+
+    /// Serve the API on `addr` until the server fails.
+    pub fn serve<K: Kernel>(&self, nfc: &mut NfcService<K>, addr: &str) -> io::Result<()> {
+        let server = tiny_http::Server::http(addr).map_err(io::Error::other)?;
+        for mut request in server.incoming_requests() {
+            let mut body = Vec::new();
+            let read = request
+                .as_reader()
+                .take(MAX_BODY + 1)
+                .read_to_end(&mut body);
+            if read.is_err() {
+                let _ = request.respond(tiny_http::Response::empty(400));
+                continue;
+            }
+            if body.len() as u64 > MAX_BODY {
+                let _ = request.respond(tiny_http::Response::empty(413));
+                continue;
+            }
+
+            let method = match request.method() {
+                tiny_http::Method::Get => Method::Get,
+                tiny_http::Method::Put => Method::Put,
+                tiny_http::Method::Post => Method::Post,
+                tiny_http::Method::Delete => Method::Delete,
+                _ => Method::Other,
+            };
+            let authorization = request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv("Authorization"))
+                .map(|header| header.value.as_str());
+            let response = self.handle(
+                nfc,
+                &ApiRequest {
+                    method,
+                    url: request.url(),
+                    authorization,
+                    body: &body,
+                },
+            );
+
+            let content_type = tiny_http::Header::from_bytes("Content-Type", "application/json")
+                .expect("a valid header");
+            let response = tiny_http::Response::from_data(response.body)
+                .with_status_code(response.status)
+                .with_header(content_type);
+            // A client hanging up only fails its own request.
+            if request.respond(response).is_err() {
+                crate::logging::log_warn!("failed to respond to an HTTP request");
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+//! Power-loss-safe card writes.
+//!
+//! A tag losing power or leaving the field mid-write is left with a torn payload.
+//! Journaled writes record their intent in a [`CardStore`] before touching the tag and
+//! only commit it once the tag was read back, so [`NfcService::recover`] can finish or
+//! undo a write that was interrupted.
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit::{AuditAction, Origin},
+    errors::{AccessError, JournalError, KernelError},
+    events::NfcEvent,
+    logging::kernel_error,
+    read_card,
+    store::CardStore,
+    write_verified, Card, Kernel, NfcService, ReaderId, Timestamp, Uid,
+};
+
+/// The intent to replace the payload of a tag, recorded before it's written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// The reader the tag is written through.
+    pub reader: ReaderId,
+    /// The payload on the tag before the write.
+    pub old: Card,
+    /// The payload being written.
+    pub new: Card,
+    /// When the write started.
+    pub at: Timestamp,
+}
+
+/// What [`NfcService::recover`] found on the tag of an interrupted write.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Recovery {
+    /// The write landed before the power was lost, the registry was updated.
+    Committed(Uid),
+    /// The write never reached the tag, there was nothing to repair.
+    Discarded(Uid),
+    /// The tag was torn and had its old payload written back.
+    Restored(Uid),
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Write a registered card back to its tag through a reader like [`NfcService::write`],
+    /// journaling the write in a store.
+    ///
+    /// The card's counter is only bumped once the tag was verified. If the write fails and
+    /// the tag can't be restored either, the intent is left `pending` for [`NfcService::recover`].
+    pub fn write_journaled<S: CardStore>(
+        &mut self,
+        reader: ReaderId,
+        card_id: Uid,
+        store: &mut S,
+    ) -> Result<(), JournalError<S::Error>> {
+        let now = self.now();
+        let result = self.journal_write(reader, card_id, store, now);
+        let outcome = match result {
+            Ok(()) => Ok(()),
+            Err(JournalError::Access { reason, .. }) => Err(reason),
+            // Nothing was written, or the tag was and only the commit failed.
+            Err(JournalError::Store(..)) => return result,
+        };
+
+        if let Err(reason) = outcome {
+            self.emit(NfcEvent::WriteFailed {
+                reader,
+                id: card_id,
+                reason,
+            });
+        }
+        self.log(
+            card_id,
+            now,
+            Origin::Reader(reader),
+            AuditAction::Write,
+            outcome,
+        );
+        result
+    }
+
+    fn journal_write<S: CardStore>(
+        &mut self,
+        reader: ReaderId,
+        card_id: Uid,
+        store: &mut S,
+        now: Timestamp,
+    ) -> Result<(), JournalError<S::Error>> {
+        let refused = |reason| JournalError::Access {
+            reason,
+            pending: false,
+        };
+        let Some(kernel) = self.readers.get_mut(&reader) else {
+            return Err(refused(AccessError::UnknownReader(reader)));
+        };
+        if self.revoked.is_revoked(card_id) {
+            return Err(refused(AccessError::Revoked(card_id)));
+        }
+        let Some(registered) = self.cards.get_mut(&card_id) else {
+            return Err(refused(AccessError::Unknown(card_id)));
+        };
+
+        let mut new = *registered;
+        new.counter = new.counter.wrapping_add(1);
+        let old = read_card(kernel, card_id).map_err(|why| refused(kernel_error(reader, why)))?;
+
+        let entry = JournalEntry {
+            reader,
+            old,
+            new,
+            at: now,
+        };
+        store.save_journal(&entry)?;
+
+        if let Err(reason) = write_verified(kernel, reader, &new) {
+            // The tag may have been partially written, put the old payload back.
+            let restored =
+                write_verified(kernel, reader, &old).is_ok() && store.clear_journal().is_ok();
+            return Err(JournalError::Access {
+                reason,
+                pending: !restored,
+            });
+        }
+
+        *registered = new;
+        self.registry_changed();
+        store.clear_journal()?;
+        Ok(())
+    }
+
+    /// Repair the write left in a store's journal, if any. Call this at startup after restoring.
+    ///
+    /// The tag must be in the field of the reader it was written through, the write stays
+    /// pending if it isn't.
+    pub fn recover<S: CardStore>(
+        &mut self,
+        store: &mut S,
+    ) -> Result<Option<Recovery>, JournalError<S::Error>> {
+        let Some(entry) = store.load_journal()? else {
+            return Ok(None);
+        };
+        let pending = |reason| JournalError::Access {
+            reason,
+            pending: true,
+        };
+        let card_id = entry.new.id;
+        let Some(kernel) = self.readers.get_mut(&entry.reader) else {
+            return Err(pending(AccessError::UnknownReader(entry.reader)));
+        };
+
+        let recovery = match read_card(kernel, card_id) {
+            Ok(card) if card == entry.new => {
+                let _ = self.cards.insert(card_id, entry.new);
+                self.registry_changed();
+                Recovery::Committed(card_id)
+            }
+            Ok(card) if card == entry.old => Recovery::Discarded(card_id),
+            Err(why @ (KernelError::NoCard | KernelError::Timeout)) => {
+                return Err(pending(kernel_error(entry.reader, why)));
+            }
+            // Torn, whatever is on the tag can't be trusted.
+            _ => {
+                write_verified(kernel, entry.reader, &entry.old).map_err(pending)?;
+                Recovery::Restored(card_id)
+            }
+        };
+        store.clear_journal()?;
+
+        let now = self.now();
+        self.log(
+            card_id,
+            now,
+            Origin::Reader(entry.reader),
+            AuditAction::Write,
+            match recovery {
+                Recovery::Committed(..) => Ok(()),
+                _ => Err(AccessError::Kernel),
+            },
+        );
+        Ok(Some(recovery))
+    }
+}
@@ -0,0 +1,207 @@
+//! Master key slots and the per-card keys derived from them.
+//!
+//! Every card carries the version of the master key its own key was derived from,
+//! so a new master key can be rolled out while cards on the previous one keep working.
+use alloc::{collections::btree_map::BTreeMap, vec::Vec};
+use core::fmt;
+
+use rand::RngCore;
+
+use crate::{
+    challenge::{Authenticator, Mac, Nonce},
+    Card, Uid,
+};
+
+/// The size of a key in bytes.
+pub const KEY_SIZE: usize = 16;
+
+/// The version of the master key a card's key is derived from.
+pub type KeyVersion = u8;
+
+/// Separates card key derivation from any other use of a master key.
+const CARD_KEY_LABEL: &[u8] = b"lowa card key";
+
+/// The data a master key is fed to derive the key of a card.
+pub(crate) fn derivation(card: Uid, version: KeyVersion) -> Vec<u8> {
+    let uid = card.as_bytes();
+    let mut data = Vec::with_capacity(CARD_KEY_LABEL.len() + 1 + uid.len());
+    data.extend_from_slice(CARD_KEY_LABEL);
+    data.push(version);
+    data.extend_from_slice(uid);
+    data
+}
+
+/// Secret key material, [`fmt::Debug`] never shows the key itself.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretKey([u8; KEY_SIZE]);
+
+#[allow(dead_code)]
+impl SecretKey {
+    #[inline]
+    pub const fn new(key: [u8; KEY_SIZE]) -> Self {
+        Self(key)
+    }
+
+    #[inline]
+    pub const fn as_bytes(&self) -> &[u8; KEY_SIZE] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+/// A keyed pseudo-random function, i.e. AES-CMAC or a truncated HMAC-SHA256.
+pub trait Prf {
+    fn prf(&self, key: &SecretKey, data: &[u8]) -> [u8; KEY_SIZE];
+}
+
+/// HMAC-SHA256 truncated to [`KEY_SIZE`] bytes, computed in software.
+///
+/// This matches what a [`SecureElement`](crate::secure::SecureElement) signs with,
+/// so cards keyed by either work with both.
+#[cfg(feature = "soft-crypto")]
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct HmacSha256;
+
+#[cfg(feature = "soft-crypto")]
+impl Prf for HmacSha256 {
+    fn prf(&self, key: &SecretKey, data: &[u8]) -> [u8; KEY_SIZE] {
+        use hmac::Mac as _;
+
+        let mut hmac = hmac::Hmac::<sha2::Sha256>::new_from_slice(key.as_bytes())
+            .expect("HMAC takes keys of any size");
+        hmac.update(data);
+        let mut out = [0; KEY_SIZE];
+        out.copy_from_slice(&hmac.finalize().into_bytes()[..KEY_SIZE]);
+        out
+    }
+}
+
+/// Master keys indexed by their version, and the version new cards are issued with.
+pub struct KeyStore<F> {
+    prf: F,
+    slots: BTreeMap<KeyVersion, SecretKey>,
+    current: Option<KeyVersion>,
+}
+
+impl<F> fmt::Debug for KeyStore<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyStore")
+            .field("versions", &self.slots.keys())
+            .field("current", &self.current)
+            .finish()
+    }
+}
+
+#[allow(dead_code)]
+impl<F: Prf> KeyStore<F> {
+    /// Create a new `KeyStore` without any master keys.
+    #[inline]
+    pub const fn new(prf: F) -> Self {
+        Self {
+            prf,
+            slots: BTreeMap::new(),
+            current: None,
+        }
+    }
+
+    /// Store a master key in a slot, returning the key it replaced.
+    ///
+    /// The first key stored becomes the current one.
+    pub fn insert(&mut self, version: KeyVersion, master: SecretKey) -> Option<SecretKey> {
+        let _ = self.current.get_or_insert(version);
+        self.slots.insert(version, master)
+    }
+
+    /// Store a master key and issue new cards with it from now on.
+    ///
+    /// The previous keys stay in their slots until removed, so cards derived from them
+    /// keep working until they're rekeyed.
+    pub fn rotate(&mut self, version: KeyVersion, master: SecretKey) -> Option<SecretKey> {
+        let replaced = self.insert(version, master);
+        self.current = Some(version);
+        replaced
+    }
+
+    /// Retire a master key, cards derived from it stop authenticating.
+    pub fn remove(&mut self, version: KeyVersion) -> Option<SecretKey> {
+        if self.current == Some(version) {
+            self.current = None;
+        }
+        self.slots.remove(&version)
+    }
+
+    /// The version new cards are issued with.
+    #[inline]
+    pub const fn current(&self) -> Option<KeyVersion> {
+        self.current
+    }
+
+    /// The versions of the stored master keys.
+    pub fn versions(&self) -> impl Iterator<Item = KeyVersion> + '_ {
+        self.slots.keys().copied()
+    }
+
+    /// Derive the key of a card's tag from a master key.
+    pub fn derive(&self, card: Uid, version: KeyVersion) -> Option<SecretKey> {
+        let master = self.slots.get(&version)?;
+        Some(SecretKey(self.prf.prf(master, &derivation(card, version))))
+    }
+
+    /// The key of a card, derived from the master key of its [`Card::key_version`].
+    #[inline]
+    pub fn card_key(&self, card: &Card) -> Option<SecretKey> {
+        self.derive(card.id(), card.key_version())
+    }
+
+    /// Whether a card was derived from a master key other than the current one.
+    #[inline]
+    pub fn needs_rekey(&self, card: &Card) -> bool {
+        self.current
+            .is_some_and(|current| current != card.key_version())
+    }
+
+    /// Authenticate cards with their derived keys, drawing nonces from `rng`.
+    #[inline]
+    pub const fn authenticator<R: RngCore>(self, rng: R) -> KeyedAuthenticator<F, R> {
+        KeyedAuthenticator { keys: self, rng }
+    }
+}
+
+/// An [`Authenticator`] MACing with the card keys of a [`KeyStore`].
+#[derive(Debug)]
+pub struct KeyedAuthenticator<F, R> {
+    keys: KeyStore<F>,
+    rng: R,
+}
+
+#[allow(dead_code)]
+impl<F, R> KeyedAuthenticator<F, R> {
+    #[inline]
+    pub const fn keys(&self) -> &KeyStore<F> {
+        &self.keys
+    }
+
+    #[inline]
+    pub fn keys_mut(&mut self) -> &mut KeyStore<F> {
+        &mut self.keys
+    }
+}
+
+impl<F: Prf, R: RngCore> Authenticator for KeyedAuthenticator<F, R> {
+    fn nonce(&mut self) -> Option<Nonce> {
+        let mut nonce = Nonce::default();
+        self.rng.try_fill_bytes(&mut nonce).ok()?;
+        Some(nonce)
+    }
+
+    fn mac(&mut self, card: &Card, data: &[u8]) -> Option<Mac> {
+        let key = self.keys.card_key(card)?;
+        Some(self.keys.prf.prf(&key, data))
+    }
+}
@@ -0,0 +1,176 @@
+//! Card memory layouts, naming the regions of a tag's memory instead of treating it as one
+//! opaque payload.
+//!
+//! A [`CardLayout`] places regions one after another from the start of the tag's memory,
+//! the card's payload in [`CardLayout::CREDENTIAL`] first. Each region is read and written
+//! on its own through the pages of the kernel, see [`Kernel::PAGE_SIZE`].
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+use crate::{
+    errors::{KernelError, LayoutError},
+    Card, Kernel, Uid,
+};
+
+/// A named region of a tag's memory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Region {
+    name: &'static str,
+    offset: usize,
+    len: usize,
+}
+
+#[allow(dead_code)]
+impl Region {
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The byte offset of this region from the start of the tag's memory.
+    #[inline]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The byte offset just past the end of this region.
+    #[inline]
+    pub const fn end(&self) -> usize {
+        self.offset + self.len
+    }
+
+    /// The pages this region spans.
+    pub const fn pages(&self, page_size: usize) -> Range<usize> {
+        self.offset / page_size..self.end().div_ceil(page_size)
+    }
+
+    /// Whether this region starts and ends on page boundaries.
+    const fn is_aligned(&self, page_size: usize) -> bool {
+        self.offset.is_multiple_of(page_size) && self.end().is_multiple_of(page_size)
+    }
+}
+
+/// The regions of a tag's memory, in the order they're laid out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CardLayout {
+    regions: Vec<Region>,
+}
+
+#[allow(dead_code)]
+impl CardLayout {
+    /// The region holding the card's payload.
+    pub const CREDENTIAL: &'static str = "credential";
+    /// The region holding information about the card's holder, i.e. a printed name.
+    pub const HOLDER: &'static str = "holder";
+    /// The region holding data of the site the card is issued by.
+    pub const SITE: &'static str = "site";
+
+    /// Create a new CardLayout without any regions.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            regions: Vec::new(),
+        }
+    }
+
+    /// The layout of tags issued by the service, a 256 byte credential followed by 64 bytes
+    /// each of holder and site data.
+    pub fn standard() -> Self {
+        Self::new()
+            .region(Self::CREDENTIAL, 256)
+            .region(Self::HOLDER, 64)
+            .region(Self::SITE, 64)
+    }
+
+    /// Add a region of `len` bytes after the last one, replacing any region with the
+    /// same name.
+    pub fn region(mut self, name: &'static str, len: usize) -> Self {
+        self.regions.retain(|region| region.name != name);
+        let offset = self.size();
+        self.regions.push(Region { name, offset, len });
+        self
+    }
+
+    /// The region with this name.
+    pub fn get(&self, name: &str) -> Option<&Region> {
+        self.regions.iter().find(|region| region.name == name)
+    }
+
+    #[inline]
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    /// The bytes of tag memory this layout takes up.
+    pub fn size(&self) -> usize {
+        self.regions.last().map_or(0, Region::end)
+    }
+
+    /// Read a region of a card's tag.
+    pub fn read<K: Kernel>(
+        &self,
+        kernel: &mut K,
+        card: Uid,
+        name: &str,
+    ) -> Result<Vec<u8>, LayoutError> {
+        let region = self.get(name).ok_or(LayoutError::UnknownRegion)?;
+        let page_size = page_size::<K>()?;
+        let pages = region.pages(page_size);
+
+        let mut buf = vec![0; pages.len() * page_size];
+        kernel.read_pages(card, pages.start, &mut buf)?;
+        let start = region.offset - pages.start * page_size;
+        Ok(buf[start..start + region.len].to_vec())
+    }
+
+    /// Write a region of a card's tag, leaving the others as they are.
+    ///
+    /// Data shorter than the region is padded with zeros. Regions that don't start and end
+    /// on page boundaries share pages with their neighbours, which are read first to keep
+    /// the neighbours' bytes.
+    pub fn write<K: Kernel>(
+        &self,
+        kernel: &mut K,
+        card: &Card,
+        name: &str,
+        data: &[u8],
+    ) -> Result<(), LayoutError> {
+        let region = self.get(name).ok_or(LayoutError::UnknownRegion)?;
+        if data.len() > region.len {
+            return Err(LayoutError::TooLarge {
+                len: data.len(),
+                capacity: region.len,
+            });
+        }
+        let page_size = page_size::<K>()?;
+        let pages = region.pages(page_size);
+
+        let mut buf = vec![0; pages.len() * page_size];
+        if !region.is_aligned(page_size) {
+            kernel.read_pages(card.id, pages.start, &mut buf)?;
+        }
+        let start = region.offset - pages.start * page_size;
+        let (data_bytes, padding) = buf[start..start + region.len].split_at_mut(data.len());
+        data_bytes.copy_from_slice(data);
+        padding.fill(0);
+        kernel.write_pages(card, pages.start, &buf)?;
+        Ok(())
+    }
+}
+
+/// The page size of a kernel, which must address pages to access regions.
+fn page_size<K: Kernel>() -> Result<usize, KernelError> {
+    K::PAGE_SIZE
+        .filter(|size| *size > 0)
+        .ok_or(KernelError::Unsupported("page access"))
+}
@@ -0,0 +1,131 @@
+use alloc::collections::btree_map::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ReaderId, Timestamp, Uid};
+
+/// What gets locked after repeated denials.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LockoutTarget {
+    Card(Uid),
+    Reader(ReaderId),
+}
+
+/// Thresholds for locking cards and readers out.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LockoutConfig {
+    /// The number of consecutive denials that triggers a lockout.
+    pub threshold: u32,
+    /// The window in seconds the denials have to happen within.
+    pub window: u64,
+    /// How long a lockout lasts in seconds.
+    pub duration: u64,
+    /// Whether readers get locked as well, not just the cards presented to them.
+    pub lock_readers: bool,
+}
+
+impl LockoutConfig {
+    /// Five denials within a minute lock a card out for five minutes.
+    pub const DEFAULT: LockoutConfig = LockoutConfig {
+        threshold: 5,
+        window: 60,
+        duration: 300,
+        lock_readers: false,
+    };
+}
+
+impl Default for LockoutConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+struct Attempts {
+    denials: u32,
+    since: Timestamp,
+    locked_until: Option<Timestamp>,
+}
+
+/// Tracks consecutive denials and the lockouts they trigger.
+#[derive(Debug, Clone)]
+pub struct Lockouts {
+    config: LockoutConfig,
+    attempts: BTreeMap<LockoutTarget, Attempts>,
+}
+
+#[allow(dead_code)]
+impl Lockouts {
+    /// Create a new tracker with the given thresholds.
+    #[inline]
+    pub const fn new(config: LockoutConfig) -> Self {
+        Self {
+            config,
+            attempts: BTreeMap::new(),
+        }
+    }
+
+    #[inline]
+    pub const fn config(&self) -> &LockoutConfig {
+        &self.config
+    }
+
+    /// Change the thresholds, existing lockouts are kept.
+    #[inline]
+    pub fn configure(&mut self, config: LockoutConfig) {
+        self.config = config;
+    }
+
+    /// Returns when the lockout of a target ends, if it's locked at `now`.
+    pub fn locked_until(&self, target: LockoutTarget, now: Timestamp) -> Option<Timestamp> {
+        self.attempts
+            .get(&target)
+            .and_then(|attempts| attempts.locked_until)
+            .filter(|until| *until > now)
+    }
+
+    /// Record a denial at `now`. Returns when the lockout ends if this one triggered it.
+    pub fn deny(&mut self, target: LockoutTarget, now: Timestamp) -> Option<Timestamp> {
+        if matches!(target, LockoutTarget::Reader(..)) && !self.config.lock_readers {
+            return None;
+        }
+
+        let config = self.config;
+        let attempts = self.attempts.entry(target).or_default();
+        if attempts.denials == 0 || now.saturating_sub(attempts.since) > config.window {
+            attempts.denials = 0;
+            attempts.since = now;
+        }
+        attempts.denials += 1;
+
+        if attempts.denials < config.threshold.max(1) {
+            return None;
+        }
+        let until = now.saturating_add(config.duration);
+        attempts.denials = 0;
+        attempts.locked_until = Some(until);
+        Some(until)
+    }
+
+    /// Record a grant, resetting the consecutive denials of a target.
+    pub fn grant(&mut self, target: LockoutTarget) {
+        if let Some(attempts) = self.attempts.get_mut(&target) {
+            attempts.denials = 0;
+        }
+    }
+
+    /// Lift the lockout of a target. Returns `false` if it wasn't tracked.
+    pub fn unlock(&mut self, target: LockoutTarget) -> bool {
+        self.attempts.remove(&target).is_some()
+    }
+
+    /// Forget about targets which aren't locked at `now` and have no recent denials.
+    pub fn prune(&mut self, now: Timestamp) {
+        let window = self.config.window;
+        self.attempts.retain(|_, attempts| {
+            attempts.locked_until.is_some_and(|until| until > now)
+                || (attempts.denials > 0 && now.saturating_sub(attempts.since) <= window)
+        });
+    }
+}
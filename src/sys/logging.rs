@@ -0,0 +1,36 @@
+//! A logging facade, emitting through `defmt` on embedded targets and `log` otherwise.
+//!
+//! Format strings must stick to the syntax both backends share, i.e. `{}` and `{:?}`,
+//! and arguments have to implement `defmt::Format` when the `defmt` feature is enabled.
+use crate::{errors::AccessError, errors::KernelError, ReaderId};
+
+#[cfg(feature = "defmt")]
+macro_rules! log_debug {
+    ($($arg:tt)*) => { ::defmt::debug!($($arg)*) };
+}
+
+#[cfg(not(feature = "defmt"))]
+macro_rules! log_debug {
+    ($($arg:tt)*) => { ::log::debug!($($arg)*) };
+}
+
+#[cfg(feature = "defmt")]
+macro_rules! log_warn {
+    ($($arg:tt)*) => { ::defmt::warn!($($arg)*) };
+}
+
+#[cfg(not(feature = "defmt"))]
+macro_rules! log_warn {
+    ($($arg:tt)*) => { ::log::warn!($($arg)*) };
+}
+
+pub(crate) use log_debug;
+#[allow(unused_imports)]
+pub(crate) use log_warn;
+
+/// Log a kernel error raised by a reader and turn it into [`AccessError::Kernel`].
+#[inline]
+pub(crate) fn kernel_error(reader: ReaderId, why: KernelError) -> AccessError {
+    log_warn!("reader {} kernel error: {:?}", reader, why);
+    AccessError::Kernel
+}
@@ -1,18 +1,150 @@
 #![no_std]
 /// An NFC service for reading, writing and interacting with NFC cards.
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+mod alarm;
+mod apdu;
+mod audit;
+mod baseline;
+mod batch;
+mod capacity;
+mod challenge;
+#[cfg(feature = "std")]
+mod cli;
+mod clock;
+mod codec;
+mod crc;
+mod delta;
+mod desfire;
+mod directory;
+mod door;
+mod dual;
+mod duress;
+mod elevation;
+mod elevator;
+mod emulate;
+mod encoding;
+#[cfg(feature = "std")]
+mod enroll;
 mod errors;
+mod escalation;
+mod events;
+mod expiry;
+mod export;
+mod felica;
+mod firmware;
+#[cfg(feature = "heapless")]
+mod fixed;
+mod frame;
+mod group;
+mod guard;
+mod health;
+mod history;
+mod holder;
+#[cfg(feature = "http")]
+mod http;
+mod journal;
+mod keys;
+mod layout;
+mod lockout;
+mod logging;
+mod maintenance;
+mod matrix;
+mod message;
+mod metrics;
+mod mifare;
+mod mock;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+mod ndef;
+mod osdp;
+#[cfg(feature = "pcsc")]
+mod pcsc;
+mod permissions;
+mod policy;
+mod power;
+mod provision;
+mod query;
+mod record;
+mod retry;
+mod revocation;
+mod role;
+mod schedule;
+mod schema;
+mod secure;
+#[cfg(feature = "embedded-io")]
+mod serial;
+mod session;
+#[cfg(feature = "critical-section")]
+mod shared;
+mod site;
+#[cfg(feature = "snapshot")]
+mod snapshot;
+mod state;
+mod store;
+mod sync;
+#[cfg(feature = "syslog")]
+mod syslog;
+mod tamper;
+mod template;
+mod tour;
+mod transaction;
+mod uid;
+#[cfg(feature = "webhook")]
+mod webhook;
+mod wiegand;
+mod zone;
 
-use core::fmt;
+use core::{fmt, time::Duration};
 
+use alarm::Alarms;
 use alloc::{boxed::Box, collections::btree_map::BTreeMap, vec::Vec};
-use errors::{ConversionError, KernelError};
+use apdu::{Command, Response};
+use audit::{AuditAction, AuditEntry, AuditLog, AuditSink, Origin};
+use baseline::PositionDefaults;
+use capacity::Capacity;
+use challenge::{Answer, Authenticator, Mac, Nonce};
+use clock::Clock;
+use desfire::Desfire;
+use door::{Door, DoorId};
+use elevation::Elevation;
+use encoding::EncodingQueue;
+use errors::{AccessError, ConversionError, EncodeError, KernelError, TagError, WireError};
+use escalation::EscalationQueue;
+use events::{AccessEvent, NfcEvent, Subscriber, Subscribers, SubscriptionId, TracedEvent};
+use expiry::PermissionExpiry;
+use felica::{Felica, FelicaKernel};
+use firmware::{FirmwareVersion, Progress};
+use health::{Check, HealthReport};
+use history::{PermissionDiff, PermissionHistory};
+use holder::Holder;
+use keys::KeyVersion;
+use lockout::{LockoutConfig, LockoutTarget, Lockouts};
+use logging::kernel_error;
+use maintenance::{Maintenance, MaintenanceSchedule};
+use metrics::{Counter, InMemoryMetrics, Metrics, Observation};
+use mifare::{Block, Key, KeyType, MifareClassic, BLOCK_SIZE};
+use policy::{AccessPolicy, Decision, DenyReason};
+use power::{PowerSchedule, PowerState};
+use retry::RetryPolicy;
+use revocation::RevocationList;
+use role::{Role, RoleId, RoleSet};
+use schema::SchemaVersion;
 use serde::{Deserialize, Serialize};
+use store::{CardStore, StoreError};
+use tamper::{Tamper, Tampers};
+use tour::GuardTours;
+use transaction::{Traced, Transactions};
+use uid::Uid;
 
 bitflags::bitflags! {
     /// Permissions that are given to Cards.
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
-    pub struct Permissions: u8 {
+    ///
+    /// Bits 6 through 15 are reserved for permissions this crate may add, bits 16 through 31
+    /// are left to deployments, see [`Permissions::custom`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Permissions: u32 {
         /// No permissions.
         const NONE = 1 << 0;
         /// Permission for regular Cards.
@@ -25,29 +157,108 @@ bitflags::bitflags! {
         const ADMIN = 1 << 4;
         /// Permission which bypasses everything.
         const SUPER_ADMIN = 1 << 5;
+        /// Deployment-specific permissions.
+        const _ = 0xFFFF_0000;
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Permissions {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "Permissions({=u32:#034b})", self.bits())
     }
 }
 
 impl Permissions {
     /// Returns all permission excluding [`Permissions::NONE`]
     #[inline]
-    const fn privileged() -> Self {
+    pub const fn privileged() -> Self {
         Self::all().symmetric_difference(Self::NONE)
     }
+
+    /// The permissions granted and revoked going from `old` to `new`.
+    #[inline]
+    pub const fn diff(old: Self, new: Self) -> PermissionDiff {
+        PermissionDiff {
+            granted: new.difference(old),
+            revoked: old.difference(new),
+        }
+    }
 }
 
+/// The position a Card holder has within the organization.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
-enum Position {
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Position {
     Manager,
     Director,
     #[default]
     Coordinator,
 }
 
+impl Position {
+    /// The seniority of this position, higher is more senior.
+    #[inline]
+    pub const fn rank(&self) -> u8 {
+        match self {
+            Self::Coordinator => 0,
+            Self::Manager => 1,
+            Self::Director => 2,
+        }
+    }
+
+    /// Check if this position is at least as senior as `other`.
+    #[inline]
+    pub const fn is_at_least(&self, other: Position) -> bool {
+        self.rank() >= other.rank()
+    }
+}
+
+/// Seconds since the unix epoch.
+pub type Timestamp = u64;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Card {
-    id: u16,
+    /// The schema version this Card is serialized with, see [`schema::Migrations`].
+    #[serde(default = "schema::current")]
+    schema: SchemaVersion,
+    id: Uid,
     permissions: Permissions,
+    #[serde(default)]
+    position: Position,
+    /// The roles assigned to this Card, expanded into permissions on access.
+    #[serde(default)]
+    roles: RoleSet,
+    /// A rolling counter bumped by the service on every verified write to the tag.
+    #[serde(default)]
+    counter: u32,
+    /// The moment this Card stops granting access, if any.
+    #[serde(default)]
+    valid_until: Option<Timestamp>,
+    /// The person this Card is issued to, if known.
+    #[serde(default)]
+    #[cfg_attr(
+        not(feature = "postcard"),
+        serde(skip_serializing_if = "Holder::is_empty")
+    )]
+    holder: Holder,
+    /// The version of the master key this Card's key is derived from.
+    #[serde(default)]
+    key_version: KeyVersion,
+    /// When individual permissions of this Card expire.
+    #[serde(default)]
+    #[cfg_attr(
+        not(feature = "postcard"),
+        serde(skip_serializing_if = "PermissionExpiry::is_empty")
+    )]
+    expiry: PermissionExpiry,
+    /// The number of accesses this Card has left, `None` if it isn't limited.
+    #[serde(default)]
+    #[cfg_attr(
+        not(feature = "postcard"),
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    uses: Option<u32>,
 }
 
 impl fmt::Display for Card {
@@ -55,20 +266,81 @@ impl fmt::Display for Card {
         f.debug_struct("Card")
             .field("id", &self.id)
             .field("permissions", &self.permissions)
+            .field("position", &self.position)
+            .field("roles", &self.roles)
+            .field("counter", &self.counter)
+            .field("valid_until", &self.valid_until)
+            .field("holder", &self.holder)
+            .field("key_version", &self.key_version)
             .finish()
     }
 }
 
 impl Card {
     /// Create a new Card.
-    pub const fn new(id: u16, permissions: Permissions) -> Self {
-        Self { id, permissions }
+    pub const fn new(id: Uid, permissions: Permissions) -> Self {
+        Self {
+            schema: schema::CURRENT,
+            id,
+            permissions,
+            position: Position::Coordinator,
+            roles: RoleSet::empty(),
+            counter: 0,
+            valid_until: None,
+            holder: Holder::EMPTY,
+            key_version: 0,
+            expiry: PermissionExpiry::NONE,
+            uses: None,
+        }
+    }
+
+    /// The unique identifier of this Card's tag.
+    #[inline]
+    pub const fn id(&self) -> Uid {
+        self.id
+    }
+
+    /// Set the position of this Card's holder.
+    #[inline]
+    pub const fn with_position(mut self, position: Position) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Set the moment this Card expires.
+    #[inline]
+    pub const fn with_expiry(mut self, valid_until: Timestamp) -> Self {
+        self.valid_until = Some(valid_until);
+        self
+    }
+
+    /// Set the person this Card is issued to.
+    #[inline]
+    pub const fn with_holder(mut self, holder: Holder) -> Self {
+        self.holder = holder;
+        self
+    }
+
+    /// Set the version of the master key this Card's key is derived from.
+    #[inline]
+    pub const fn with_key_version(mut self, version: KeyVersion) -> Self {
+        self.key_version = version;
+        self
+    }
+
+    /// Limit this Card to a number of accesses, i.e. for visitors.
+    ///
+    /// The service revokes it once they're used up.
+    #[inline]
+    pub const fn with_uses(mut self, uses: u32) -> Self {
+        self.uses = Some(uses);
+        self
     }
 
     /// A default Card object.
     #[inline]
     pub const fn default() -> Self {
-        Self::new(0, Permissions::REGULAR)
+        Self::new(Uid::from_u16(0), Permissions::REGULAR)
     }
 
     /// An immutable reference of this Card's permissions.
@@ -77,34 +349,375 @@ impl Card {
         &self.permissions
     }
 
+    /// The roles assigned to this Card.
+    #[inline]
+    pub const fn roles(&self) -> RoleSet {
+        self.roles
+    }
+
+    /// The position of this Card's holder.
+    #[inline]
+    pub const fn position(&self) -> Position {
+        self.position
+    }
+
+    /// The current value of this Card's anti-replay counter.
+    #[inline]
+    pub const fn counter(&self) -> u32 {
+        self.counter
+    }
+
+    /// The person this Card is issued to.
+    #[inline]
+    pub const fn holder(&self) -> &Holder {
+        &self.holder
+    }
+
+    /// The version of the master key this Card's key is derived from.
+    #[inline]
+    pub const fn key_version(&self) -> KeyVersion {
+        self.key_version
+    }
+
+    /// The moment this Card expires, if it does.
+    #[inline]
+    pub const fn valid_until(&self) -> Option<Timestamp> {
+        self.valid_until
+    }
+
+    /// The number of accesses this Card has left, `None` if it isn't limited.
+    #[inline]
+    pub const fn remaining_uses(&self) -> Option<u32> {
+        self.uses
+    }
+
+    /// Check if this Card has expired at `now`.
+    #[inline]
+    pub const fn is_expired(&self, now: Timestamp) -> bool {
+        match self.valid_until {
+            Some(until) => now >= until,
+            None => false,
+        }
+    }
+
     /// Check if this Card has specific permissions.
     #[inline]
     pub const fn is(&self, perms: Permissions) -> bool {
         self.permissions.contains(perms)
     }
 
+    /// Decode a Card, upgrading JSON payloads of older schema versions.
+    ///
+    /// The payload's checksum is checked first, see [`Card::try_to_bytes`].
+    /// Payloads written before they carried one decode with [`Card::from_json`].
     #[inline]
-    pub fn from_bytes<'a>(bytes: &'a [u8]) -> Result<Self, ConversionError<'a>> {
-        Self::try_from(bytes)
+    #[cfg(not(feature = "postcard"))]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ConversionError> {
+        Self::from_json(&codec::decompress("Card", checked("Card", bytes)?)?)
+    }
+
+    /// Decode a Card.
+    ///
+    /// The payload's checksum is checked first, see [`Card::try_to_bytes`].
+    /// Postcard payloads don't describe their fields, so older schema versions can't be
+    /// upgraded and are rejected.
+    #[cfg(feature = "postcard")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ConversionError> {
+        let payload = codec::decompress("Card", checked("Card", bytes)?)?;
+        let card: Self = codec::from_slice("Card", &payload)?;
+        if card.schema != schema::CURRENT {
+            return Err(ConversionError::Schema {
+                target: "Card",
+                version: card.schema,
+            });
+        }
+        Ok(card)
+    }
+
+    /// Decode a Card from JSON, upgrading payloads of older schema versions.
+    #[inline]
+    #[cfg(feature = "json")]
+    pub fn from_json(bytes: &[u8]) -> Result<Self, ConversionError> {
+        Self::from_json_with(bytes, &schema::Migrations::new())
+    }
+
+    /// Decode a Card from JSON, upgrading payloads of older schema versions with `migrations`.
+    #[cfg(feature = "json")]
+    pub fn from_json_with(
+        bytes: &[u8],
+        migrations: &schema::Migrations,
+    ) -> Result<Self, ConversionError> {
+        let mut payload = serde_json::from_slice(bytes)
+            .map_err(|why| ConversionError::deserialize("Card", &why, bytes))?;
+        migrations
+            .upgrade(&mut payload)
+            .map_err(|version| ConversionError::Schema {
+                target: "Card",
+                version,
+            })?;
+        serde_json::from_value(payload)
+            .map_err(|why| ConversionError::deserialize("Card", &why, bytes))
     }
 
     /// Convert this Card into bytes payload ready to get sent.
     #[inline]
+    #[deprecated(note = "use `Card::try_to_bytes` or `Card::encode_into` instead")]
     pub fn as_bytes(&self) -> Vec<u8> {
-        // SAFETY: We're converting from self which's always a valid value.
-        unsafe { serde_json::to_vec(self).unwrap_unchecked() }
+        self.try_to_bytes().unwrap_or_default()
+    }
+
+    /// Convert this Card into bytes payload ready to get sent.
+    ///
+    /// The payload ends with a CRC-16 of the encoded card in little endian, so
+    /// [`Card::from_bytes`] tells a payload garbled over the air from a malformed one.
+    /// The encoded card is compressed first with the `miniz` feature, see [`codec::DEFLATE`].
+    #[inline]
+    pub fn try_to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        let mut bytes = codec::compress(codec::to_vec(self)?);
+        let crc = crc::crc16(&bytes);
+        bytes.extend_from_slice(&crc.to_le_bytes());
+        Ok(bytes)
+    }
+
+    /// Encode this Card into `buf` like [`Card::try_to_bytes`], returning the number of
+    /// bytes written.
+    ///
+    /// This doesn't allocate when the `postcard` or `std` feature is enabled, other
+    /// `no_std` builds encode through a temporary buffer first, as does compressing.
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let capacity = buf.len();
+        let len = self.encode_payload(buf)?;
+        let len = codec::compress_into(buf, len);
+        let crc = crc::crc16(&buf[..len]).to_le_bytes();
+        buf.get_mut(len..len + CHECKSUM_SIZE)
+            .ok_or(EncodeError::BufferTooSmall { capacity })?
+            .copy_from_slice(&crc);
+        Ok(len + CHECKSUM_SIZE)
+    }
+
+    /// Encode this Card into `buf` without its checksum.
+    fn encode_payload(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let capacity = buf.len();
+
+        #[cfg(feature = "postcard")]
+        {
+            postcard::to_slice(self, buf)
+                .map(|written| written.len())
+                .map_err(|why| match why {
+                    postcard::Error::SerializeBufferFull => {
+                        EncodeError::BufferTooSmall { capacity }
+                    }
+                    _ => EncodeError::Serialize,
+                })
+        }
+
+        #[cfg(all(feature = "std", not(feature = "postcard")))]
+        {
+            let mut out = &mut *buf;
+            serde_json::to_writer(&mut out, self).map_err(|why| match why.is_io() {
+                true => EncodeError::BufferTooSmall { capacity },
+                false => EncodeError::Serialize,
+            })?;
+            Ok(capacity - out.len())
+        }
+
+        #[cfg(not(any(feature = "std", feature = "postcard")))]
+        {
+            let bytes = codec::to_vec(self)?;
+            buf.get_mut(..bytes.len())
+                .ok_or(EncodeError::BufferTooSmall { capacity })?
+                .copy_from_slice(&bytes);
+            Ok(bytes.len())
+        }
+    }
+}
+
+impl Card {
+    /// Admit a payload presented for this registered Card at `now`.
+    ///
+    /// Rejects the payload if this Card expired more than `grace` seconds ago or the
+    /// payload's counter is behind the one last written to the tag or its uses ran out,
+    /// otherwise returns this Card. The use is only taken by [`Card::take_use`].
+    pub(crate) fn admit(
+        &mut self,
+        payload: &Card,
+        now: Timestamp,
+        grace: u64,
+    ) -> Result<Card, AccessError> {
+        // The registry is authoritative, an expiry can't be dropped by rewriting the tag.
+        let expired = self.is_expired(now.saturating_sub(grace));
+        if let Some(valid_until) = self.valid_until.filter(|_| expired) {
+            return Err(AccessError::Expired { valid_until });
+        }
+
+        if payload.counter < self.counter {
+            return Err(AccessError::Replayed {
+                expected: self.counter,
+                presented: payload.counter,
+            });
+        }
+
+        if self.uses == Some(0) {
+            return Err(AccessError::UsedUp(self.id));
+        }
+
+        Ok(*self)
+    }
+
+    /// Use up one of this Card's uses once it was granted access, returning the updated Card.
+    pub(crate) fn take_use(&mut self) -> Card {
+        if let Some(uses) = self.uses {
+            self.uses = Some(uses.saturating_sub(1));
+        }
+        *self
+    }
+}
+
+/// The size of the checksum ending a Card's payload, see [`Card::try_to_bytes`].
+pub const CHECKSUM_SIZE: usize = 2;
+
+/// The largest payload the service reads from a tag, see [`read_card`].
+pub const MAX_PAYLOAD: usize = 512;
+
+/// Read a tag through a kernel into a buffer on the stack and decode its payload,
+/// without allocating unless the payload is compressed or JSON.
+///
+/// Payloads that fail their checksum are reported as [`KernelError::Crc`].
+pub(crate) fn read_card<K: Kernel + ?Sized>(
+    kernel: &mut K,
+    card: Uid,
+) -> Result<Card, KernelError> {
+    let mut buf = [0; MAX_PAYLOAD];
+    let len = kernel.read_into(card, &mut buf)?;
+    let payload = buf.get(..len).ok_or(KernelError::Read { status: 0 })?;
+    Card::from_bytes(payload).map_err(|why| match why {
+        ConversionError::Corrupted { .. } => KernelError::Crc,
+        _ => KernelError::Read { status: 0 },
+    })
+}
+
+/// Split the checksum off the end of a payload, checking it matches the rest.
+fn checked<'a>(target: &'static str, bytes: &'a [u8]) -> Result<&'a [u8], ConversionError> {
+    let Some(split) = bytes.len().checked_sub(CHECKSUM_SIZE) else {
+        return Err(ConversionError::Corrupted {
+            target,
+            expected: crc::crc16(&[]),
+            found: None,
+        });
+    };
+
+    let (payload, crc) = bytes.split_at(split);
+    let expected = crc::crc16(payload);
+    let found = u16::from_le_bytes([crc[0], crc[1]]);
+    if expected != found {
+        return Err(ConversionError::Corrupted {
+            target,
+            expected,
+            found: Some(found),
+        });
     }
+    Ok(payload)
 }
 
-impl<'a> TryFrom<&'a [u8]> for Card {
-    type Error = ConversionError<'a>;
+/// The size of a Card's fixed wire representation, five NFC Forum type 2 pages.
+pub const WIRE_SIZE: usize = 20;
+
+/// The tag the fixed wire representation starts with.
+const WIRE_FORMAT: u8 = b'M';
+
+/// The size of the fixed wire representation written before permissions were widened,
+/// four NFC Forum type 2 pages.
+pub const LEGACY_WIRE_SIZE: usize = 16;
+
+/// The tag the legacy fixed wire representation starts with.
+const LEGACY_WIRE_FORMAT: u8 = b'L';
+
+#[allow(dead_code)]
+impl Card {
+    /// Encode this Card's id, permissions and key version into a fixed layout, without a serializer.
+    ///
+    /// The layout is the format tag, the UID length, the UID padded to 10 bytes,
+    /// the permission bits in little endian, the key version, a reserved zero byte
+    /// and a CRC-16 of the preceding bytes.
+    pub const fn to_array(&self) -> [u8; WIRE_SIZE] {
+        let mut bytes = [0; WIRE_SIZE];
+        bytes[0] = WIRE_FORMAT;
+        bytes[1] = self.id.len() as u8;
+
+        let id = self.id.as_bytes();
+        let mut i = 0;
+        while i < id.len() {
+            bytes[2 + i] = id[i];
+            i += 1;
+        }
+        let permissions = self.permissions.bits().to_le_bytes();
+        let mut i = 0;
+        while i < permissions.len() {
+            bytes[12 + i] = permissions[i];
+            i += 1;
+        }
+        bytes[16] = self.key_version;
+
+        let (data, _) = bytes.split_at(WIRE_SIZE - 2);
+        let crc = crc::crc16(data).to_le_bytes();
+        bytes[18] = crc[0];
+        bytes[19] = crc[1];
+        bytes
+    }
+
+    /// Decode a Card from its fixed layout, see [`Card::to_array`].
+    ///
+    /// Only the id, permissions and key version are carried, everything else is left at its default.
+    pub fn from_array(bytes: &[u8; WIRE_SIZE]) -> Result<Self, WireError> {
+        let [.., a, b, c, d, key_version, _, _, _] = *bytes;
+        Self::from_wire(
+            bytes,
+            WIRE_FORMAT,
+            u32::from_le_bytes([a, b, c, d]),
+            key_version,
+        )
+    }
+
+    /// Decode a Card from the fixed layout written before permissions were widened,
+    /// which carried the permission bits in a single byte.
+    pub fn from_legacy_array(bytes: &[u8; LEGACY_WIRE_SIZE]) -> Result<Self, WireError> {
+        Self::from_wire(bytes, LEGACY_WIRE_FORMAT, bytes[12] as u32, bytes[13])
+    }
+
+    fn from_wire(
+        bytes: &[u8],
+        format: u8,
+        permissions: u32,
+        key_version: KeyVersion,
+    ) -> Result<Self, WireError> {
+        if bytes[0] != format {
+            return Err(WireError::Format(bytes[0]));
+        }
+
+        let (data, crc) = bytes.split_at(bytes.len() - 2);
+        let expected = crc::crc16(data);
+        let found = u16::from_le_bytes([crc[0], crc[1]]);
+        if expected != found {
+            return Err(WireError::Checksum { expected, found });
+        }
+
+        let len = bytes[1];
+        let id = bytes
+            .get(2..2 + len as usize)
+            .and_then(Uid::new)
+            .ok_or(WireError::Uid(len))?;
+        let permissions =
+            Permissions::from_bits(permissions).ok_or(WireError::Permissions(permissions))?;
+        Ok(Self::new(id, permissions).with_key_version(key_version))
+    }
+}
+
+impl TryFrom<&[u8]> for Card {
+    type Error = ConversionError;
     /// Try to convert the given bytes into [Card] object.
     #[inline]
-    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
-        match serde_json::from_slice(value) {
-            Ok(emp) => return Ok(emp),
-            Err(..) => Err(ConversionError::new("Cant convert to Card", value)),
-        }
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(value)
     }
 }
 
@@ -116,30 +729,216 @@ fn m(x: Result<&str, ()>) -> Result<(), ()> {
 }
 
 impl TryInto<Vec<u8>> for Card {
-    type Error = ConversionError<'static>;
+    type Error = ConversionError;
 
     /// Try to convert the given card into [Vec<u8>] of bytes.
     #[inline]
     fn try_into(self) -> Result<Vec<u8>, Self::Error> {
-        match serde_json::to_vec(&self) {
+        match self.try_to_bytes() {
             Ok(bytes) => Ok(bytes),
-            Err(..) => Err(ConversionError {
-                message: "Invalid bytes to convert.",
-                // empty slice, the conversion failed.
-                bytes: &[],
-            }),
+            Err(..) => Err(ConversionError::serialize("Card")),
         }
     }
 }
 
+/// The identifier of a reader attached to the [`NfcService`].
+pub type ReaderId = u16;
+
+/// The card technology a reader talks to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Technology {
+    /// ISO 14443 type A tags, i.e. MIFARE Classic, Ultralight and DESFire.
+    #[default]
+    Iso14443A,
+    /// Sony FeliCa cards.
+    FeliCa,
+}
+
 /// An interface for a lower-level system that controls the NFC cards.
 /// For an example this can be an PN532 NFC reader/writer.
 #[allow(unused)]
 trait Kernel: Send + Sync + 'static {
-    fn read(&self, card: u16) -> Result<&Card, KernelError>;
-    fn read_mut(&mut self, card: u16) -> Result<&mut Card, KernelError>;
-    fn write(&self, card: &Card, data: &[u8]) -> Result<(), KernelError>;
-    fn sense(&self);
+    /// Read a tag, returning the card the kernel decoded from it.
+    ///
+    /// Kernels implement either this or [`Kernel::read_into`], the service reads tags
+    /// through the latter.
+    fn read(&self, card: Uid) -> Result<&Card, KernelError> {
+        let _ = card;
+        Err(KernelError::Unsupported("owned reads"))
+    }
+
+    /// Read a tag's payload into `buf`, returning the number of bytes read.
+    ///
+    /// The payload is decoded by the caller with [`Card::from_bytes`], so the kernel needn't
+    /// own any cards. By default the card [`Kernel::read`] returns is encoded into `buf`.
+    fn read_into(&mut self, card: Uid, buf: &mut [u8]) -> Result<usize, KernelError> {
+        self.read(card)?
+            .encode_into(buf)
+            .map_err(|_| KernelError::Read { status: 0 })
+    }
+
+    fn write(&mut self, card: &Card, data: &[u8]) -> Result<(), KernelError>;
+
+    /// The size of the pages [`Kernel::read_pages`] and [`Kernel::write_pages`] address,
+    /// `None` if it doesn't address pages.
+    const PAGE_SIZE: Option<usize> = None;
+
+    /// Read `buf.len()` bytes of a tag's memory from page `page` on.
+    ///
+    /// Kernels that only read whole payloads don't need to implement this.
+    fn read_pages(&mut self, card: Uid, page: usize, buf: &mut [u8]) -> Result<(), KernelError> {
+        let _ = (card, page, buf);
+        Err(KernelError::Unsupported("page reads"))
+    }
+
+    /// Write part of a tag's memory from page `page` on, leaving the other pages as they are.
+    /// The card's payload starts at page 0, see [`layout::CardLayout`].
+    ///
+    /// The last page written may be short, it's padded as by [`Kernel::write`].
+    /// Kernels that only write whole payloads don't need to implement this.
+    fn write_pages(&mut self, card: &Card, page: usize, data: &[u8]) -> Result<(), KernelError> {
+        let _ = (card, page, data);
+        Err(KernelError::Unsupported("page writes"))
+    }
+
+    /// Switch the card technology this kernel polls for.
+    ///
+    /// Kernels only supporting [`Technology::Iso14443A`] don't need to implement this.
+    fn set_technology(&mut self, technology: Technology) -> Result<(), KernelError> {
+        match technology {
+            Technology::Iso14443A => Ok(()),
+            _ => Err(KernelError::Unsupported("technology")),
+        }
+    }
+
+    /// Exchange a raw APDU with an ISO 14443-4 tag, returning the raw response.
+    ///
+    /// Kernels for plain memory tags don't need to implement this.
+    fn transceive(&mut self, apdu: &[u8]) -> Result<Vec<u8>, KernelError> {
+        let _ = apdu;
+        Err(KernelError::Unsupported("APDU exchange"))
+    }
+
+    /// Send the reader's challenge to a tag, returning the tag's answer.
+    ///
+    /// Kernels for tags without keys don't need to implement this.
+    fn challenge(&mut self, card: Uid, challenge: &Nonce) -> Result<Answer, KernelError> {
+        let _ = (card, challenge);
+        Err(KernelError::Unsupported("challenge-response"))
+    }
+
+    /// Send the reader's proof to a tag after checking its answer.
+    fn confirm(&mut self, card: Uid, proof: &Mac) -> Result<(), KernelError> {
+        let _ = (card, proof);
+        Err(KernelError::Unsupported("challenge-response"))
+    }
+
+    /// The largest chunk of a firmware image [`Kernel::write_firmware`] is given.
+    const FIRMWARE_CHUNK: usize = 256;
+
+    /// The firmware version this reader runs.
+    fn firmware_version(&mut self) -> Result<FirmwareVersion, KernelError> {
+        Err(KernelError::Unsupported("firmware version"))
+    }
+
+    /// Prepare the reader to receive a firmware image of `len` bytes.
+    fn begin_firmware(&mut self, len: usize) -> Result<(), KernelError> {
+        let _ = len;
+        Err(KernelError::Unsupported("firmware update"))
+    }
+
+    /// Write a chunk of the firmware image at `offset`.
+    fn write_firmware(&mut self, offset: usize, chunk: &[u8]) -> Result<(), KernelError> {
+        let _ = (offset, chunk);
+        Err(KernelError::Unsupported("firmware update"))
+    }
+
+    /// Verify and boot into the written image, returning the version it reports.
+    fn finish_firmware(&mut self) -> Result<FirmwareVersion, KernelError> {
+        Err(KernelError::Unsupported("firmware update"))
+    }
+
+    /// Write a whole firmware image in [`Kernel::FIRMWARE_CHUNK`] sized chunks.
+    fn update_firmware(
+        &mut self,
+        image: &[u8],
+        progress: &mut dyn FnMut(Progress),
+    ) -> Result<FirmwareVersion, KernelError> {
+        self.begin_firmware(image.len())?;
+        let mut written = 0;
+        for chunk in image.chunks(Self::FIRMWARE_CHUNK.max(1)) {
+            self.write_firmware(written, chunk)?;
+            written += chunk.len();
+            progress(Progress {
+                written,
+                total: image.len(),
+            });
+        }
+        self.finish_firmware()
+    }
+
+    /// Check the reader's EEPROM reads back correctly.
+    fn check_eeprom(&mut self) -> Result<(), KernelError> {
+        Err(KernelError::Unsupported("EEPROM check"))
+    }
+
+    /// Run the reader's self-test.
+    ///
+    /// The RF field is checked by polling it once, i.e. [`Kernel::sense`].
+    fn self_test(&mut self) -> HealthReport {
+        HealthReport {
+            rf_field: Check::from_result(self.sense()),
+            eeprom: Check::from_result(self.check_eeprom()),
+            firmware: self.firmware_version().ok(),
+        }
+    }
+
+    /// Turn the RF field off and put the reader in its low-power mode.
+    ///
+    /// Readers without a low-power mode don't need to implement this, they stay awake.
+    fn sleep(&mut self) -> Result<(), KernelError> {
+        Ok(())
+    }
+
+    /// Bring the reader back from [`Kernel::sleep`], with its RF field ready to poll.
+    fn wake(&mut self) -> Result<(), KernelError> {
+        Ok(())
+    }
+
+    /// Wait before retrying a failed operation, see [`retry::RetryPolicy`].
+    ///
+    /// The current thread sleeps with the `std` feature. Otherwise kernels should wait on
+    /// a timer of their own, by default they retry right away.
+    fn delay(&mut self, duration: Duration) {
+        #[cfg(feature = "std")]
+        std::thread::sleep(duration);
+        #[cfg(not(feature = "std"))]
+        let _ = duration;
+    }
+
+    /// Report whether the reader's tamper sensors tripped, i.e. its case switch opened.
+    ///
+    /// Readers without tamper sensors don't need to implement this.
+    fn tamper(&mut self) -> Result<Option<Tamper>, KernelError> {
+        Ok(None)
+    }
+
+    /// The time a single [`Kernel::sense`] poll takes.
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Poll the RF field once, returning the tag that's present if any.
+    fn sense(&mut self) -> Result<Option<Uid>, KernelError>;
+
+    /// Keep polling the RF field until a tag is present or `timeout` elapses.
+    fn sense_timeout(&mut self, timeout: Duration) -> Result<Option<Uid>, KernelError> {
+        let polls = timeout.as_millis() / Self::POLL_INTERVAL.as_millis().max(1);
+        for _ in 0..polls.max(1) {
+            if let Some(uid) = self.sense()? {
+                return Ok(Some(uid));
+            }
+        }
+        Ok(None)
+    }
 }
 
 /// The base system implementation that [`NfcService`] uses.
@@ -156,21 +955,17 @@ impl SystemBase {
 
 #[allow(unused_variables)]
 impl Kernel for SystemBase {
-    fn read(&self, card: u16) -> Result<&Card, KernelError> {
+    fn read(&self, card: Uid) -> Result<&Card, KernelError> {
         unimplemented!("Read a card from the database")
     }
 
-    fn read_mut(&mut self, card: u16) -> Result<&mut Card, KernelError> {
-        unimplemented!("Read a card from the database?")
-    }
-
-    fn write(&self, card: &Card, data: &[u8]) -> Result<(), KernelError> {
+    fn write(&mut self, card: &Card, data: &[u8]) -> Result<(), KernelError> {
         unimplemented!("Read a card from the database")
     }
 
-    fn sense(&self) {
+    fn sense(&mut self) -> Result<Option<Uid>, KernelError> {
         static _CARDS: [Card; 0] = [];
-        while let Some(_card) = _CARDS.iter().next() {}
+        Ok(_CARDS.first().map(|card| card.id))
     }
 }
 
@@ -179,8 +974,41 @@ struct NfcService<S>
 where
     S: Kernel,
 {
-    cards: BTreeMap<u16, Card>,
-    system: S,
+    cards: BTreeMap<Uid, Card>,
+    revoked: RevocationList,
+    policy: AccessPolicy,
+    lockouts: Lockouts,
+    audit: AuditLog,
+    audit_sinks: Vec<Box<dyn AuditSink>>,
+    history: PermissionHistory,
+    events: Vec<TracedEvent>,
+    subscribers: Subscribers,
+    next_subscription: u32,
+    readers: BTreeMap<ReaderId, S>,
+    technologies: BTreeMap<ReaderId, Technology>,
+    clock: Option<Box<dyn Clock + Send>>,
+    authenticator: Option<Box<dyn Authenticator + Send>>,
+    escalations: EscalationQueue,
+    encoding: EncodingQueue,
+    tours: GuardTours,
+    tampers: Tampers,
+    alarms: Alarms,
+    transactions: Transactions,
+    elevations: BTreeMap<Uid, Elevation>,
+    duress: BTreeMap<Uid, Uid>,
+    /// The first card presented to each two-person door and when.
+    first_cards: BTreeMap<DoorId, (Uid, Timestamp)>,
+    capacity: Capacity,
+    /// The seconds cards keep being granted access after they expired.
+    expiry_grace: u64,
+    position_defaults: PositionDefaults,
+    retry: RetryPolicy,
+    metrics: InMemoryMetrics,
+    metrics_sinks: Vec<Box<dyn Metrics>>,
+    power: PowerState,
+    maintenance: Maintenance,
+    #[cfg(feature = "snapshot")]
+    snapshots: Option<snapshot::SnapshotHandle>,
 }
 
 impl<K> fmt::Debug for NfcService<K>
@@ -189,7 +1017,13 @@ where
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Service")
+            .field("readers", &self.readers.len())
             .field("cards", &self.cards.len())
+            .field("revoked", &self.revoked.len())
+            .field("doors", &self.policy.doors().count())
+            .field("audit", &self.audit.len())
+            .field("events", &self.events.len())
+            .field("subscribers", &self.subscribers.len())
             .finish()
     }
 }
@@ -205,30 +1039,146 @@ impl<K> NfcService<K>
 where
     K: Kernel,
 {
+    /// The reader a system provider is attached as by [`NfcService::new_in`].
+    pub const DEFAULT_READER: ReaderId = 0;
+
     /// Create a new basic `NfcService`.
     #[must_use]
     #[inline]
-    pub const fn new() -> NfcService<SystemBase> {
-        NfcService {
-            system: SystemBase::Global,
-            cards: BTreeMap::new(),
-        }
+    pub fn new() -> NfcService<SystemBase> {
+        NfcService::new_in(SystemBase::Global)
+    }
+
+    /// Create a new NfcService with a system provider attached as [`Self::DEFAULT_READER`].
+    #[must_use]
+    #[inline]
+    pub fn new_in(system: K) -> NfcService<K> {
+        let mut this = Self::empty();
+        let _ = this.attach(Self::DEFAULT_READER, system);
+        this
     }
 
-    /// Create a new NfcService with a system provider.
+    /// Create a new NfcService without any readers attached.
     #[must_use]
     #[inline]
-    pub const fn new_in(system: K) -> NfcService<K> {
+    pub const fn empty() -> NfcService<K> {
         Self {
-            system,
+            readers: BTreeMap::new(),
+            technologies: BTreeMap::new(),
             cards: BTreeMap::new(),
+            revoked: RevocationList::new(),
+            policy: AccessPolicy::new(),
+            lockouts: Lockouts::new(LockoutConfig::DEFAULT),
+            audit: AuditLog::new(),
+            audit_sinks: Vec::new(),
+            history: PermissionHistory::new(),
+            events: Vec::new(),
+            subscribers: BTreeMap::new(),
+            next_subscription: 0,
+            clock: None,
+            authenticator: None,
+            escalations: EscalationQueue::new(),
+            encoding: EncodingQueue::new(),
+            tours: GuardTours::new(),
+            tampers: Tampers::new(),
+            alarms: Alarms::new(),
+            transactions: Transactions::new(),
+            elevations: BTreeMap::new(),
+            duress: BTreeMap::new(),
+            first_cards: BTreeMap::new(),
+            capacity: Capacity::new(),
+            expiry_grace: 0,
+            position_defaults: PositionDefaults::STANDARD,
+            retry: RetryPolicy::DEFAULT,
+            metrics: InMemoryMetrics::new(),
+            metrics_sinks: Vec::new(),
+            power: PowerState::new(PowerSchedule::DEFAULT),
+            maintenance: Maintenance::new(MaintenanceSchedule::DEFAULT),
+            #[cfg(feature = "snapshot")]
+            snapshots: None,
         }
     }
 
-    /// Return a reference to the current kernel of this service.
+    /// Use `clock` to timestamp audits and check expiry and schedules.
+    #[must_use]
+    pub fn with_clock<C>(mut self, clock: C) -> Self
+    where
+        C: Clock + Send + 'static,
+    {
+        self.set_clock(clock);
+        self
+    }
+
+    /// Replace the clock of this service, see [`NfcService::with_clock`].
+    pub fn set_clock<C>(&mut self, clock: C)
+    where
+        C: Clock + Send + 'static,
+    {
+        self.clock = Some(Box::new(clock));
+    }
+
+    /// The current time according to the clock of this service.
+    ///
+    /// Without a clock, this is the [`SystemClock`](clock::SystemClock) with `std`
+    /// and the unix epoch otherwise, which leaves every card unexpired.
+    pub fn now(&self) -> Timestamp {
+        match self.clock {
+            Some(ref clock) => clock.now(),
+            #[cfg(feature = "std")]
+            None => clock::SystemClock.now(),
+            #[cfg(not(feature = "std"))]
+            None => 0,
+        }
+    }
+
+    /// Attach a reader to this service, returning the one previously attached with the same id.
+    pub fn attach(&mut self, reader: ReaderId, system: K) -> Option<K> {
+        self.readers.insert(reader, system)
+    }
+
+    /// Detach a reader from this service.
+    pub fn detach(&mut self, reader: ReaderId) -> Option<K> {
+        let _ = self.technologies.remove(&reader);
+        self.readers.remove(&reader)
+    }
+
+    /// Switch the card technology a reader polls for.
+    pub fn set_technology(
+        &mut self,
+        reader: ReaderId,
+        technology: Technology,
+    ) -> Result<(), AccessError> {
+        let kernel = self
+            .readers
+            .get_mut(&reader)
+            .ok_or(AccessError::UnknownReader(reader))?;
+        kernel
+            .set_technology(technology)
+            .map_err(|why| kernel_error(reader, why))?;
+        let _ = self.technologies.insert(reader, technology);
+        Ok(())
+    }
+
+    /// The card technology a reader polls for.
+    pub fn technology(&self, reader: ReaderId) -> Technology {
+        self.technologies.get(&reader).copied().unwrap_or_default()
+    }
+
+    /// An iterator over the ids of the attached readers.
+    pub fn readers(&self) -> impl Iterator<Item = ReaderId> + '_ {
+        self.readers.keys().copied()
+    }
+
+    /// Return a reference to the kernel of a reader.
     #[inline]
-    pub const fn kernel<'a>(&'a self) -> &'a K {
-        &self.system
+    pub fn kernel(&self, reader: ReaderId) -> Option<&K> {
+        self.readers.get(&reader)
+    }
+
+    /// Return a mutable reference to the kernel of a reader.
+    #[inline]
+    pub fn kernel_mut(&mut self, reader: ReaderId) -> Option<&mut K> {
+        self.readers.get_mut(&reader)
     }
 
     #[inline]
@@ -236,35 +1186,1028 @@ where
         self.cards.len()
     }
 
+    /// A copy of every registered card, see [`NfcService::iter`] to borrow them instead.
     pub fn cards(&self) -> Box<[Card]> {
         self.cards.values().copied().collect()
     }
 
-    pub fn unbind(&mut self, card_id: &u16) -> Option<Card> {
-        self.cards.remove(card_id)
+    pub fn unbind(&mut self, card_id: &Uid) -> Option<Card> {
+        let card = self.cards.remove(card_id)?;
+        let _ = self.duress.remove(card_id);
+        let _ = self.elevations.remove(card_id);
+        self.capacity.forget(card_id);
+        self.emit(NfcEvent::CardRemoved { id: card.id });
+        self.registry_changed();
+        Some(card)
+    }
+
+    /// Unbind a card on behalf of an admin, recording it in the audit log.
+    pub fn remove(&mut self, card_id: Uid) -> Result<Card, AccessError> {
+        let now = self.now();
+        let card = self.unbind(&card_id).ok_or(AccessError::Unknown(card_id));
+        self.log(
+            card_id,
+            now,
+            Origin::Admin,
+            AuditAction::Unbind,
+            card.map(|_| ()),
+        );
+        card
     }
 
     pub fn put(&mut self, card: Card) {
         let _ = self.cards.insert(card.id, card);
+        self.capacity.touch(card.id);
+        self.emit(NfcEvent::CardEnrolled { id: card.id });
+        self.evict();
+        self.registry_changed();
+    }
+
+    /// Publish the registry to every snapshot handle after changing it, see
+    /// [`NfcService::publish`].
+    #[inline]
+    pub(crate) fn registry_changed(&mut self) {
+        #[cfg(feature = "snapshot")]
+        self.publish();
     }
 
-    pub fn get(&self, card_id: &u16) -> Option<&Card> {
+    pub fn get(&self, card_id: &Uid) -> Option<&Card> {
         self.cards.get(card_id)
     }
 
-    pub fn contains(&self, card_id: &u16) -> bool {
+    /// Find the card issued to an employee.
+    pub fn find_employee(&self, employee: u32) -> Option<&Card> {
+        self.find(|card| card.holder.employee() == Some(employee))
+    }
+
+    pub fn contains(&self, card_id: &Uid) -> bool {
         self.cards.contains_key(card_id)
     }
+
+    /// Grant additional permissions to a registered card.
+    pub fn grant(&mut self, card_id: Uid, perms: Permissions) -> Result<(), AccessError> {
+        let now = self.now();
+        let result = match self.cards.get_mut(&card_id) {
+            Some(card) => {
+                let old = card.permissions;
+                card.permissions.insert(perms);
+                self.history.record(card_id, now, old, card.permissions);
+                Ok(())
+            }
+            None => Err(AccessError::Unknown(card_id)),
+        };
+        self.log(
+            card_id,
+            now,
+            Origin::Admin,
+            AuditAction::Grant(perms),
+            result,
+        );
+        if result.is_ok() {
+            self.registry_changed();
+        }
+        result
+    }
+
+    /// Replace the permissions of a registered card.
+    pub fn set_permissions(&mut self, card_id: Uid, perms: Permissions) -> Result<(), AccessError> {
+        let now = self.now();
+        let result = match self.cards.get_mut(&card_id) {
+            Some(card) => {
+                self.history.record(card_id, now, card.permissions, perms);
+                card.permissions = perms;
+                Ok(())
+            }
+            None => Err(AccessError::Unknown(card_id)),
+        };
+        self.log(
+            card_id,
+            now,
+            Origin::Admin,
+            AuditAction::SetPermissions(perms),
+            result,
+        );
+        if result.is_ok() {
+            self.registry_changed();
+        }
+        result
+    }
+
+    /// An immutable reference to the permission history of this service.
+    #[inline]
+    pub fn permission_history(&self) -> &PermissionHistory {
+        &self.history
+    }
+
+    /// The cards that held all of `perms` at any moment between `from` and `until`.
+    ///
+    /// Cards unbound since are included as long as their permissions changed through this service.
+    pub fn held_permissions(
+        &self,
+        perms: Permissions,
+        from: Timestamp,
+        until: Timestamp,
+    ) -> Vec<Uid> {
+        let mut ids = self.cards.keys().copied().collect::<Vec<_>>();
+        ids.extend(
+            self.history
+                .cards()
+                .filter(|id| !self.cards.contains_key(id)),
+        );
+        ids.retain(|id| {
+            // Unbound cards kept whatever their last change left them with.
+            let current = match self.cards.get(id) {
+                Some(card) => card.permissions,
+                None => self
+                    .history
+                    .of(*id)
+                    .last()
+                    .map_or(Permissions::empty(), |c| c.new),
+            };
+            self.history.held(*id, perms, from, until, current)
+        });
+        ids.sort();
+        ids
+    }
+
+    /// Define a new role. Returns its id or `None` if no more roles can be defined.
+    pub fn define_role(&mut self, role: Role) -> Option<RoleId> {
+        self.policy.roles_mut().define(role)
+    }
+
+    /// Remove a role, unassigning it from every registered card so its id can be given
+    /// to a new role.
+    pub fn remove_role(&mut self, id: RoleId) -> Option<Role> {
+        let role = self.policy.roles_mut().remove(id)?;
+        for card in self.cards.values_mut() {
+            let _ = card.roles.remove(id);
+        }
+        self.policy.roles_mut().release(id);
+        self.registry_changed();
+        Some(role)
+    }
+
+    /// Assign a defined role to a registered card.
+    pub fn assign_role(&mut self, card_id: Uid, role: RoleId) -> Result<(), AccessError> {
+        let now = self.now();
+        let result = match self.cards.get_mut(&card_id) {
+            Some(_) if self.policy.roles().get(role).is_none() => {
+                Err(AccessError::UnknownRole(role))
+            }
+            Some(card) => {
+                let _ = card.roles.insert(role);
+                Ok(())
+            }
+            None => Err(AccessError::Unknown(card_id)),
+        };
+        self.log(
+            card_id,
+            now,
+            Origin::Admin,
+            AuditAction::AssignRole(role),
+            result,
+        );
+        if result.is_ok() {
+            self.registry_changed();
+        }
+        result
+    }
+
+    /// Remove a role from a registered card.
+    pub fn unassign_role(&mut self, card_id: Uid, role: RoleId) -> Result<(), AccessError> {
+        let now = self.now();
+        let result = match self.cards.get_mut(&card_id) {
+            Some(card) => {
+                let _ = card.roles.remove(role);
+                Ok(())
+            }
+            None => Err(AccessError::Unknown(card_id)),
+        };
+        self.log(
+            card_id,
+            now,
+            Origin::Admin,
+            AuditAction::UnassignRole(role),
+            result,
+        );
+        if result.is_ok() {
+            self.registry_changed();
+        }
+        result
+    }
+
+    /// Revoke a card, it will be denied on every access decision until reinstated.
+    ///
+    /// The card stays registered. Returns `false` if it isn't registered or was already
+    /// revoked.
+    pub fn revoke(&mut self, card_id: Uid) -> bool {
+        self.try_revoke(card_id).is_ok()
+    }
+
+    /// Revoke a card as [`NfcService::revoke`] does, failing with why it wasn't.
+    pub(crate) fn try_revoke(&mut self, card_id: Uid) -> Result<(), AccessError> {
+        let now = self.now();
+        let result = if !self.cards.contains_key(&card_id) {
+            Err(AccessError::Unknown(card_id))
+        } else if self.revoked.revoke(card_id) {
+            Ok(())
+        } else {
+            Err(AccessError::Revoked(card_id))
+        };
+        self.log(card_id, now, Origin::Admin, AuditAction::Revoke, result);
+        if result.is_ok() {
+            self.registry_changed();
+        }
+        result
+    }
+
+    /// Lift the revocation of a card. Returns `false` if it wasn't revoked.
+    pub fn reinstate(&mut self, card_id: Uid) -> bool {
+        let now = self.now();
+        let result = if self.revoked.reinstate(card_id) {
+            Ok(())
+        } else if self.cards.contains_key(&card_id) {
+            Err(AccessError::NotRevoked(card_id))
+        } else {
+            Err(AccessError::Unknown(card_id))
+        };
+        self.log(card_id, now, Origin::Admin, AuditAction::Reinstate, result);
+        if result.is_ok() {
+            self.registry_changed();
+        }
+        result.is_ok()
+    }
+
+    /// An immutable reference to the access policy of this service.
+    #[inline]
+    pub const fn policy(&self) -> &AccessPolicy {
+        &self.policy
+    }
+
+    /// A mutable reference to the access policy of this service.
+    #[inline]
+    pub fn policy_mut(&mut self) -> &mut AccessPolicy {
+        &mut self.policy
+    }
+
+    /// Poll a reader's RF field once, returning the tag that's present if any.
+    ///
+    /// Emits [`NfcEvent::CardDetected`] when a tag is present, beginning its transaction.
+    pub fn sense(&mut self, reader: ReaderId) -> Result<Option<Uid>, AccessError> {
+        let Some(kernel) = self.readers.get_mut(&reader) else {
+            return Err(AccessError::UnknownReader(reader));
+        };
+
+        let uid = kernel.sense().map_err(|why| kernel_error(reader, why))?;
+        self.detected(reader, uid);
+        Ok(uid)
+    }
+
+    /// Keep polling a reader's RF field until a tag is present or `timeout` elapses.
+    pub fn sense_timeout(
+        &mut self,
+        reader: ReaderId,
+        timeout: Duration,
+    ) -> Result<Option<Uid>, AccessError> {
+        let Some(kernel) = self.readers.get_mut(&reader) else {
+            return Err(AccessError::UnknownReader(reader));
+        };
+
+        let uid = kernel
+            .sense_timeout(timeout)
+            .map_err(|why| kernel_error(reader, why))?;
+        self.detected(reader, uid);
+        Ok(uid)
+    }
+
+    /// Begin the transaction of a tag a reader detected, or end the reader's transaction
+    /// if its field is empty.
+    fn detected(&mut self, reader: ReaderId, uid: Option<Uid>) {
+        match uid {
+            Some(id) => {
+                let _ = self.transactions.begin(reader, id);
+                self.emit(NfcEvent::CardDetected { reader, id });
+            }
+            None => {
+                let _ = self.transactions.end(reader);
+            }
+        }
+    }
+
+    /// Poll every attached reader once, returning the tags that are present.
+    ///
+    /// Readers which fail to poll are skipped.
+    pub fn poll(&mut self) -> Vec<(ReaderId, Uid)> {
+        let polled = self
+            .readers
+            .iter_mut()
+            .filter_map(|(&reader, kernel)| {
+                let uid = kernel.sense().map_err(|why| kernel_error(reader, why));
+                Some((reader, uid.ok()?))
+            })
+            .collect::<Vec<_>>();
+        let mut detected = Vec::new();
+        for (reader, uid) in polled {
+            self.detected(reader, uid);
+            if let Some(id) = uid {
+                detected.push((reader, id));
+            }
+        }
+        detected
+    }
+
+    /// Exchange a command APDU with the tag in a reader's field.
+    ///
+    /// Remaining response data signaled by `61XX` is fetched and appended, and
+    /// commands answered with `6CXX` are reissued with the right Le.
+    pub fn exchange(
+        &mut self,
+        reader: ReaderId,
+        command: &Command,
+    ) -> Result<Response, AccessError> {
+        let kernel = self
+            .readers
+            .get_mut(&reader)
+            .ok_or(AccessError::UnknownReader(reader))?;
+        let mut transceive = |command: &Command| {
+            kernel
+                .transceive(&command.encode())
+                .map_err(|why| kernel_error(reader, why))
+                .and_then(|bytes| Response::parse(&bytes).map_err(|_| AccessError::Kernel))
+        };
+
+        let mut response = transceive(command)?;
+        if let Some(le) = response.status().wrong_le() {
+            response = transceive(&command.clone().le(if le == 0 { 256 } else { le as u32 }))?;
+        }
+        while let Some(remaining) = response.status().bytes_remaining() {
+            let rest = transceive(&Command::get_response(remaining))?;
+            response.extend(rest);
+        }
+        Ok(response)
+    }
+
+    /// Talk to the DESFire tag in a reader's field.
+    pub fn desfire(&mut self, reader: ReaderId) -> Result<Desfire<'_, K>, AccessError> {
+        self.readers
+            .get_mut(&reader)
+            .map(Desfire::new)
+            .ok_or(AccessError::UnknownReader(reader))
+    }
+
+    /// Install a door this service controls.
+    pub fn install_door(&mut self, door: Door) -> Option<Door> {
+        self.policy.install(door)
+    }
+
+    /// Authorize a card presented to a reader and decide whether it may open a door.
+    pub fn open(
+        &mut self,
+        reader: ReaderId,
+        payload: &Card,
+        door_id: DoorId,
+    ) -> Result<Card, Traced<AccessError>> {
+        self.transact_traced(reader, payload.id, |this| {
+            let now = this.now();
+            let result = this.admit(reader, payload, now).and_then(|card| {
+                match this.policy.decide(&card, door_id, now) {
+                    Decision::Granted => this.second_card(&card, door_id, now),
+                    Decision::Denied(reason) => Err(AccessError::Denied {
+                        door: door_id,
+                        reason,
+                    }),
+                }?;
+                // Only a door that opens takes a use.
+                Ok(this.take_use(reader, card, now))
+            });
+            this.report(reader, payload.id, Some(door_id), &result, now);
+            this.log(
+                payload.id,
+                now,
+                Origin::Reader(reader),
+                AuditAction::Open(door_id),
+                result.map(|_| ()),
+            );
+            result
+        })
+    }
+
+    /// An immutable reference to the audit log of this service.
+    #[inline]
+    pub const fn audit(&self) -> &AuditLog {
+        &self.audit
+    }
+
+    #[inline]
+    fn log(
+        &mut self,
+        card: Uid,
+        at: Timestamp,
+        origin: Origin,
+        action: AuditAction,
+        result: Result<(), AccessError>,
+    ) {
+        let transaction = match origin {
+            Origin::Reader(reader) => Some(self.transactions.of(reader, card)),
+            Origin::Admin => None,
+        };
+        let entry = AuditEntry {
+            card,
+            at,
+            origin,
+            action,
+            result,
+            transaction,
+        };
+        for sink in &mut self.audit_sinks {
+            sink.record(&entry);
+        }
+        self.audit.record(entry);
+    }
+
+    /// Write every audit entry to a sink as well as it's recorded.
+    pub fn add_audit_sink<S: AuditSink + 'static>(&mut self, sink: S) {
+        self.audit_sinks.push(Box::new(sink));
+    }
+
+    /// An immutable reference to the revocation list of this service.
+    #[inline]
+    pub const fn revocations(&self) -> &RevocationList {
+        &self.revoked
+    }
+
+    /// Replace the revocation list of this service, i.e. one restored from storage.
+    pub fn restore_revocations(&mut self, revoked: RevocationList) {
+        self.revoked = revoked;
+        self.registry_changed();
+    }
+
+    /// Persist the registered cards and the revocation list to a store.
+    pub fn persist<S: CardStore>(&self, store: &mut S) -> Result<(), StoreError<S::Error>> {
+        store.save_cards(&self.cards())?;
+        store.save_revocations(&self.revoked)
+    }
+
+    /// Restore the registered cards and the revocation list from a store.
+    ///
+    /// Cards already registered with the same id are replaced.
+    pub fn restore<S: CardStore>(&mut self, store: &mut S) -> Result<(), StoreError<S::Error>> {
+        let cards = store.load_cards()?;
+        self.revoked = store.load_revocations()?;
+        self.cards
+            .extend(cards.into_iter().map(|card| (card.id, card)));
+        self.evict();
+        self.registry_changed();
+        Ok(())
+    }
+
+    /// Take all the events emitted since the last call.
+    pub fn drain_events(&mut self) -> Vec<NfcEvent> {
+        self.drain_traced_events()
+            .into_iter()
+            .map(|traced| traced.event)
+            .collect()
+    }
+
+    /// Get notified of every event as it's emitted.
+    pub fn subscribe<S: Subscriber + 'static>(&mut self, subscriber: S) -> SubscriptionId {
+        let id = SubscriptionId(self.next_subscription);
+        self.next_subscription = self.next_subscription.wrapping_add(1);
+        let _ = self.subscribers.insert(id, Box::new(subscriber));
+        id
+    }
+
+    /// Stop notifying a subscriber. Returns `false` if it wasn't subscribed.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.subscribers.remove(&id).is_some()
+    }
+
+    /// Notify the subscribers of an event and queue it to be drained.
+    fn emit(&mut self, event: NfcEvent) {
+        let transaction = match (event.reader(), event.card()) {
+            (Some(reader), Some(card)) => Some(self.transactions.of(reader, card)),
+            (Some(reader), None) => self.transactions.open(reader),
+            (None, _) => None,
+        };
+        let event = TracedEvent { transaction, event };
+        logging::log_debug!("{:?}", event);
+        for subscriber in self.subscribers.values_mut() {
+            subscriber.on_traced(&event);
+        }
+        self.events.push(event);
+    }
+
+    /// Change the thresholds for locking cards and readers out after repeated denials.
+    pub fn set_lockout(&mut self, config: LockoutConfig) {
+        self.lockouts.configure(config);
+    }
+
+    /// An immutable reference to the lockout tracker of this service.
+    #[inline]
+    pub const fn lockouts(&self) -> &Lockouts {
+        &self.lockouts
+    }
+
+    /// Lift the lockout of a card or reader. Returns `false` if it wasn't tracked.
+    pub fn unlock(&mut self, target: LockoutTarget) -> bool {
+        self.lockouts.unlock(target)
+    }
+
+    /// Emit the outcome of an access decision made at `now`, locking out after repeated denials.
+    fn report(
+        &mut self,
+        reader: ReaderId,
+        id: Uid,
+        door: Option<DoorId>,
+        result: &Result<Card, AccessError>,
+        now: Timestamp,
+    ) {
+        match *result {
+            Ok(..) => self.count(reader, Counter::Grants),
+            Err(AccessError::Kernel | AccessError::UnknownReader(..)) => {}
+            Err(..) => self.count(reader, Counter::Denials),
+        }
+        match *result {
+            Ok(..) => {
+                self.lockouts.grant(LockoutTarget::Card(id));
+                self.lockouts.grant(LockoutTarget::Reader(reader));
+            }
+            // Attempts while locked out don't extend the lockout, neither do first cards of
+            // a two-person rule.
+            Err(
+                AccessError::LockedOut { .. }
+                | AccessError::UnknownReader(..)
+                | AccessError::Denied {
+                    reason: DenyReason::AwaitingSecondCard { .. },
+                    ..
+                },
+            ) => {}
+            Err(..) => {
+                for target in [LockoutTarget::Card(id), LockoutTarget::Reader(reader)] {
+                    if let Some(until) = self.lockouts.deny(target, now) {
+                        self.emit(NfcEvent::Lockout {
+                            reader,
+                            target,
+                            until,
+                        });
+                    }
+                }
+            }
+        }
+
+        let access = AccessEvent::new(
+            Some(self.transactions.of(reader, id)),
+            id,
+            reader,
+            result,
+            now,
+        );
+        for subscriber in self.subscribers.values_mut() {
+            subscriber.on_access(&access);
+        }
+        self.emit(match *result {
+            Ok(card) => NfcEvent::AccessGranted {
+                reader,
+                id,
+                door,
+                remaining: card.uses,
+            },
+            Err(AccessError::Denied {
+                door,
+                reason: DenyReason::AwaitingSecondCard { until },
+            }) => NfcEvent::AwaitingSecondCard {
+                reader,
+                id,
+                door,
+                until,
+            },
+            Err(reason) => NfcEvent::AccessDenied {
+                reader,
+                id,
+                door,
+                reason,
+            },
+        });
+    }
+
+    /// Keep granting access to cards for `grace` seconds after they expired, so renewals
+    /// processed late don't lock their holders out.
+    ///
+    /// [`NfcEvent::CardInGrace`] is emitted every time a card is granted access within
+    /// its grace period.
+    #[must_use]
+    pub fn with_expiry_grace(mut self, grace: u64) -> Self {
+        self.set_expiry_grace(grace);
+        self
+    }
+
+    /// Replace the expiry grace period of this service, see [`NfcService::with_expiry_grace`].
+    #[inline]
+    pub fn set_expiry_grace(&mut self, grace: u64) {
+        self.expiry_grace = grace;
+    }
+
+    /// The seconds cards keep being granted access after they expired, none by default.
+    #[inline]
+    pub const fn expiry_grace(&self) -> u64 {
+        self.expiry_grace
+    }
+
+    /// Return the cards expiring within `window` seconds from now.
+    ///
+    /// Cards which already expired are not included.
+    pub fn expiring(&self, window: u64) -> Box<[Card]> {
+        let now = self.now();
+        let deadline = now.saturating_add(window);
+        self.cards
+            .values()
+            .filter(
+                |card| matches!(card.valid_until, Some(until) if until > now && until <= deadline),
+            )
+            .copied()
+            .collect()
+    }
+
+    /// Authorize a card payload that was presented to a reader.
+    ///
+    /// Revoked cards are rejected and [`NfcEvent::CardRevoked`] is emitted.
+    /// Expired cards are rejected and [`NfcEvent::CardExpired`] is emitted.
+    /// The payload's counter must not be behind the one last written to the tag,
+    /// otherwise the card is considered cloned and [`NfcEvent::ClonedCard`] is emitted.
+    /// On success the updated card is returned, the counter is only bumped by
+    /// [`NfcService::write`].
+    pub fn authorize(
+        &mut self,
+        reader: ReaderId,
+        payload: &Card,
+    ) -> Result<Card, Traced<AccessError>> {
+        self.transact_traced(reader, payload.id, |this| {
+            let now = this.now();
+            let result = this.decide(reader, payload, now);
+            this.report(reader, payload.id, None, &result, now);
+            this.log(
+                payload.id,
+                now,
+                Origin::Reader(reader),
+                AuditAction::Access,
+                result.map(|_| ()),
+            );
+            result
+        })
+    }
+
+    /// Authorize a payload and take a use of its card, see [`NfcService::authorize`].
+    pub(crate) fn decide(
+        &mut self,
+        reader: ReaderId,
+        payload: &Card,
+        now: Timestamp,
+    ) -> Result<Card, AccessError> {
+        let card = self.admit(reader, payload, now)?;
+        Ok(self.take_use(reader, card, now))
+    }
+
+    /// Authorize a payload without taking a use of its card, for access that may still be
+    /// denied by the door policy.
+    pub(crate) fn admit(
+        &mut self,
+        reader: ReaderId,
+        payload: &Card,
+        now: Timestamp,
+    ) -> Result<Card, AccessError> {
+        if !self.readers.contains_key(&reader) {
+            return Err(AccessError::UnknownReader(reader));
+        }
+
+        let locked = [
+            LockoutTarget::Card(payload.id),
+            LockoutTarget::Reader(reader),
+        ]
+        .into_iter()
+        .filter_map(|target| self.lockouts.locked_until(target, now))
+        .max();
+        if let Some(until) = locked {
+            return Err(AccessError::LockedOut { until });
+        }
+
+        if self.revoked.is_revoked(payload.id) {
+            self.emit(NfcEvent::CardRevoked {
+                reader,
+                id: payload.id,
+            });
+            return Err(AccessError::Revoked(payload.id));
+        }
+
+        self.revert_elevation(payload.id, Origin::Reader(reader), now);
+        let Some(card) = self.cards.get_mut(&payload.id) else {
+            return Err(AccessError::Unknown(payload.id));
+        };
+
+        let result = card.admit(payload, now, self.expiry_grace).map(|card| {
+            self.expire_permissions(card.id, Origin::Reader(reader), now);
+            self.cards.get(&card.id).copied().unwrap_or(card)
+        });
+        match result {
+            Ok(card) => {
+                self.capacity.touch(card.id);
+                if let Some(valid_until) = card.valid_until.filter(|_| card.is_expired(now)) {
+                    self.emit(NfcEvent::CardInGrace {
+                        reader,
+                        id: card.id,
+                        valid_until,
+                    });
+                }
+                if let Some(&id) = self.duress.get(&payload.id) {
+                    self.emit(NfcEvent::DuressAlarm {
+                        reader,
+                        id,
+                        credential: payload.id,
+                    });
+                }
+            }
+            Err(AccessError::Expired { valid_until }) => self.emit(NfcEvent::CardExpired {
+                reader,
+                id: payload.id,
+                valid_until,
+            }),
+            Err(AccessError::Replayed {
+                expected,
+                presented,
+            }) => self.emit(NfcEvent::ClonedCard {
+                reader,
+                id: payload.id,
+                expected,
+                presented,
+            }),
+            _ => {}
+        }
+        result
+    }
+
+    /// Take a use of a granted card, revoking it once they ran out.
+    pub(crate) fn take_use(&mut self, reader: ReaderId, card: Card, now: Timestamp) -> Card {
+        let card = self
+            .cards
+            .get_mut(&card.id)
+            .map(Card::take_use)
+            .unwrap_or(card);
+        if card.uses == Some(0) {
+            let _ = self.revoked.revoke(card.id);
+            self.log(
+                card.id,
+                now,
+                Origin::Reader(reader),
+                AuditAction::Revoke,
+                Ok(()),
+            );
+            self.registry_changed();
+        }
+        card
+    }
+
+    /// Read a card through a reader's kernel and authorize it.
+    ///
+    /// With an authenticator, the tag must pass [`NfcService::authenticate`] before it's read.
+    pub fn read(&mut self, reader: ReaderId, card_id: Uid) -> Result<Card, Traced<TagError>> {
+        self.read_with(reader, card_id, true)
+    }
+
+    /// Read a card through a reader's kernel and authorize it, authenticating the tag
+    /// first if asked to.
+    pub(crate) fn read_with(
+        &mut self,
+        reader: ReaderId,
+        card_id: Uid,
+        authenticate: bool,
+    ) -> Result<Card, Traced<TagError>> {
+        self.transact_traced(reader, card_id, |this| {
+            let now = this.now();
+            let authenticated = match authenticate {
+                true => this.authenticate(reader, card_id),
+                false => Ok(()),
+            };
+            let mut failed = None;
+            let result = authenticated.and_then(|()| {
+                let retry = this.retry;
+                let mut attempts = 0;
+                let read = this.readers.get_mut(&reader).map(|kernel| {
+                    retry.read(kernel, |kernel| {
+                        attempts += 1;
+                        read_card(kernel, card_id)
+                    })
+                });
+                if read.is_some() {
+                    this.count(reader, Counter::Reads);
+                    this.observe(reader, Observation::Attempts, attempts);
+                }
+                match read {
+                    Some(Ok(payload)) => this.decide(reader, &payload, now),
+                    Some(Err(why)) => {
+                        this.count(reader, Counter::Errors);
+                        let reason = kernel_error(reader, why.last());
+                        failed = Some(why);
+                        Err(reason)
+                    }
+                    None => Err(AccessError::UnknownReader(reader)),
+                }
+            });
+            this.report(reader, card_id, None, &result, now);
+            this.log(
+                card_id,
+                now,
+                Origin::Reader(reader),
+                AuditAction::Read,
+                result.map(|_| ()),
+            );
+            result.map_err(|reason| TagError {
+                reason,
+                retry: failed,
+            })
+        })
+    }
+
+    /// Write a registered card back to its tag through a reader, bumping its counter.
+    ///
+    /// The tag is read back before the write is reported successful.
+    pub fn write(&mut self, reader: ReaderId, card_id: Uid) -> Result<(), Traced<TagError>> {
+        self.transact_traced(reader, card_id, |this| {
+            let now = this.now();
+            let result = this.write_card(reader, card_id);
+            let outcome = result.as_ref().map_err(|why| why.reason).copied();
+            if let Err(reason) = outcome {
+                this.emit(NfcEvent::WriteFailed {
+                    reader,
+                    id: card_id,
+                    reason,
+                });
+            }
+            this.log(
+                card_id,
+                now,
+                Origin::Reader(reader),
+                AuditAction::Write,
+                outcome,
+            );
+            result
+        })
+    }
+
+    fn write_card(&mut self, reader: ReaderId, card_id: Uid) -> Result<(), TagError> {
+        let Some(kernel) = self.readers.get_mut(&reader) else {
+            return Err(AccessError::UnknownReader(reader).into());
+        };
+
+        if self.revoked.is_revoked(card_id) {
+            return Err(AccessError::Revoked(card_id).into());
+        }
+
+        let Some(mut card) = self.cards.get(&card_id).copied() else {
+            return Err(AccessError::Unknown(card_id).into());
+        };
+        card.counter = card.counter.wrapping_add(1);
+
+        let bytes = card.try_to_bytes().map_err(|_| AccessError::Kernel)?;
+        let mut attempts = 0;
+        let written = self.retry.write(kernel, |kernel| {
+            attempts += 1;
+            write_once(kernel, &card, &bytes)
+        });
+        self.count(reader, Counter::Writes);
+        self.observe(reader, Observation::Attempts, attempts);
+        if !matches!(written, Ok(true)) {
+            self.count(reader, Counter::Errors);
+        }
+        match written {
+            Ok(true) => {
+                // The counter is only bumped once the tag holds it.
+                let _ = self.cards.insert(card_id, card);
+                self.registry_changed();
+                Ok(())
+            }
+            Ok(false) => Err(AccessError::WriteVerifyFailed(card_id).into()),
+            Err(why) => Err(TagError {
+                reason: kernel_error(reader, why.last()),
+                retry: Some(why),
+            }),
+        }
+    }
+}
+
+/// Write a card to its tag and read it back, failing with [`AccessError::WriteVerifyFailed`]
+/// unless the tag holds exactly what was written.
+///
+/// Tags leaving the field mid-write are left partially written without the kernel noticing.
+/// Only the pages that changed are written if the kernel writes pages, see [`delta`].
+pub(crate) fn write_verified<K: Kernel>(
+    kernel: &mut K,
+    reader: ReaderId,
+    card: &Card,
+) -> Result<(), AccessError> {
+    let bytes = card.try_to_bytes().map_err(|_| AccessError::Kernel)?;
+    match write_once(kernel, card, &bytes) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(AccessError::WriteVerifyFailed(card.id)),
+        Err(why) => Err(kernel_error(reader, why)),
+    }
+}
+
+/// Write a card's payload to its tag and read it back, returning whether the tag holds
+/// exactly what was written.
+fn write_once<K: Kernel>(kernel: &mut K, card: &Card, bytes: &[u8]) -> Result<bool, KernelError> {
+    match delta::write_delta(kernel, card, bytes)? {
+        true => {}
+        false => kernel.write(card, bytes)?,
+    }
+    read_card(kernel, card.id).map(|written| written == *card)
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: MifareClassic,
+{
+    /// Authenticate a sector of a tag and read one of its blocks through a reader.
+    pub fn read_block(
+        &mut self,
+        reader: ReaderId,
+        uid: Uid,
+        block: Block,
+        key_type: KeyType,
+        key: &Key,
+    ) -> Result<[u8; BLOCK_SIZE], AccessError> {
+        let kernel = self
+            .readers
+            .get_mut(&reader)
+            .ok_or(AccessError::UnknownReader(reader))?;
+
+        kernel
+            .authenticate(uid, block.sector(), key_type, key)
+            .and_then(|_| kernel.read_block(block))
+            .map_err(|why| kernel_error(reader, why))
+    }
+
+    /// Authenticate a sector of a tag and write one of its blocks through a reader.
+    ///
+    /// Writing sector trailers is refused since a bad trailer locks the sector for good,
+    /// and so is writing the read-only manufacturer block.
+    pub fn write_block(
+        &mut self,
+        reader: ReaderId,
+        uid: Uid,
+        block: Block,
+        key_type: KeyType,
+        key: &Key,
+        data: &[u8; BLOCK_SIZE],
+    ) -> Result<(), AccessError> {
+        if block.is_trailer() || block.is_manufacturer() {
+            return Err(AccessError::Kernel);
+        }
+
+        let kernel = self
+            .readers
+            .get_mut(&reader)
+            .ok_or(AccessError::UnknownReader(reader))?;
+
+        kernel
+            .authenticate(uid, block.sector(), key_type, key)
+            .and_then(|_| kernel.write_block(block, data))
+            .map_err(|why| kernel_error(reader, why))
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: FelicaKernel,
+{
+    /// Talk to the FeliCa card in a reader's field.
+    ///
+    /// The reader has to be switched to [`Technology::FeliCa`] first.
+    pub fn felica(&mut self, reader: ReaderId) -> Result<Felica<'_, K>, AccessError> {
+        if self.technology(reader) != Technology::FeliCa {
+            return Err(AccessError::Kernel);
+        }
+        self.readers
+            .get_mut(&reader)
+            .map(Felica::new)
+            .ok_or(AccessError::UnknownReader(reader))
+    }
+}
+
+#[cfg(feature = "std")]
+fn main() -> std::process::ExitCode {
+    cli::run(std::env::args().skip(1))
 }
 
+#[cfg(not(feature = "std"))]
 fn main() {
     let mut nfc = NfcService::<System>::new();
     nfc.put(Card::default());
 
-    let bytes = nfc.get(&0).unwrap().as_bytes();
+    let bytes = nfc.get(&Uid::from_u16(0)).unwrap().try_to_bytes().unwrap();
     match Card::try_from(&bytes[..]) {
         Ok(ref card) => log::info!("{card}"),
-        Err(why) => log::debug!("{} - {:?}", why.message, why.bytes),
+        Err(why) => log::debug!("{}", why),
     }
 
     match Vec::<u8>::try_from(&bytes[..]) {
@@ -0,0 +1,196 @@
+//! Background maintenance the service runs on a schedule driven by its clock.
+//!
+//! Hosts call [`NfcService::maintain`] in their main loop, which runs whichever tasks of the
+//! [`MaintenanceSchedule`] are due: pruning expired cards, flushing the audit log to the
+//! store and re-syncing the revocation list with it.
+use alloc::vec::Vec;
+
+use crate::{
+    store::{CardStore, StoreError},
+    Kernel, NfcService, Timestamp, Uid,
+};
+
+/// A task run by [`NfcService::maintain`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Task {
+    /// Remove the cards that expired, see [`NfcService::prune_expired`].
+    PruneExpired,
+    /// Write the audit log to the store.
+    FlushAudit,
+    /// Merge the revocation list with the store's, see [`NfcService::sync_revocations`].
+    SyncRevocations,
+}
+
+#[allow(dead_code)]
+impl Task {
+    /// All the tasks, in the order they're run.
+    pub const ALL: [Task; 3] = [Task::PruneExpired, Task::FlushAudit, Task::SyncRevocations];
+
+    #[inline]
+    const fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// How often in seconds each maintenance task runs, `None` to never run it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MaintenanceSchedule {
+    pub prune_expired: Option<u64>,
+    pub flush_audit: Option<u64>,
+    pub sync_revocations: Option<u64>,
+}
+
+impl MaintenanceSchedule {
+    /// Prune hourly, flush the audit log every five minutes and sync revocations every
+    /// fifteen.
+    pub const DEFAULT: MaintenanceSchedule = MaintenanceSchedule {
+        prune_expired: Some(3600),
+        flush_audit: Some(300),
+        sync_revocations: Some(900),
+    };
+
+    /// The interval of a task.
+    #[inline]
+    pub const fn interval(&self, task: Task) -> Option<u64> {
+        match task {
+            Task::PruneExpired => self.prune_expired,
+            Task::FlushAudit => self.flush_audit,
+            Task::SyncRevocations => self.sync_revocations,
+        }
+    }
+}
+
+impl Default for MaintenanceSchedule {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The schedule of a service and when each task last ran.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Maintenance {
+    schedule: MaintenanceSchedule,
+    last_run: [Option<Timestamp>; Task::ALL.len()],
+}
+
+impl Maintenance {
+    #[inline]
+    pub(crate) const fn new(schedule: MaintenanceSchedule) -> Self {
+        Self {
+            schedule,
+            last_run: [None; Task::ALL.len()],
+        }
+    }
+
+    fn is_due(&self, task: Task, now: Timestamp) -> bool {
+        match (self.schedule.interval(task), self.last_run[task.index()]) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(interval), Some(at)) => now.saturating_sub(at) >= interval,
+        }
+    }
+}
+
+/// What a single [`NfcService::maintain`] did.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MaintenanceReport {
+    /// The tasks that were due and ran.
+    pub ran: Vec<Task>,
+    /// The expired cards that were removed.
+    pub pruned: usize,
+    /// The audit entries written to the store.
+    pub flushed: usize,
+    /// The cards revoked in the store that weren't revoked locally.
+    pub revoked: usize,
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Run maintenance tasks on `schedule`, see [`NfcService::maintain`].
+    #[must_use]
+    pub fn with_maintenance_schedule(mut self, schedule: MaintenanceSchedule) -> Self {
+        self.set_maintenance_schedule(schedule);
+        self
+    }
+
+    /// Replace the maintenance schedule of this service.
+    #[inline]
+    pub fn set_maintenance_schedule(&mut self, schedule: MaintenanceSchedule) {
+        self.maintenance.schedule = schedule;
+    }
+
+    #[inline]
+    pub const fn maintenance_schedule(&self) -> &MaintenanceSchedule {
+        &self.maintenance.schedule
+    }
+
+    /// When a maintenance task last ran, `None` if it didn't yet.
+    #[inline]
+    pub const fn last_maintained(&self, task: Task) -> Option<Timestamp> {
+        self.maintenance.last_run[task.index()]
+    }
+
+    /// Run the maintenance tasks that are due, call this periodically.
+    ///
+    /// Every task runs as soon as it's first called. A task failing with the store is
+    /// retried on the next call, the tasks after it are left for then as well.
+    pub fn maintain<S: CardStore>(
+        &mut self,
+        store: &mut S,
+    ) -> Result<MaintenanceReport, StoreError<S::Error>> {
+        let now = self.now();
+        let mut report = MaintenanceReport::default();
+        for task in Task::ALL {
+            if !self.maintenance.is_due(task, now) {
+                continue;
+            }
+            match task {
+                Task::PruneExpired => report.pruned = self.prune_expired(),
+                Task::FlushAudit => {
+                    store.save_audit(&self.audit)?;
+                    report.flushed = self.audit.len();
+                }
+                Task::SyncRevocations => report.revoked = self.sync_revocations(store)?,
+            }
+            self.maintenance.last_run[task.index()] = Some(now);
+            report.ran.push(task);
+        }
+        Ok(report)
+    }
+
+    /// Remove the cards whose expiry and grace period passed, returning how many were removed.
+    ///
+    /// [`NfcEvent::CardRemoved`](crate::events::NfcEvent::CardRemoved) is emitted for each.
+    pub fn prune_expired(&mut self) -> usize {
+        let now = self.now().saturating_sub(self.expiry_grace);
+        let expired: Vec<Uid> = self
+            .cards
+            .values()
+            .filter(|card| card.is_expired(now))
+            .map(|card| card.id)
+            .collect();
+        for card_id in &expired {
+            let _ = self.unbind(card_id);
+        }
+        expired.len()
+    }
+
+    /// Merge the revocation list of a store into this service's and persist the result,
+    /// returning the cards newly revoked.
+    ///
+    /// This picks up cards revoked by other services sharing the store. A card reinstated
+    /// here stays revoked if the store still lists it, persist after reinstating cards.
+    pub fn sync_revocations<S: CardStore>(
+        &mut self,
+        store: &mut S,
+    ) -> Result<usize, StoreError<S::Error>> {
+        let stored = store.load_revocations()?;
+        let revoked = self.revoked.merge(&stored);
+        self.registry_changed();
+        store.save_revocations(&self.revoked)?;
+        Ok(revoked)
+    }
+}
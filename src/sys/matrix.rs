@@ -0,0 +1,140 @@
+//! The mapping of doors to the permissions they require, as a value of its own.
+//!
+//! An [`AccessMatrix`] is taken from a running service's policy and swapped into it, i.e.
+//! after being edited offline, so which permissions open which doors lives in configuration
+//! rather than in the code installing the doors.
+use alloc::{collections::btree_map::BTreeMap, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::{door::DoorId, Kernel, NfcService, Permissions};
+
+/// The permissions required by each door.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AccessMatrix {
+    doors: BTreeMap<DoorId, Permissions>,
+}
+
+/// A difference between two [`AccessMatrix`], see [`AccessMatrix::diff`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatrixChange {
+    /// The door is only in the newer matrix.
+    Added { door: DoorId, required: Permissions },
+    /// The door is only in the older matrix.
+    Removed { door: DoorId, required: Permissions },
+    /// The door requires other permissions in the newer matrix.
+    Changed {
+        door: DoorId,
+        from: Permissions,
+        to: Permissions,
+    },
+}
+
+#[allow(dead_code)]
+impl AccessMatrix {
+    /// Create a new AccessMatrix without any doors.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            doors: BTreeMap::new(),
+        }
+    }
+
+    /// Require permissions to open a door.
+    #[inline]
+    pub fn require(mut self, door_id: DoorId, required: Permissions) -> Self {
+        let _ = self.doors.insert(door_id, required);
+        self
+    }
+
+    /// Set the permissions a door requires, returning the ones it required before.
+    #[inline]
+    pub fn insert(&mut self, door_id: DoorId, required: Permissions) -> Option<Permissions> {
+        self.doors.insert(door_id, required)
+    }
+
+    #[inline]
+    pub fn remove(&mut self, door_id: DoorId) -> Option<Permissions> {
+        self.doors.remove(&door_id)
+    }
+
+    /// The permissions a door requires.
+    #[inline]
+    pub fn get(&self, door_id: DoorId) -> Option<Permissions> {
+        self.doors.get(&door_id).copied()
+    }
+
+    /// An iterator over the doors and the permissions they require.
+    pub fn iter(&self) -> impl Iterator<Item = (DoorId, Permissions)> + '_ {
+        self.doors.iter().map(|(door, required)| (*door, *required))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.doors.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.doors.is_empty()
+    }
+
+    /// The changes turning this matrix into a newer one, by door.
+    pub fn diff(&self, newer: &AccessMatrix) -> Vec<MatrixChange> {
+        let removed = self
+            .iter()
+            .filter(|(door, _)| !newer.doors.contains_key(door))
+            .map(|(door, required)| MatrixChange::Removed { door, required });
+        let added_or_changed = newer.iter().filter_map(|(door, to)| match self.get(door) {
+            None => Some(MatrixChange::Added { door, required: to }),
+            Some(from) if from != to => Some(MatrixChange::Changed { door, from, to }),
+            Some(..) => None,
+        });
+
+        let mut changes: Vec<MatrixChange> = removed.chain(added_or_changed).collect();
+        changes.sort_by_key(MatrixChange::door);
+        changes
+    }
+}
+
+#[allow(dead_code)]
+impl MatrixChange {
+    /// The door that changed.
+    #[inline]
+    pub const fn door(&self) -> DoorId {
+        match *self {
+            Self::Added { door, .. } | Self::Removed { door, .. } | Self::Changed { door, .. } => {
+                door
+            }
+        }
+    }
+}
+
+impl FromIterator<(DoorId, Permissions)> for AccessMatrix {
+    fn from_iter<I: IntoIterator<Item = (DoorId, Permissions)>>(iter: I) -> Self {
+        Self {
+            doors: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// The permissions required by the doors installed in the policy.
+    #[inline]
+    pub fn access_matrix(&self) -> AccessMatrix {
+        self.policy.matrix()
+    }
+
+    /// Swap the permissions required by the doors for the ones of a matrix while the service
+    /// is running, returning the changes made, see [`AccessPolicy::swap_matrix`].
+    ///
+    /// [`AccessPolicy::swap_matrix`]: crate::policy::AccessPolicy::swap_matrix
+    pub fn swap_access_matrix(&mut self, matrix: &AccessMatrix) -> Vec<MatrixChange> {
+        self.policy.swap_matrix(matrix).diff(matrix)
+    }
+}
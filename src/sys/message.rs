@@ -0,0 +1,128 @@
+//! The request and response messages shared by every remote transport.
+//!
+//! The serial provisioning protocol, the HTTP management API and MQTT all carry a
+//! [`Request`] and answer it with the [`Response`] of [`NfcService::handle`], so a host
+//! speaks the same schema whichever transport it reaches the service over.
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit::AuditEntry, errors::AccessError, Card, Kernel, NfcService, Permissions, Timestamp, Uid,
+};
+
+/// A filter over the audit log, an empty one matches every entry.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditQuery {
+    /// Only the entries of this card.
+    pub card: Option<Uid>,
+    /// Only the entries at or after this time.
+    pub from: Timestamp,
+    /// Only the entries at or before this time.
+    pub until: Timestamp,
+    /// Only the entries of failed operations.
+    pub failures: bool,
+    /// The number of matching entries to skip.
+    #[serde(default)]
+    pub offset: usize,
+    /// The most matching entries to answer, all of them if `None`.
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl Default for AuditQuery {
+    fn default() -> Self {
+        Self {
+            card: None,
+            from: Timestamp::MIN,
+            until: Timestamp::MAX,
+            failures: false,
+            offset: 0,
+            limit: None,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl AuditQuery {
+    /// Whether an entry passes the filter.
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.card.is_none_or(|id| entry.card == id)
+            && entry.at >= self.from
+            && entry.at <= self.until
+            && (!self.failures || !entry.is_ok())
+    }
+}
+
+/// Check two tokens are equal without leaking how much of them matched.
+#[cfg(any(feature = "http", feature = "mqtt"))]
+pub(crate) fn tokens_match(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// A request a host sends to the service.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Request {
+    /// Read a registered card.
+    ReadCard { id: Uid },
+    /// Register a card, replacing the one with the same id.
+    WriteCard { card: Card },
+    /// Grant permissions to a card.
+    Grant { id: Uid, permissions: Permissions },
+    /// Revoke a card.
+    Revoke { id: Uid },
+    /// Read the audit entries matching a query.
+    QueryAudit(AuditQuery),
+}
+
+/// The answer to a [`Request`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Response {
+    /// The card read or written.
+    Card(Card),
+    /// The request succeeded without anything to answer.
+    Done,
+    /// The audit entries matching a query, oldest first.
+    Audit(Vec<AuditEntry>),
+    /// The request was refused.
+    Refused(AccessError),
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Handle a request from any transport.
+    pub fn handle(&mut self, request: Request) -> Response {
+        match request {
+            Request::ReadCard { id } => match self.get(&id) {
+                Some(card) => Response::Card(*card),
+                None => Response::Refused(AccessError::Unknown(id)),
+            },
+            Request::WriteCard { card } => {
+                self.put(card);
+                Response::Card(card)
+            }
+            Request::Grant { id, permissions } => match self.grant(id, permissions) {
+                Ok(()) => Response::Done,
+                Err(why) => Response::Refused(why),
+            },
+            Request::Revoke { id } => match self.try_revoke(id) {
+                Ok(()) => Response::Done,
+                Err(why) => Response::Refused(why),
+            },
+            Request::QueryAudit(query) => Response::Audit(
+                self.audit()
+                    .entries()
+                    .iter()
+                    .filter(|entry| query.matches(entry))
+                    .skip(query.offset)
+                    .take(query.limit.unwrap_or(usize::MAX))
+                    .copied()
+                    .collect(),
+            ),
+        }
+    }
+}
@@ -0,0 +1,289 @@
+//! Counting the operations of a service per reader, for monitoring.
+//!
+//! The service keeps its counts in an [`InMemoryMetrics`], which renders them in the
+//! Prometheus text format. Other [`Metrics`] can be added to forward every count as it's
+//! made, i.e. to a StatsD client or the logs.
+use alloc::{boxed::Box, collections::btree_map::BTreeMap, string::String};
+use core::fmt::{self, Write as _};
+
+use crate::{Kernel, NfcService, ReaderId};
+
+/// The events counted per reader.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Counter {
+    /// Tags read through the reader.
+    Reads,
+    /// Tags written through the reader.
+    Writes,
+    /// Cards granted access at the reader.
+    Grants,
+    /// Cards denied access at the reader.
+    Denials,
+    /// Reads and writes that failed at the reader's kernel, or didn't verify.
+    Errors,
+}
+
+#[allow(dead_code)]
+impl Counter {
+    pub const ALL: [Self; 5] = [
+        Self::Reads,
+        Self::Writes,
+        Self::Grants,
+        Self::Denials,
+        Self::Errors,
+    ];
+
+    /// The snake case name of this counter.
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Reads => "reads",
+            Self::Writes => "writes",
+            Self::Grants => "grants",
+            Self::Denials => "denials",
+            Self::Errors => "errors",
+        }
+    }
+}
+
+/// The values observed per reader.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Observation {
+    /// The attempts a read or write took, see [`RetryPolicy`](crate::retry::RetryPolicy).
+    Attempts,
+}
+
+#[allow(dead_code)]
+impl Observation {
+    /// The snake case name of this observation.
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Attempts => "attempts",
+        }
+    }
+}
+
+/// A recipient of the counts and observations of a service.
+pub trait Metrics: Send {
+    /// Count an event at a reader.
+    fn increment(&mut self, reader: ReaderId, counter: Counter);
+
+    /// Record a value observed at a reader.
+    fn observe(&mut self, reader: ReaderId, observation: Observation, value: u64);
+}
+
+/// The number, sum and largest of the values observed.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub count: u64,
+    pub sum: u64,
+    pub max: u64,
+}
+
+#[allow(dead_code)]
+impl Summary {
+    fn record(&mut self, value: u64) {
+        self.count = self.count.saturating_add(1);
+        self.sum = self.sum.saturating_add(value);
+        self.max = self.max.max(value);
+    }
+
+    /// The mean of the values observed, `0` if none were.
+    #[inline]
+    pub fn mean(&self) -> f32 {
+        match self.count {
+            0 => 0.0,
+            count => self.sum as f32 / count as f32,
+        }
+    }
+}
+
+/// The counts and observations of a single reader.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ReaderMetrics {
+    pub reads: u64,
+    pub writes: u64,
+    pub grants: u64,
+    pub denials: u64,
+    pub errors: u64,
+    pub attempts: Summary,
+}
+
+#[allow(dead_code)]
+impl ReaderMetrics {
+    #[inline]
+    pub const fn get(&self, counter: Counter) -> u64 {
+        match counter {
+            Counter::Reads => self.reads,
+            Counter::Writes => self.writes,
+            Counter::Grants => self.grants,
+            Counter::Denials => self.denials,
+            Counter::Errors => self.errors,
+        }
+    }
+
+    /// The share of reads and writes that failed, `0` if none were made.
+    pub fn error_rate(&self) -> f32 {
+        match self.reads + self.writes {
+            0 => 0.0,
+            operations => self.errors as f32 / operations as f32,
+        }
+    }
+}
+
+/// Metrics kept in memory, per reader.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InMemoryMetrics {
+    readers: BTreeMap<ReaderId, ReaderMetrics>,
+}
+
+#[allow(dead_code)]
+impl InMemoryMetrics {
+    /// Create a new InMemoryMetrics without any counts.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            readers: BTreeMap::new(),
+        }
+    }
+
+    /// The metrics of a reader, `None` if nothing was counted at it yet.
+    #[inline]
+    pub fn reader(&self, reader: ReaderId) -> Option<&ReaderMetrics> {
+        self.readers.get(&reader)
+    }
+
+    /// An iterator over the readers and their metrics in ascending id order.
+    pub fn iter(&self) -> impl Iterator<Item = (ReaderId, &ReaderMetrics)> + '_ {
+        self.readers
+            .iter()
+            .map(|(reader, metrics)| (*reader, metrics))
+    }
+
+    /// The metrics of every reader added up.
+    pub fn total(&self) -> ReaderMetrics {
+        self.readers
+            .values()
+            .fold(ReaderMetrics::default(), |total, metrics| ReaderMetrics {
+                reads: total.reads + metrics.reads,
+                writes: total.writes + metrics.writes,
+                grants: total.grants + metrics.grants,
+                denials: total.denials + metrics.denials,
+                errors: total.errors + metrics.errors,
+                attempts: Summary {
+                    count: total.attempts.count + metrics.attempts.count,
+                    sum: total.attempts.sum + metrics.attempts.sum,
+                    max: total.attempts.max.max(metrics.attempts.max),
+                },
+            })
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.readers.clear();
+    }
+
+    /// Render the metrics in the Prometheus text exposition format, i.e.
+    ///
+    /// ```text
+    /// # TYPE lowa_reads_total counter
+    /// lowa_reads_total{reader="1"} 12
+    /// ```
+    pub fn prometheus(&self) -> String {
+        let mut out = String::new();
+        // Writing to a String never fails.
+        let _ = self.write_prometheus(&mut out);
+        out
+    }
+
+    fn write_prometheus(&self, out: &mut String) -> fmt::Result {
+        for counter in Counter::ALL {
+            writeln!(out, "# TYPE lowa_{}_total counter", counter.name())?;
+            for (reader, metrics) in self.iter() {
+                writeln!(
+                    out,
+                    "lowa_{}_total{{reader=\"{}\"}} {}",
+                    counter.name(),
+                    reader,
+                    metrics.get(counter)
+                )?;
+            }
+        }
+        let name = Observation::Attempts.name();
+        writeln!(out, "# TYPE lowa_{} summary", name)?;
+        for (reader, metrics) in self.iter() {
+            writeln!(
+                out,
+                "lowa_{}_sum{{reader=\"{}\"}} {}",
+                name, reader, metrics.attempts.sum
+            )?;
+            writeln!(
+                out,
+                "lowa_{}_count{{reader=\"{}\"}} {}",
+                name, reader, metrics.attempts.count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Metrics for InMemoryMetrics {
+    fn increment(&mut self, reader: ReaderId, counter: Counter) {
+        let metrics = self.readers.entry(reader).or_default();
+        let count = match counter {
+            Counter::Reads => &mut metrics.reads,
+            Counter::Writes => &mut metrics.writes,
+            Counter::Grants => &mut metrics.grants,
+            Counter::Denials => &mut metrics.denials,
+            Counter::Errors => &mut metrics.errors,
+        };
+        *count = count.saturating_add(1);
+    }
+
+    fn observe(&mut self, reader: ReaderId, observation: Observation, value: u64) {
+        let metrics = self.readers.entry(reader).or_default();
+        match observation {
+            Observation::Attempts => metrics.attempts.record(value),
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// The counts and observations of this service per reader.
+    #[inline]
+    pub const fn metrics(&self) -> &InMemoryMetrics {
+        &self.metrics
+    }
+
+    /// Forget every count and observation, i.e. after they were exported.
+    #[inline]
+    pub fn reset_metrics(&mut self) {
+        self.metrics.clear();
+    }
+
+    /// Forward every count and observation to `metrics` as well as they're made.
+    pub fn add_metrics<M: Metrics + 'static>(&mut self, metrics: M) {
+        self.metrics_sinks.push(Box::new(metrics));
+    }
+
+    pub(crate) fn count(&mut self, reader: ReaderId, counter: Counter) {
+        self.metrics.increment(reader, counter);
+        for sink in &mut self.metrics_sinks {
+            sink.increment(reader, counter);
+        }
+    }
+
+    pub(crate) fn observe(&mut self, reader: ReaderId, observation: Observation, value: u64) {
+        self.metrics.observe(reader, observation, value);
+        for sink in &mut self.metrics_sinks {
+            sink.observe(reader, observation, value);
+        }
+    }
+}
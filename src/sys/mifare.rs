@@ -0,0 +1,160 @@
+//! MIFARE Classic sector/block addressing and authentication.
+use crate::{errors::KernelError, Kernel, Uid};
+
+/// The size of a single block in bytes.
+pub const BLOCK_SIZE: usize = 16;
+
+/// The number of sectors on a MIFARE Classic 4K tag, 1K tags use the first 16.
+pub const SECTORS: u8 = 40;
+
+/// Which of the two sector keys to authenticate with.
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyType {
+    A,
+    B,
+}
+
+/// A 6 byte MIFARE Classic sector key.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Key([u8; 6]);
+
+#[allow(dead_code)]
+impl Key {
+    /// The transport key tags ship with.
+    pub const DEFAULT: Key = Key([0xff; 6]);
+
+    #[inline]
+    pub const fn new(bytes: [u8; 6]) -> Self {
+        Self(bytes)
+    }
+
+    #[inline]
+    pub const fn as_bytes(&self) -> &[u8; 6] {
+        &self.0
+    }
+}
+
+impl core::fmt::Debug for Key {
+    // Keys are secrets, don't leak them into logs.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
+
+/// The address of a block within a sector.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Block {
+    sector: u8,
+    block: u8,
+}
+
+#[allow(dead_code)]
+impl Block {
+    /// Address a block within a sector, Returns `None` if it's out of range.
+    ///
+    /// Sectors 0 to 31 have 4 blocks, sectors 32 to 39 have 16.
+    #[inline]
+    pub const fn new(sector: u8, block: u8) -> Option<Self> {
+        if sector >= SECTORS || block >= Self::blocks_in(sector) {
+            return None;
+        }
+        Some(Self { sector, block })
+    }
+
+    /// The number of blocks in a sector.
+    #[inline]
+    pub const fn blocks_in(sector: u8) -> u8 {
+        if sector < 32 {
+            4
+        } else {
+            16
+        }
+    }
+
+    /// The trailer block of a sector, holding its keys and access bits.
+    #[inline]
+    pub const fn trailer(sector: u8) -> Option<Self> {
+        Self::new(sector, Self::blocks_in(sector) - 1)
+    }
+
+    #[inline]
+    pub const fn sector(&self) -> u8 {
+        self.sector
+    }
+
+    #[inline]
+    pub const fn block(&self) -> u8 {
+        self.block
+    }
+
+    /// The absolute block number on the tag.
+    #[inline]
+    pub const fn absolute(&self) -> u8 {
+        if self.sector < 32 {
+            self.sector * 4 + self.block
+        } else {
+            128 + (self.sector - 32) * 16 + self.block
+        }
+    }
+
+    /// Whether this is its sector's trailer block.
+    #[inline]
+    pub const fn is_trailer(&self) -> bool {
+        self.block == Self::blocks_in(self.sector) - 1
+    }
+
+    /// Whether this is the manufacturer block, which is read-only.
+    #[inline]
+    pub const fn is_manufacturer(&self) -> bool {
+        self.sector == 0 && self.block == 0
+    }
+}
+
+/// A [`Kernel`] able to address MIFARE Classic tags by sector and block.
+///
+/// Every sector has to be authenticated with one of its keys before its
+/// blocks can be read or written.
+#[allow(unused)]
+pub trait MifareClassic: Kernel {
+    /// Authenticate a sector of the tag in the field.
+    fn authenticate(
+        &mut self,
+        uid: Uid,
+        sector: u8,
+        key_type: KeyType,
+        key: &Key,
+    ) -> Result<(), KernelError>;
+
+    /// Read a block of an authenticated sector.
+    fn read_block(&mut self, block: Block) -> Result<[u8; BLOCK_SIZE], KernelError>;
+
+    /// Write a block of an authenticated sector.
+    fn write_block(&mut self, block: Block, data: &[u8; BLOCK_SIZE]) -> Result<(), KernelError>;
+
+    /// Authenticate a sector and read all of its data blocks, excluding the trailer.
+    ///
+    /// Returns the number of bytes read into `buf`.
+    fn read_sector(
+        &mut self,
+        uid: Uid,
+        sector: u8,
+        key_type: KeyType,
+        key: &Key,
+        buf: &mut [u8],
+    ) -> Result<usize, KernelError> {
+        self.authenticate(uid, sector, key_type, key)?;
+
+        let mut read = 0;
+        for (i, chunk) in buf
+            .chunks_exact_mut(BLOCK_SIZE)
+            .take(Block::blocks_in(sector) as usize - 1)
+            .enumerate()
+        {
+            let block = Block::new(sector, i as u8).ok_or(KernelError::Read { status: 0 })?;
+            chunk.copy_from_slice(&self.read_block(block)?);
+            read += BLOCK_SIZE;
+        }
+        Ok(read)
+    }
+}
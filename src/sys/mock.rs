@@ -0,0 +1,209 @@
+//! A scripted [`Kernel`] for exercising [`NfcService`](crate::NfcService) without hardware.
+use alloc::{
+    collections::{btree_map::BTreeMap, VecDeque},
+    vec::Vec,
+};
+use core::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+use crate::{errors::KernelError, firmware::FirmwareVersion, Card, Kernel, Technology, Uid};
+
+/// A single scripted poll of the RF field.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// A tag is presented.
+    Present(Uid),
+    /// The field is empty.
+    Absent,
+    /// The poll fails.
+    Fail(KernelError),
+}
+
+/// A [`Kernel`] whose behavior is scripted up front.
+///
+/// Every operation advances a simulated clock by the configured latency instead of sleeping,
+/// see [`MockKernel::elapsed`].
+#[derive(Debug, Default)]
+pub struct MockKernel {
+    cards: BTreeMap<Uid, Card>,
+    script: VecDeque<Step>,
+    responses: VecDeque<Result<Vec<u8>, KernelError>>,
+    read_errors: BTreeMap<u32, KernelError>,
+    write_errors: BTreeMap<u32, KernelError>,
+    reads: AtomicU32,
+    writes: AtomicU32,
+    latency: Duration,
+    elapsed: AtomicU32,
+    firmware: Option<FirmwareVersion>,
+    technology: Technology,
+}
+
+#[allow(dead_code)]
+impl MockKernel {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a card on the simulated tags, readable through [`Kernel::read`].
+    #[inline]
+    pub fn with_card(mut self, card: Card) -> Self {
+        self.cards.insert(card.id, card);
+        self
+    }
+
+    /// Present a tag on the next unscripted poll.
+    #[inline]
+    pub fn present(mut self, id: Uid) -> Self {
+        self.script.push_back(Step::Present(id));
+        self
+    }
+
+    /// Leave the field empty on the next unscripted poll.
+    #[inline]
+    pub fn absent(mut self) -> Self {
+        self.script.push_back(Step::Absent);
+        self
+    }
+
+    /// Fail the next unscripted poll.
+    #[inline]
+    pub fn fail(mut self, error: KernelError) -> Self {
+        self.script.push_back(Step::Fail(error));
+        self
+    }
+
+    /// Fail the `nth` read, counting from zero.
+    #[inline]
+    pub fn fail_read(mut self, nth: u32, error: KernelError) -> Self {
+        self.read_errors.insert(nth, error);
+        self
+    }
+
+    /// Fail the `nth` write, counting from zero.
+    #[inline]
+    pub fn fail_write(mut self, nth: u32, error: KernelError) -> Self {
+        self.write_errors.insert(nth, error);
+        self
+    }
+
+    /// Answer the next unanswered APDU exchange.
+    #[inline]
+    pub fn respond(mut self, response: Result<Vec<u8>, KernelError>) -> Self {
+        self.responses.push_back(response);
+        self
+    }
+
+    /// Set the simulated time every operation takes.
+    #[inline]
+    pub const fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Set the firmware version the kernel reports.
+    #[inline]
+    pub const fn with_firmware(mut self, version: FirmwareVersion) -> Self {
+        self.firmware = Some(version);
+        self
+    }
+
+    /// The number of reads attempted so far.
+    #[inline]
+    pub fn reads(&self) -> u32 {
+        self.reads.load(Ordering::Relaxed)
+    }
+
+    /// The number of writes attempted so far.
+    #[inline]
+    pub fn writes(&self) -> u32 {
+        self.writes.load(Ordering::Relaxed)
+    }
+
+    /// The simulated time spent in operations so far, at millisecond precision.
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_millis(self.elapsed.load(Ordering::Relaxed) as u64)
+    }
+
+    /// The polls left in the script.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.script.len()
+    }
+
+    /// The technology the kernel was last switched to.
+    #[inline]
+    pub const fn technology(&self) -> Technology {
+        self.technology
+    }
+
+    fn tick(&self) {
+        let millis = self.latency.as_millis().min(u32::MAX as u128) as u32;
+        self.elapsed.fetch_add(millis, Ordering::Relaxed);
+    }
+
+    fn count(
+        &self,
+        counter: &AtomicU32,
+        errors: &BTreeMap<u32, KernelError>,
+    ) -> Result<(), KernelError> {
+        self.tick();
+        let nth = counter.fetch_add(1, Ordering::Relaxed);
+        match errors.get(&nth) {
+            Some(error) => Err(*error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Kernel for MockKernel {
+    fn read(&self, card: Uid) -> Result<&Card, KernelError> {
+        self.count(&self.reads, &self.read_errors)?;
+        self.cards.get(&card).ok_or(KernelError::NoCard)
+    }
+
+    fn write(&mut self, card: &Card, data: &[u8]) -> Result<(), KernelError> {
+        let _ = data;
+        self.count(&self.writes, &self.write_errors)?;
+        // Written tags read back as written.
+        let _ = self.cards.insert(card.id, *card);
+        Ok(())
+    }
+
+    fn delay(&mut self, duration: Duration) {
+        let millis = duration.as_millis().min(u32::MAX as u128) as u32;
+        self.elapsed.fetch_add(millis, Ordering::Relaxed);
+    }
+
+    fn set_technology(&mut self, technology: Technology) -> Result<(), KernelError> {
+        self.tick();
+        self.technology = technology;
+        Ok(())
+    }
+
+    fn transceive(&mut self, apdu: &[u8]) -> Result<Vec<u8>, KernelError> {
+        let _ = apdu;
+        self.tick();
+        self.responses
+            .pop_front()
+            .unwrap_or(Err(KernelError::Unsupported("APDU exchange")))
+    }
+
+    fn firmware_version(&mut self) -> Result<FirmwareVersion, KernelError> {
+        self.tick();
+        self.firmware
+            .ok_or(KernelError::Unsupported("firmware version"))
+    }
+
+    fn sense(&mut self) -> Result<Option<Uid>, KernelError> {
+        self.tick();
+        match self.script.pop_front() {
+            Some(Step::Present(id)) => Ok(Some(id)),
+            Some(Step::Fail(error)) => Err(error),
+            Some(Step::Absent) | None => Ok(None),
+        }
+    }
+}
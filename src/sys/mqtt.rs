@@ -0,0 +1,209 @@
+//! Publishing service events to an MQTT broker, for building automation systems.
+//!
+//! Events are published as JSON to `<prefix>/readers/<reader>/<event>`, or to
+//! `<prefix>/cards/<event>` for administrative events without a reader. Access decisions
+//! are published as [`AccessEvent`]s to `<prefix>/readers/<reader>/access`.
+//!
+//! [`MqttRequests`] takes JSON [`Request`]s published to `<prefix>/requests` and publishes
+//! the JSON [`Response`](crate::message::Response)s to `<prefix>/responses`. Anyone able to
+//! publish to the broker reaches the topic, so every request is wrapped in an envelope
+//! carrying a token shared with the service:
+//!
+//! ```text
+//! {"token": "...", "request": <Request>}
+//! ```
+use alloc::{format, string::String, vec::Vec};
+use core::time::Duration;
+use std::{sync::mpsc, thread};
+
+use rumqttc::{Client, Event, MqttOptions, Packet, QoS};
+use serde::Deserialize;
+
+use crate::{
+    events::{AccessEvent, NfcEvent, Subscriber, TracedEvent},
+    message::{tokens_match, Request},
+    Kernel, NfcService,
+};
+
+/// The number of events queued for the broker before new ones are dropped.
+const CAPACITY: usize = 64;
+
+/// A [`Subscriber`] publishing every event it's notified of to an MQTT broker.
+///
+/// Publishing never blocks the service, events are dropped while the queue is full.
+pub struct MqttPublisher {
+    client: Client,
+    prefix: String,
+    qos: QoS,
+}
+
+#[allow(dead_code)]
+impl MqttPublisher {
+    /// Connect to a broker, driving the connection from a background thread.
+    ///
+    /// The connection is retried for as long as the publisher lives.
+    pub fn connect(options: MqttOptions, prefix: impl Into<String>) -> Self {
+        let (client, mut connection) = Client::new(options, CAPACITY);
+        let _ = thread::spawn(move || {
+            for notification in connection.iter() {
+                if notification.is_err() {
+                    thread::sleep(Duration::from_secs(1));
+                }
+            }
+        });
+
+        Self {
+            client,
+            prefix: prefix.into(),
+            qos: QoS::AtLeastOnce,
+        }
+    }
+
+    /// Set the quality of service events are published with.
+    #[inline]
+    pub fn with_qos(mut self, qos: QoS) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// The topic an event gets published to.
+    pub fn topic(&self, event: &NfcEvent) -> String {
+        match event.reader() {
+            Some(reader) => format!("{}/readers/{}/{}", self.prefix, reader, event.name()),
+            None => format!("{}/cards/{}", self.prefix, event.name()),
+        }
+    }
+}
+
+impl Subscriber for MqttPublisher {
+    fn on_event(&mut self, event: &NfcEvent) {
+        self.on_traced(&TracedEvent {
+            transaction: None,
+            event: *event,
+        })
+    }
+
+    fn on_traced(&mut self, event: &TracedEvent) {
+        if !event.event.is_access() {
+            self.publish(self.topic(&event.event), event.event.name(), event);
+        }
+    }
+
+    fn on_access(&mut self, event: &AccessEvent) {
+        let topic = format!("{}/readers/{}/access", self.prefix, event.reader);
+        self.publish(topic, "access", event);
+    }
+}
+
+impl MqttPublisher {
+    /// Queue an event for publishing, dropping it if the queue is full.
+    fn publish<E: serde::Serialize>(&mut self, topic: String, name: &str, event: &E) {
+        let Ok(payload) = serde_json::to_vec(event) else {
+            return;
+        };
+
+        if self
+            .client
+            .try_publish(topic, self.qos, false, payload)
+            .is_err()
+        {
+            crate::logging::log_debug!("dropped an MQTT event: {}", name);
+        }
+    }
+}
+
+/// Requests to a service received from an MQTT broker.
+///
+/// Requests are queued as they arrive and handled by [`MqttRequests::poll`], requests
+/// arriving while the queue is full are dropped.
+pub struct MqttRequests {
+    client: Client,
+    requests: mpsc::Receiver<Vec<u8>>,
+    prefix: String,
+    token: String,
+}
+
+/// A [`Request`] with the token authenticating it.
+#[derive(Deserialize)]
+struct Envelope {
+    token: String,
+    request: Request,
+}
+
+#[allow(dead_code)]
+impl MqttRequests {
+    /// Connect to a broker and subscribe to `<prefix>/requests`, driving the connection
+    /// from a background thread. Only requests carrying `token` are handled.
+    ///
+    /// The connection is retried, and the subscription renewed, for as long as this lives.
+    /// Returns `None` without connecting if the token is empty.
+    pub fn connect(
+        options: MqttOptions,
+        prefix: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Option<Self> {
+        let token = token.into();
+        if token.is_empty() {
+            return None;
+        }
+        let prefix = prefix.into();
+        let (client, mut connection) = Client::new(options, CAPACITY);
+        let (sender, requests) = mpsc::sync_channel(CAPACITY);
+        let subscriber = client.clone();
+        let topic = format!("{}/requests", prefix);
+        let _ = thread::spawn(move || {
+            for notification in connection.iter() {
+                match notification {
+                    Ok(Event::Incoming(Packet::ConnAck(..))) => {
+                        let _ = subscriber.try_subscribe(topic.as_str(), QoS::AtLeastOnce);
+                    }
+                    Ok(Event::Incoming(Packet::Publish(publish))) => {
+                        if sender.try_send(publish.payload.to_vec()).is_err() {
+                            crate::logging::log_debug!("dropped an MQTT request");
+                        }
+                    }
+                    Ok(..) => {}
+                    Err(..) => thread::sleep(Duration::from_secs(1)),
+                }
+            }
+        });
+
+        Some(Self {
+            client,
+            requests,
+            prefix,
+            token,
+        })
+    }
+
+    /// Handle the requests received since the last call, publishing their responses.
+    ///
+    /// Returns the number of requests handled, payloads that aren't a request or don't
+    /// carry the token are skipped.
+    pub fn poll<K: Kernel>(&mut self, nfc: &mut NfcService<K>) -> usize {
+        let topic = format!("{}/responses", self.prefix);
+        let mut handled = 0;
+        while let Ok(payload) = self.requests.try_recv() {
+            let Ok(envelope) = serde_json::from_slice::<Envelope>(&payload) else {
+                continue;
+            };
+            if !tokens_match(envelope.token.as_bytes(), self.token.as_bytes()) {
+                crate::logging::log_warn!("refused an unauthenticated MQTT request");
+                continue;
+            }
+            let response = nfc.handle(envelope.request);
+            handled += 1;
+            let Ok(payload) = serde_json::to_vec(&response) else {
+                continue;
+            };
+            if self
+                .client
+                .try_publish(topic.as_str(), QoS::AtLeastOnce, false, payload)
+                .is_err()
+            {
+                crate::logging::log_debug!("dropped an MQTT response");
+            }
+        }
+        handled
+    }
+}
@@ -0,0 +1,308 @@
+//! NDEF messages, the standard format phones and NFC tooling exchange data in.
+use alloc::{string::String, vec::Vec};
+
+use crate::{
+    errors::{EncodeError, NdefError},
+    Card,
+};
+
+/// The media type cards are stored under in an NDEF message.
+pub const CARD_MEDIA_TYPE: &str = "application/vnd.lowa.card+json";
+
+const MB: u8 = 0x80;
+const ME: u8 = 0x40;
+const CF: u8 = 0x20;
+const SR: u8 = 0x10;
+const IL: u8 = 0x08;
+
+const TNF_WELL_KNOWN: u8 = 0x01;
+const TNF_MEDIA: u8 = 0x02;
+
+/// URI identifier codes as defined by the NFC Forum URI RTD.
+const URI_PREFIXES: [&str; 36] = [
+    "",
+    "http://www.",
+    "https://www.",
+    "http://",
+    "https://",
+    "tel:",
+    "mailto:",
+    "ftp://anonymous:anonymous@",
+    "ftp://ftp.",
+    "ftps://",
+    "sftp://",
+    "smb://",
+    "nfs://",
+    "ftp://",
+    "dav://",
+    "news:",
+    "telnet://",
+    "imap:",
+    "rtsp://",
+    "urn:",
+    "pop:",
+    "sip:",
+    "sips:",
+    "tftp:",
+    "btspp://",
+    "btl2cap://",
+    "btgoep://",
+    "tcpobex://",
+    "irdaobex://",
+    "file://",
+    "urn:epc:id:",
+    "urn:epc:tag:",
+    "urn:epc:pat:",
+    "urn:epc:raw:",
+    "urn:epc:",
+    "urn:nfc:",
+];
+
+/// A single record of an NDEF [`Message`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Record {
+    /// A well-known text record.
+    Text { lang: String, text: String },
+    /// A well-known URI record.
+    Uri(String),
+    /// A record carrying data of a media type, i.e. `application/json`.
+    Mime { media_type: String, data: Vec<u8> },
+    /// Any other record, kept as is so messages survive a round trip.
+    Unknown {
+        tnf: u8,
+        kind: Vec<u8>,
+        payload: Vec<u8>,
+    },
+}
+
+#[allow(dead_code)]
+impl Record {
+    /// Create a new text record.
+    #[inline]
+    pub fn text(lang: impl Into<String>, text: impl Into<String>) -> Self {
+        Self::Text {
+            lang: lang.into(),
+            text: text.into(),
+        }
+    }
+
+    /// Create a new URI record.
+    #[inline]
+    pub fn uri(uri: impl Into<String>) -> Self {
+        Self::Uri(uri.into())
+    }
+
+    /// Create a new media type record.
+    #[inline]
+    pub fn mime(media_type: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        Self::Mime {
+            media_type: media_type.into(),
+            data: data.into(),
+        }
+    }
+
+    /// The type name format, type and payload of this record.
+    fn parts(&self) -> (u8, &[u8], Vec<u8>) {
+        match self {
+            Self::Text { lang, text } => {
+                let mut payload = Vec::with_capacity(1 + lang.len() + text.len());
+                // UTF-8 encoded, the lower 6 bits hold the language code length.
+                payload.push(lang.len() as u8 & 0x3f);
+                payload.extend_from_slice(lang.as_bytes());
+                payload.extend_from_slice(text.as_bytes());
+                (TNF_WELL_KNOWN, b"T", payload)
+            }
+            Self::Uri(uri) => {
+                let (code, prefix) = URI_PREFIXES
+                    .iter()
+                    .enumerate()
+                    .skip(1)
+                    .filter(|(_, prefix)| uri.starts_with(**prefix))
+                    .max_by_key(|(_, prefix)| prefix.len())
+                    .unwrap_or((0, &""));
+                let mut payload = Vec::with_capacity(1 + uri.len() - prefix.len());
+                payload.push(code as u8);
+                payload.extend_from_slice(&uri.as_bytes()[prefix.len()..]);
+                (TNF_WELL_KNOWN, b"U", payload)
+            }
+            Self::Mime { media_type, data } => (TNF_MEDIA, media_type.as_bytes(), data.clone()),
+            Self::Unknown { tnf, kind, payload } => (*tnf, kind, payload.clone()),
+        }
+    }
+
+    fn from_parts(tnf: u8, kind: &[u8], payload: &[u8]) -> Result<Self, NdefError> {
+        match (tnf, kind) {
+            (TNF_WELL_KNOWN, b"T") => {
+                let (&status, rest) = payload.split_first().ok_or(NdefError::Truncated)?;
+                if status & 0x80 != 0 {
+                    return Err(NdefError::Unsupported("UTF-16 text records"));
+                }
+                let lang_len = (status & 0x3f) as usize;
+                if rest.len() < lang_len {
+                    return Err(NdefError::Truncated);
+                }
+                let (lang, text) = rest.split_at(lang_len);
+                Ok(Self::Text {
+                    lang: utf8(lang)?,
+                    text: utf8(text)?,
+                })
+            }
+            (TNF_WELL_KNOWN, b"U") => {
+                let (&code, rest) = payload.split_first().ok_or(NdefError::Truncated)?;
+                let prefix = URI_PREFIXES.get(code as usize).copied().unwrap_or("");
+                let mut uri = String::from(prefix);
+                uri.push_str(&utf8(rest)?);
+                Ok(Self::Uri(uri))
+            }
+            (TNF_MEDIA, _) => Ok(Self::Mime {
+                media_type: utf8(kind)?,
+                data: payload.into(),
+            }),
+            _ => Ok(Self::Unknown {
+                tnf,
+                kind: kind.into(),
+                payload: payload.into(),
+            }),
+        }
+    }
+}
+
+/// An NDEF message, a sequence of records.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Message {
+    records: Vec<Record>,
+}
+
+#[allow(dead_code)]
+impl Message {
+    /// Create a new empty Message.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            records: Vec::new(),
+        }
+    }
+
+    /// Add a record to this Message.
+    #[inline]
+    pub fn with(mut self, record: Record) -> Self {
+        self.records.push(record);
+        self
+    }
+
+    #[inline]
+    pub fn push(&mut self, record: Record) {
+        self.records.push(record);
+    }
+
+    #[inline]
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Wrap a Card in a message, ready to be written to a tag.
+    pub fn from_card(card: &Card) -> Result<Self, EncodeError> {
+        Ok(Self::new().with(Record::mime(CARD_MEDIA_TYPE, card.try_to_bytes()?)))
+    }
+
+    /// Find the first Card carried by this message.
+    pub fn card(&self) -> Option<Card> {
+        self.records.iter().find_map(|record| match record {
+            Record::Mime { media_type, data } if media_type == CARD_MEDIA_TYPE => {
+                Card::from_bytes(data).ok()
+            }
+            _ => None,
+        })
+    }
+
+    /// Encode this Message into a bytes payload.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let last = self.records.len().saturating_sub(1);
+        for (i, record) in self.records.iter().enumerate() {
+            let (tnf, kind, payload) = record.parts();
+            let short = payload.len() <= u8::MAX as usize;
+
+            let mut header = tnf & 0x07;
+            if i == 0 {
+                header |= MB;
+            }
+            if i == last {
+                header |= ME;
+            }
+            if short {
+                header |= SR;
+            }
+
+            bytes.push(header);
+            bytes.push(kind.len() as u8);
+            if short {
+                bytes.push(payload.len() as u8);
+            } else {
+                bytes.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+            }
+            bytes.extend_from_slice(kind);
+            bytes.extend_from_slice(&payload);
+        }
+        bytes
+    }
+
+    /// Decode a Message from a bytes payload.
+    pub fn decode(mut bytes: &[u8]) -> Result<Self, NdefError> {
+        let mut this = Self::new();
+        while let Some((&header, rest)) = bytes.split_first() {
+            if header & CF != 0 {
+                return Err(NdefError::Unsupported("chunked records"));
+            }
+
+            let (kind_len, rest) = take(rest, 1)?;
+            let (payload_len, rest) = if header & SR != 0 {
+                let (len, rest) = take(rest, 1)?;
+                (len[0] as usize, rest)
+            } else {
+                let (len, rest) = take(rest, 4)?;
+                (
+                    u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize,
+                    rest,
+                )
+            };
+            let (id_len, rest) = if header & IL != 0 {
+                let (len, rest) = take(rest, 1)?;
+                (len[0] as usize, rest)
+            } else {
+                (0, rest)
+            };
+            let (kind, rest) = take(rest, kind_len[0] as usize)?;
+            let (_id, rest) = take(rest, id_len)?;
+            let (payload, rest) = take(rest, payload_len)?;
+
+            this.records
+                .push(Record::from_parts(header & 0x07, kind, payload)?);
+            bytes = rest;
+
+            if header & ME != 0 {
+                break;
+            }
+        }
+
+        if this.records.is_empty() {
+            return Err(NdefError::Truncated);
+        }
+        Ok(this)
+    }
+}
+
+#[inline]
+fn take(bytes: &[u8], n: usize) -> Result<(&[u8], &[u8]), NdefError> {
+    if bytes.len() < n {
+        return Err(NdefError::Truncated);
+    }
+    Ok(bytes.split_at(n))
+}
+
+#[inline]
+fn utf8(bytes: &[u8]) -> Result<String, NdefError> {
+    core::str::from_utf8(bytes)
+        .map(String::from)
+        .map_err(|_| NdefError::InvalidUtf8)
+}
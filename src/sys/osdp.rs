@@ -0,0 +1,308 @@
+//! An OSDP peripheral, reporting card reads to an access control head-end.
+//!
+//! Only the clear text subset of the protocol is implemented, packets that carry a
+//! security block are refused with [`Nak::SecureChannel`].
+use alloc::{collections::VecDeque, vec::Vec};
+
+use crate::{crc::crc16_ccitt, errors::OsdpError, events::NfcEvent, ReaderId, Uid};
+
+/// Marks the start of a packet.
+pub const SOM: u8 = 0x53;
+
+/// The address every peripheral answers to.
+pub const BROADCAST: u8 = 0x7f;
+
+/// Set on the address of packets sent by a peripheral.
+const REPLY: u8 = 0x80;
+
+/// The header is the start of message, address, length and control bytes.
+const HEADER: usize = 5;
+
+const CTRL_SEQUENCE: u8 = 0b0000_0011;
+const CTRL_CRC: u8 = 0b0000_0100;
+const CTRL_SECURITY: u8 = 0b0000_1000;
+
+/// OSDP command codes understood by the peripheral.
+pub mod command {
+    pub const POLL: u8 = 0x60;
+    pub const ID: u8 = 0x61;
+    pub const CAP: u8 = 0x62;
+    pub const LSTAT: u8 = 0x64;
+    pub const LED: u8 = 0x69;
+    pub const BUZ: u8 = 0x6a;
+}
+
+/// OSDP reply codes sent by the peripheral.
+pub mod reply {
+    pub const ACK: u8 = 0x40;
+    pub const NAK: u8 = 0x41;
+    pub const PDID: u8 = 0x45;
+    pub const PDCAP: u8 = 0x46;
+    pub const LSTATR: u8 = 0x48;
+    pub const RAW: u8 = 0x50;
+}
+
+/// The reasons a command can be refused with.
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Nak {
+    Checksum = 0x01,
+    Length = 0x02,
+    UnknownCommand = 0x03,
+    Sequence = 0x04,
+    SecureChannel = 0x05,
+}
+
+/// A decoded OSDP packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub address: u8,
+    pub sequence: u8,
+    pub code: u8,
+    pub data: Vec<u8>,
+}
+
+/// The 8-bit two's complement checksum used when CRCs are disabled.
+#[inline]
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes
+        .iter()
+        .fold(0u8, |sum, b| sum.wrapping_add(*b))
+        .wrapping_neg()
+}
+
+#[allow(dead_code)]
+impl Packet {
+    /// Encode this packet, always protected by a CRC.
+    pub fn encode(&self) -> Vec<u8> {
+        let len = HEADER + 1 + self.data.len() + 2;
+        let mut bytes = Vec::with_capacity(len);
+        bytes.push(SOM);
+        bytes.push(self.address);
+        bytes.extend_from_slice(&(len as u16).to_le_bytes());
+        bytes.push(self.sequence & CTRL_SEQUENCE | CTRL_CRC);
+        bytes.push(self.code);
+        bytes.extend_from_slice(&self.data);
+        let crc = crc16_ccitt(0x1d0f, &bytes);
+        bytes.extend_from_slice(&crc.to_le_bytes());
+        bytes
+    }
+
+    /// Decode the packet at the start of `bytes`, returning it and the number of bytes it spans.
+    pub fn decode(bytes: &[u8]) -> Result<(Self, usize), OsdpError> {
+        match bytes {
+            [SOM, address, lo, hi, ctrl, ..] => {
+                let len = u16::from_le_bytes([*lo, *hi]) as usize;
+                let trailer = if ctrl & CTRL_CRC != 0 { 2 } else { 1 };
+                if len < HEADER + 1 + trailer {
+                    return Err(OsdpError::Length(len));
+                }
+                if bytes.len() < len {
+                    return Err(OsdpError::Incomplete);
+                }
+                if ctrl & CTRL_SECURITY != 0 {
+                    return Err(OsdpError::SecureChannel);
+                }
+
+                let end = len - trailer;
+                let valid = if trailer == 2 {
+                    crc16_ccitt(0x1d0f, &bytes[..end])
+                        == u16::from_le_bytes([bytes[end], bytes[end + 1]])
+                } else {
+                    checksum(&bytes[..end]) == bytes[end]
+                };
+                if !valid {
+                    return Err(OsdpError::Checksum);
+                }
+
+                let packet = Self {
+                    address: *address,
+                    sequence: ctrl & CTRL_SEQUENCE,
+                    code: bytes[HEADER],
+                    data: bytes[HEADER + 1..end].to_vec(),
+                };
+                Ok((packet, len))
+            }
+            [SOM, ..] | [] => Err(OsdpError::Incomplete),
+            _ => Err(OsdpError::Sync),
+        }
+    }
+}
+
+/// What the peripheral reports about itself to `osdp_ID`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Identity {
+    pub vendor: [u8; 3],
+    pub model: u8,
+    pub version: u8,
+    pub serial: u32,
+    pub firmware: [u8; 3],
+}
+
+impl Identity {
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(12);
+        bytes.extend_from_slice(&self.vendor);
+        bytes.push(self.model);
+        bytes.push(self.version);
+        bytes.extend_from_slice(&self.serial.to_le_bytes());
+        bytes.extend_from_slice(&self.firmware);
+        bytes
+    }
+}
+
+/// The capabilities reported to `osdp_CAP`, as `(function, compliance, count)` triples.
+const CAPABILITIES: [[u8; 3]; 3] = [
+    // Card data format, raw bits.
+    [4, 1, 0],
+    // Check character support, CRC-16.
+    [8, 1, 0],
+    // Communication security, none.
+    [9, 0, 0],
+];
+
+/// A read waiting to be reported on the next poll.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Read {
+    reader: ReaderId,
+    id: Uid,
+}
+
+/// An OSDP peripheral device.
+#[derive(Debug, Clone)]
+pub struct Peripheral {
+    address: u8,
+    identity: Identity,
+    tampered: bool,
+    reads: VecDeque<Read>,
+    last: Option<(u8, Vec<u8>)>,
+}
+
+#[allow(dead_code)]
+impl Peripheral {
+    /// Create a peripheral answering at `address`.
+    #[inline]
+    pub const fn new(address: u8, identity: Identity) -> Self {
+        Self {
+            address: address & !REPLY,
+            identity,
+            tampered: false,
+            reads: VecDeque::new(),
+            last: None,
+        }
+    }
+
+    #[inline]
+    pub const fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// The number of reads waiting to be polled.
+    #[inline]
+    pub fn pending(&self) -> usize {
+        self.reads.len()
+    }
+
+    /// Set the tamper status reported to `osdp_LSTAT`.
+    #[inline]
+    pub fn set_tampered(&mut self, tampered: bool) {
+        self.tampered = tampered;
+    }
+
+    /// Queue a card read to be reported on the next poll.
+    #[inline]
+    pub fn card_read(&mut self, reader: ReaderId, id: Uid) {
+        self.reads.push_back(Read { reader, id });
+    }
+
+    /// Queue the card reads found in a service event.
+    #[inline]
+    pub fn observe(&mut self, event: &NfcEvent) {
+        if let NfcEvent::CardDetected { reader, id } = *event {
+            self.card_read(reader, id);
+        }
+    }
+
+    /// Handle a packet received from the head-end, returning the encoded reply.
+    ///
+    /// Returns `None` for packets that are addressed to another device or can't be decoded.
+    pub fn handle(&mut self, bytes: &[u8]) -> Option<Vec<u8>> {
+        let packet = match Packet::decode(bytes) {
+            Ok((packet, _)) => packet,
+            Err(err @ (OsdpError::SecureChannel | OsdpError::Checksum)) => {
+                let packet = Packet {
+                    address: bytes[1],
+                    sequence: bytes[4] & CTRL_SEQUENCE,
+                    code: 0,
+                    data: Vec::new(),
+                };
+                if !self.addressed(&packet) {
+                    return None;
+                }
+                let reason = match err {
+                    OsdpError::Checksum => Nak::Checksum,
+                    _ => Nak::SecureChannel,
+                };
+                return Some(self.nak(&packet, reason));
+            }
+            Err(..) => return None,
+        };
+        if !self.addressed(&packet) {
+            return None;
+        }
+
+        // A repeated sequence number means our last reply was lost.
+        if let Some((sequence, reply)) = &self.last {
+            if packet.sequence != 0 && *sequence == packet.sequence {
+                return Some(reply.clone());
+            }
+        }
+
+        let (code, data) = match packet.code {
+            command::POLL => match self.reads.pop_front() {
+                Some(read) => (reply::RAW, Self::raw(read)),
+                None => (reply::ACK, Vec::new()),
+            },
+            command::ID => (reply::PDID, self.identity.encode()),
+            command::CAP => (reply::PDCAP, CAPABILITIES.concat()),
+            command::LSTAT => (reply::LSTATR, [self.tampered as u8, 0].to_vec()),
+            command::LED | command::BUZ => (reply::ACK, Vec::new()),
+            _ => return Some(self.nak(&packet, Nak::UnknownCommand)),
+        };
+        Some(self.reply(&packet, code, data))
+    }
+
+    fn addressed(&self, packet: &Packet) -> bool {
+        packet.address == self.address || packet.address == BROADCAST
+    }
+
+    fn nak(&mut self, packet: &Packet, reason: Nak) -> Vec<u8> {
+        self.reply(packet, reply::NAK, [reason as u8].to_vec())
+    }
+
+    fn reply(&mut self, packet: &Packet, code: u8, data: Vec<u8>) -> Vec<u8> {
+        let bytes = Packet {
+            address: self.address | REPLY,
+            sequence: packet.sequence,
+            code,
+            data,
+        }
+        .encode();
+        self.last = Some((packet.sequence, bytes.clone()));
+        bytes
+    }
+
+    /// Encode an `osdp_RAW` reply carrying the UID's bits.
+    fn raw(read: Read) -> Vec<u8> {
+        let uid = read.id.as_bytes();
+        let bits = uid.len() as u16 * 8;
+        let mut data = Vec::with_capacity(4 + uid.len());
+        data.push(read.reader as u8);
+        // Raw, unspecified format.
+        data.push(0);
+        data.extend_from_slice(&bits.to_le_bytes());
+        data.extend_from_slice(uid);
+        data
+    }
+}
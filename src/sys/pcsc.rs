@@ -0,0 +1,185 @@
+//! A [`Kernel`] for PC/SC desktop readers, for development and kiosk deployments.
+//!
+//! Card payloads are stored on memory tags from page 4 onwards, prefixed with their
+//! length as a big endian `u16`.
+use alloc::{collections::btree_map::BTreeMap, ffi::CString, vec::Vec};
+use core::ffi::CStr;
+
+use ::pcsc::{Context, Protocols, Scope, ShareMode, MAX_BUFFER_SIZE};
+
+use crate::{errors::KernelError, Card, Kernel, Uid};
+
+/// The first page of user memory on NFC Forum type 2 tags.
+const FIRST_PAGE: u8 = 4;
+
+/// The size of a type 2 tag page.
+const PAGE_SIZE: usize = 4;
+
+/// The number of bytes a single READ BINARY returns.
+const READ_SIZE: usize = 16;
+
+/// GET DATA, answered with the UID of the tag in the field.
+const GET_UID: [u8; 5] = [0xff, 0xca, 0x00, 0x00, 0x00];
+
+#[inline]
+fn error(why: ::pcsc::Error) -> KernelError {
+    match why {
+        ::pcsc::Error::NoSmartcard | ::pcsc::Error::RemovedCard => KernelError::NoCard,
+        ::pcsc::Error::Timeout => KernelError::Timeout,
+        _ => KernelError::Transport { status: 0 },
+    }
+}
+
+/// A PC/SC reader, i.e. a USB desktop reader.
+///
+/// Cards are cached as they're read, [`Kernel::read`] answers from the cache
+/// while [`Kernel::read_into`] always reads the tag.
+pub struct PcscKernel {
+    context: Context,
+    reader: CString,
+    card: Option<::pcsc::Card>,
+    cards: BTreeMap<Uid, Card>,
+}
+
+#[allow(dead_code)]
+impl PcscKernel {
+    /// Connect to a reader by its PC/SC name.
+    pub fn open(reader: &CStr) -> Result<Self, KernelError> {
+        Ok(Self {
+            context: Context::establish(Scope::User).map_err(error)?,
+            reader: reader.into(),
+            card: None,
+            cards: BTreeMap::new(),
+        })
+    }
+
+    /// Connect to the first reader the PC/SC service lists.
+    pub fn first() -> Result<Self, KernelError> {
+        let context = Context::establish(Scope::User).map_err(error)?;
+        let reader = context
+            .list_readers_owned()
+            .map_err(error)?
+            .into_iter()
+            .next()
+            .ok_or(KernelError::Transport { status: 0 })?;
+        Ok(Self {
+            context,
+            reader,
+            card: None,
+            cards: BTreeMap::new(),
+        })
+    }
+
+    /// The PC/SC name of this reader.
+    #[inline]
+    pub fn reader(&self) -> &CStr {
+        &self.reader
+    }
+
+    /// Send an APDU to the connected tag, returning its raw response.
+    fn transmit(&mut self, apdu: &[u8]) -> Result<Vec<u8>, KernelError> {
+        let card = self.card.as_ref().ok_or(KernelError::NoCard)?;
+        let mut buf = [0; MAX_BUFFER_SIZE];
+        card.transmit(apdu, &mut buf)
+            .map(<[u8]>::to_vec)
+            .map_err(error)
+    }
+
+    /// Send an APDU to the reader, returning its response without the status word.
+    fn command(&mut self, apdu: &[u8]) -> Result<Vec<u8>, KernelError> {
+        let mut response = self.transmit(apdu)?;
+        let at = response
+            .len()
+            .checked_sub(2)
+            .ok_or(KernelError::Transport { status: 0 })?;
+        match u16::from_be_bytes([response[at], response[at + 1]]) {
+            0x9000 => {
+                response.truncate(at);
+                Ok(response)
+            }
+            status => Err(KernelError::Read { status }),
+        }
+    }
+
+    fn read_pages(&mut self, page: u8) -> Result<Vec<u8>, KernelError> {
+        self.command(&[0xff, 0xb0, 0x00, page, READ_SIZE as u8])
+    }
+
+    fn write_page(&mut self, page: u8, data: [u8; PAGE_SIZE]) -> Result<(), KernelError> {
+        let mut apdu = [0xff, 0xd6, 0x00, page, PAGE_SIZE as u8, 0, 0, 0, 0];
+        apdu[5..].copy_from_slice(&data);
+        self.command(&apdu).map(|_| ()).map_err(|why| match why {
+            KernelError::Read { status } => KernelError::Write { status },
+            why => why,
+        })
+    }
+}
+
+impl Kernel for PcscKernel {
+    fn read(&self, card: Uid) -> Result<&Card, KernelError> {
+        self.cards.get(&card).ok_or(KernelError::NoCard)
+    }
+
+    fn read_into(&mut self, card: Uid, buf: &mut [u8]) -> Result<usize, KernelError> {
+        let mut bytes = self.read_pages(FIRST_PAGE)?;
+        let len = match bytes[..] {
+            [hi, lo, ..] => u16::from_be_bytes([hi, lo]) as usize + 2,
+            _ => return Err(KernelError::Read { status: 0 }),
+        };
+
+        let mut page = FIRST_PAGE;
+        while bytes.len() < len {
+            page += (READ_SIZE / PAGE_SIZE) as u8;
+            let more = self.read_pages(page)?;
+            bytes.extend_from_slice(&more);
+        }
+
+        let payload = &bytes[2..len];
+        let read = Card::from_bytes(payload).map_err(|_| KernelError::Read { status: 0 })?;
+        if read.id != card {
+            return Err(KernelError::Read { status: 0 });
+        }
+        let _ = self.cards.insert(card, read);
+        buf.get_mut(..payload.len())
+            .ok_or(KernelError::Read { status: 0 })?
+            .copy_from_slice(payload);
+        Ok(payload.len())
+    }
+
+    fn write(&mut self, card: &Card, data: &[u8]) -> Result<(), KernelError> {
+        let len = u16::try_from(data.len()).map_err(|_| KernelError::Write { status: 0 })?;
+        let mut bytes = Vec::with_capacity(2 + data.len());
+        bytes.extend_from_slice(&len.to_be_bytes());
+        bytes.extend_from_slice(data);
+
+        for (page, chunk) in (FIRST_PAGE..).zip(bytes.chunks(PAGE_SIZE)) {
+            let mut data = [0; PAGE_SIZE];
+            data[..chunk.len()].copy_from_slice(chunk);
+            self.write_page(page, data)?;
+        }
+        self.cards.insert(card.id, *card);
+        Ok(())
+    }
+
+    fn transceive(&mut self, apdu: &[u8]) -> Result<Vec<u8>, KernelError> {
+        self.transmit(apdu)
+    }
+
+    fn sense(&mut self) -> Result<Option<Uid>, KernelError> {
+        // Reconnect on every poll, the previous tag may have left the field.
+        self.card = None;
+        match self
+            .context
+            .connect(&self.reader, ShareMode::Shared, Protocols::ANY)
+        {
+            Ok(card) => self.card = Some(card),
+            Err(::pcsc::Error::NoSmartcard | ::pcsc::Error::RemovedCard) => return Ok(None),
+            Err(why) => return Err(error(why)),
+        }
+
+        let uid = self.command(&GET_UID)?;
+        Uid::new(&uid)
+            .map(Some)
+            .ok_or(KernelError::Read { status: 0 })
+    }
+}
@@ -0,0 +1,203 @@
+//! Composing, comparing and parsing [`Permissions`].
+//!
+//! Permissions are written as flag names separated by `|`, i.e. `REGULAR|OPEN_DOORS`,
+//! with deployment-specific permissions written as `CUSTOM_<n>`.
+//!
+//! Permissions used to be a byte, payloads serialized back then still decode. Postcard
+//! writes the old bits as the same byte a `u32` varint takes, and human-readable formats
+//! accept the bits as a plain number besides the flag names.
+use alloc::string::ToString;
+use core::{fmt, str::FromStr};
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{errors::PermissionsError, Permissions};
+
+/// The lowest bit left to deployment-specific permissions.
+const CUSTOM_SHIFT: u32 = 16;
+
+/// The prefix of deployment-specific permission names.
+const CUSTOM_PREFIX: &str = "CUSTOM_";
+
+#[allow(dead_code)]
+impl Permissions {
+    /// The bits left to deployment-specific permissions, see [`Permissions::custom`].
+    pub const CUSTOM: Self = Self::from_bits_retain(0xFFFF_0000);
+
+    /// The number of deployment-specific permissions.
+    pub const CUSTOM_COUNT: u8 = 16;
+
+    /// The `n`th deployment-specific permission, i.e.
+    /// `const VAULT: Permissions = Permissions::custom(0);`.
+    ///
+    /// These are never assigned a meaning by this crate.
+    ///
+    /// # Panics
+    ///
+    /// If `n` isn't below [`Permissions::CUSTOM_COUNT`].
+    #[inline]
+    pub const fn custom(n: u8) -> Self {
+        assert!(n < Self::CUSTOM_COUNT, "custom permission out of range");
+        Self::from_bits_retain(1 << (CUSTOM_SHIFT + n as u32))
+    }
+
+    /// The deployment-specific permissions among these, bit `n` standing for
+    /// [`Permissions::custom`]`(n)`.
+    #[inline]
+    pub const fn custom_bits(self) -> u16 {
+        (self.bits() >> CUSTOM_SHIFT) as u16
+    }
+
+    /// A builder composing permissions flag by flag.
+    #[inline]
+    pub const fn builder() -> PermissionsBuilder {
+        PermissionsBuilder::new()
+    }
+
+    /// These permissions with the ones they imply through the hierarchy.
+    ///
+    /// [`Permissions::SUPER_ADMIN`] implies every permission, and [`Permissions::ADMIN`]
+    /// every one below it. Neither implies the deployment-specific ones.
+    #[inline]
+    pub const fn effective(self) -> Self {
+        let builtin = Self::all().difference(Self::CUSTOM);
+        if self.contains(Self::SUPER_ADMIN) {
+            self.union(builtin)
+        } else if self.contains(Self::ADMIN) {
+            self.union(builtin.difference(Self::SUPER_ADMIN))
+        } else {
+            self
+        }
+    }
+
+    /// Whether holding these permissions amounts to holding all of `other`.
+    #[inline]
+    pub const fn implies(self, other: Self) -> bool {
+        self.effective().contains(other)
+    }
+}
+
+impl fmt::Display for Permissions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        let mut separate = |f: &mut fmt::Formatter<'_>| {
+            let separator = if first { "" } else { "|" };
+            first = false;
+            f.write_str(separator)
+        };
+        for (name, _) in self.iter_names() {
+            separate(f)?;
+            f.write_str(name)?;
+        }
+        let custom = self.custom_bits();
+        for n in (0..Self::CUSTOM_COUNT).filter(|n| custom & (1 << n) != 0) {
+            separate(f)?;
+            write!(f, "{}{}", CUSTOM_PREFIX, n)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Permissions {
+    type Err = PermissionsError;
+
+    /// Parse flag names separated by `|` or `,`, an empty string has no permissions.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(['|', ','])
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .try_fold(Self::empty(), |perms, name| {
+                let custom = name
+                    .strip_prefix(CUSTOM_PREFIX)
+                    .and_then(|n| n.parse::<u8>().ok())
+                    .filter(|n| *n < Self::CUSTOM_COUNT)
+                    .map(Self::custom);
+                custom
+                    .or_else(|| Self::from_name(name))
+                    .map(|perm| perms | perm)
+                    .ok_or_else(|| PermissionsError::UnknownFlag(name.to_string()))
+            })
+    }
+}
+
+impl Serialize for Permissions {
+    /// Serialize as flag names like `"REGULAR | OPEN_DOORS"` to human-readable formats,
+    /// the bits otherwise.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        bitflags::serde::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Permissions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PermissionsVisitor;
+
+        impl Visitor<'_> for PermissionsVisitor {
+            type Value = Permissions;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("`|` separated permission flags or their bits")
+            }
+
+            fn visit_str<E: de::Error>(self, flags: &str) -> Result<Self::Value, E> {
+                bitflags::parser::from_str(flags).map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, bits: u64) -> Result<Self::Value, E> {
+                u32::try_from(bits)
+                    .map(Permissions::from_bits_retain)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(bits), &self))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(PermissionsVisitor)
+        } else {
+            u32::deserialize(deserializer).map(Self::from_bits_retain)
+        }
+    }
+}
+
+/// Composes [`Permissions`], see [`Permissions::builder`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct PermissionsBuilder(Permissions);
+
+#[allow(dead_code)]
+impl PermissionsBuilder {
+    /// Create a new PermissionsBuilder without any permissions.
+    #[inline]
+    pub const fn new() -> Self {
+        Self(Permissions::empty())
+    }
+
+    /// Add permissions.
+    #[inline]
+    pub const fn with(self, perms: Permissions) -> Self {
+        Self(self.0.union(perms))
+    }
+
+    /// Remove permissions.
+    #[inline]
+    pub const fn without(self, perms: Permissions) -> Self {
+        Self(self.0.difference(perms))
+    }
+
+    /// Add the permissions [`Permissions::SUPER_ADMIN`] or [`Permissions::ADMIN`] imply.
+    #[inline]
+    pub const fn effective(self) -> Self {
+        Self(self.0.effective())
+    }
+
+    /// Add the permissions parsed from a string like `REGULAR|OPEN_DOORS`.
+    #[inline]
+    pub fn parse(self, perms: &str) -> Result<Self, PermissionsError> {
+        perms.parse().map(|perms| self.with(perms))
+    }
+
+    #[inline]
+    pub const fn build(self) -> Permissions {
+        self.0
+    }
+}
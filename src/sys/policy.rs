@@ -0,0 +1,472 @@
+use alloc::{
+    collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+    vec::Vec,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clock::Clock,
+    door::{AccessPoint, Door, DoorId},
+    elevator::{Elevator, FloorSet},
+    group::DoorGroups,
+    matrix::AccessMatrix,
+    role::RoleRegistry,
+    schedule::{HolidayCalendar, Schedule},
+    zone::Zones,
+    Card, Permissions, Position, Timestamp,
+};
+
+/// Why an access point refused to open.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DenyReason {
+    /// The access point isn't known to the policy.
+    UnknownDoor,
+    /// The card is missing some of the required permissions.
+    MissingPermissions(Permissions),
+    /// The access point's schedule doesn't allow access at this time.
+    OutsideSchedule,
+    /// The card holder's position isn't allowed through the access point.
+    Position(Position),
+    /// The access point is locked down, i.e. after its reader was tampered with.
+    LockedDown,
+    /// The card may use the elevator, but none of its floors.
+    NoFloors,
+    /// The card was authorized, but the access point waits for a second card until `until`.
+    AwaitingSecondCard { until: Timestamp },
+}
+
+/// The outcome of an access decision.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Decision {
+    Granted,
+    Denied(DenyReason),
+}
+
+#[allow(dead_code)]
+impl Decision {
+    #[inline]
+    pub const fn is_granted(&self) -> bool {
+        matches!(self, Self::Granted)
+    }
+}
+
+/// A rule restricting or extending access to a door based on [`Position`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PositionRule {
+    /// Require at least this position, i.e. `Manager` admits managers and directors.
+    AtLeast(Position),
+    /// Grant this position access even without the required permissions.
+    Grant(Position),
+    /// Deny this position access regardless of its permissions.
+    Deny(Position),
+}
+
+/// Position based rules which are consulted in addition to the permission bits.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PositionPolicy {
+    rules: BTreeMap<DoorId, Vec<PositionRule>>,
+}
+
+#[allow(dead_code)]
+impl PositionPolicy {
+    /// Create a new `PositionPolicy` without any rules.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            rules: BTreeMap::new(),
+        }
+    }
+
+    /// Add a rule to a door.
+    pub fn add(&mut self, door_id: DoorId, rule: PositionRule) {
+        self.rules.entry(door_id).or_default().push(rule);
+    }
+
+    /// Remove all the rules of a door.
+    pub fn clear(&mut self, door_id: DoorId) -> Vec<PositionRule> {
+        self.rules.remove(&door_id).unwrap_or_default()
+    }
+
+    pub fn rules(&self, door_id: DoorId) -> &[PositionRule] {
+        self.rules.get(&door_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Evaluate the rules of a door for a position.
+    ///
+    /// Returns `None` if no rule decides, leaving it to the permission bits.
+    pub fn evaluate(&self, position: Position, door_id: DoorId) -> Option<Decision> {
+        let rules = self.rules(door_id);
+        let denied = rules.iter().any(|rule| match *rule {
+            PositionRule::Deny(denied) => denied == position,
+            PositionRule::AtLeast(minimum) => !position.is_at_least(minimum),
+            PositionRule::Grant(..) => false,
+        });
+        if denied {
+            return Some(Decision::Denied(DenyReason::Position(position)));
+        }
+
+        rules
+            .contains(&PositionRule::Grant(position))
+            .then_some(Decision::Granted)
+    }
+}
+
+/// The policy engine which maps card permissions to concrete doors.
+///
+/// Lockdowns and emergency unlocks are left out when it's serialized, they're raised by
+/// the readers and alarms of the device the policy runs on.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccessPolicy {
+    doors: BTreeMap<DoorId, Door>,
+    elevators: BTreeMap<DoorId, Elevator>,
+    door_schedules: BTreeMap<DoorId, Schedule>,
+    permission_schedules: Vec<(Permissions, Schedule)>,
+    holidays: HolidayCalendar,
+    groups: DoorGroups,
+    #[serde(skip)]
+    lockdowns: BTreeSet<DoorId>,
+    #[serde(skip)]
+    emergency_unlocks: BTreeSet<DoorId>,
+    positions: PositionPolicy,
+    roles: RoleRegistry,
+    zones: Zones,
+}
+
+#[allow(dead_code)]
+impl AccessPolicy {
+    /// Create a new `AccessPolicy` without any doors.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            doors: BTreeMap::new(),
+            elevators: BTreeMap::new(),
+            door_schedules: BTreeMap::new(),
+            permission_schedules: Vec::new(),
+            holidays: HolidayCalendar::new(),
+            groups: DoorGroups::new(),
+            lockdowns: BTreeSet::new(),
+            emergency_unlocks: BTreeSet::new(),
+            positions: PositionPolicy::new(),
+            roles: RoleRegistry::new(),
+            zones: Zones::new(),
+        }
+    }
+
+    /// Replace this policy with another one, keeping the lockdowns and emergency unlocks
+    /// in effect. Returns the previous policy without them.
+    pub fn replace(&mut self, mut policy: AccessPolicy) -> AccessPolicy {
+        policy.lockdowns = core::mem::take(&mut self.lockdowns);
+        policy.emergency_unlocks = core::mem::take(&mut self.emergency_unlocks);
+        core::mem::replace(self, policy)
+    }
+
+    /// Install a door, replacing any door with the same id.
+    pub fn install(&mut self, door: Door) -> Option<Door> {
+        self.doors.insert(door.id(), door)
+    }
+
+    pub fn uninstall(&mut self, door_id: DoorId) -> Option<Door> {
+        let _ = self.door_schedules.remove(&door_id);
+        let _ = self.positions.clear(door_id);
+        let _ = self.zones.unplace(door_id);
+        self.groups.leave_all(door_id);
+        self.doors.remove(&door_id)
+    }
+
+    /// Install an elevator, replacing any elevator with the same id.
+    ///
+    /// Elevators share the id space of doors, schedules and position rules of an id apply
+    /// to both.
+    pub fn install_elevator(&mut self, elevator: Elevator) -> Option<Elevator> {
+        self.elevators.insert(elevator.id(), elevator)
+    }
+
+    pub fn uninstall_elevator(&mut self, elevator_id: DoorId) -> Option<Elevator> {
+        self.elevators.remove(&elevator_id)
+    }
+
+    pub fn elevator(&self, elevator_id: DoorId) -> Option<&Elevator> {
+        self.elevators.get(&elevator_id)
+    }
+
+    pub const fn roles(&self) -> &RoleRegistry {
+        &self.roles
+    }
+
+    pub fn roles_mut(&mut self) -> &mut RoleRegistry {
+        &mut self.roles
+    }
+
+    pub const fn zones(&self) -> &Zones {
+        &self.zones
+    }
+
+    pub fn zones_mut(&mut self) -> &mut Zones {
+        &mut self.zones
+    }
+
+    pub const fn groups(&self) -> &DoorGroups {
+        &self.groups
+    }
+
+    /// The door groups, changes to their doors and grants apply on the next decision.
+    pub fn groups_mut(&mut self) -> &mut DoorGroups {
+        &mut self.groups
+    }
+
+    pub const fn positions(&self) -> &PositionPolicy {
+        &self.positions
+    }
+
+    pub fn positions_mut(&mut self) -> &mut PositionPolicy {
+        &mut self.positions
+    }
+
+    /// Only open a door within a schedule, replacing its previous schedule.
+    pub fn schedule_door(&mut self, door_id: DoorId, schedule: Schedule) -> Option<Schedule> {
+        self.door_schedules.insert(door_id, schedule)
+    }
+
+    /// Only honor some permissions within a schedule.
+    ///
+    /// Outside the schedule these permissions are masked out of the card's
+    /// effective permissions, i.e. [`Permissions::REGULAR`] during business hours.
+    pub fn schedule_permissions(&mut self, perms: Permissions, schedule: Schedule) {
+        self.permission_schedules.push((perms, schedule));
+    }
+
+    pub const fn holidays(&self) -> &HolidayCalendar {
+        &self.holidays
+    }
+
+    pub fn holidays_mut(&mut self) -> &mut HolidayCalendar {
+        &mut self.holidays
+    }
+
+    /// Replace the holidays on which schedules don't apply, returning the previous ones.
+    ///
+    /// Scheduled doors stay closed and scheduled permissions are masked on holidays,
+    /// as if it was outside their schedule.
+    pub fn set_holidays(&mut self, holidays: HolidayCalendar) -> HolidayCalendar {
+        core::mem::replace(&mut self.holidays, holidays)
+    }
+
+    /// Lock down an access point, denying every card until the lockdown is lifted.
+    pub fn lock_down(&mut self, door_id: DoorId) {
+        let _ = self.lockdowns.insert(door_id);
+    }
+
+    /// Lift the lockdown of an access point. Returns `false` if it wasn't locked down.
+    pub fn lift_lockdown(&mut self, door_id: DoorId) -> bool {
+        self.lockdowns.remove(&door_id)
+    }
+
+    #[inline]
+    pub fn is_locked_down(&self, door_id: DoorId) -> bool {
+        self.lockdowns.contains(&door_id)
+    }
+
+    /// Emergency unlock an access point, i.e. for evacuation, granting every card until
+    /// the unlock is lifted. This overrides lockdowns and schedules.
+    pub fn emergency_unlock(&mut self, door_id: DoorId) {
+        let _ = self.emergency_unlocks.insert(door_id);
+    }
+
+    /// Lift the emergency unlock of an access point. Returns `false` if it wasn't unlocked.
+    pub fn lift_emergency_unlock(&mut self, door_id: DoorId) -> bool {
+        self.emergency_unlocks.remove(&door_id)
+    }
+
+    #[inline]
+    pub fn is_emergency_unlocked(&self, door_id: DoorId) -> bool {
+        self.emergency_unlocks.contains(&door_id)
+    }
+
+    /// The permissions of a card that are in effect at `now`.
+    ///
+    /// These are the card's own permissions that haven't expired and the ones of its roles.
+    pub fn effective_permissions(&self, card: &Card, now: Timestamp) -> Permissions {
+        let own = card
+            .permissions()
+            .difference(card.permission_expiry().expired(now));
+        let perms = own | self.roles.expand(card.roles());
+        self.permission_schedules
+            .iter()
+            .filter(|(_, schedule)| !schedule.allows_except(now, &self.holidays))
+            .fold(perms, |perms, (masked, _)| perms.difference(*masked))
+    }
+
+    pub fn door(&self, door_id: DoorId) -> Option<&Door> {
+        self.doors.get(&door_id)
+    }
+
+    pub fn doors(&self) -> impl Iterator<Item = &Door> + '_ {
+        self.doors.values()
+    }
+
+    /// The permissions required by the installed doors.
+    pub fn matrix(&self) -> AccessMatrix {
+        self.doors
+            .values()
+            .map(|door| (door.id(), door.required()))
+            .collect()
+    }
+
+    /// Swap the permissions required by the installed doors for the ones of a matrix,
+    /// returning the previous matrix.
+    ///
+    /// Doors only in the matrix are installed as by [`Door::requiring`], doors missing from
+    /// it are uninstalled. The other doors keep their settings besides the permissions.
+    pub fn swap_matrix(&mut self, matrix: &AccessMatrix) -> AccessMatrix {
+        let previous = self.matrix();
+        let removed: Vec<DoorId> = previous
+            .iter()
+            .filter(|(door_id, _)| matrix.get(*door_id).is_none())
+            .map(|(door_id, _)| door_id)
+            .collect();
+        for door_id in removed {
+            let _ = self.uninstall(door_id);
+        }
+        for (door_id, required) in matrix.iter() {
+            let door = match self.doors.get(&door_id) {
+                Some(door) => door.with_required(required),
+                None => Door::requiring(door_id, required),
+            };
+            let _ = self.doors.insert(door_id, door);
+        }
+        previous
+    }
+
+    /// Decide whether a card may open a door installed in this policy,
+    /// consulting the clock for any schedules.
+    pub fn decide<C: Clock>(&self, card: &Card, door_id: DoorId, clock: C) -> Decision {
+        let Some(door) = self.doors.get(&door_id) else {
+            return Decision::Denied(DenyReason::UnknownDoor);
+        };
+        if self.is_emergency_unlocked(door_id) {
+            return Decision::Granted;
+        }
+        if self.is_locked_down(door_id) {
+            return Decision::Denied(DenyReason::LockedDown);
+        }
+
+        let now = clock.now();
+        let perms = self.effective_permissions(card, now);
+        if Self::bypasses(perms, door) {
+            return Decision::Granted;
+        }
+
+        match self.door_schedules.get(&door_id) {
+            Some(schedule) if !schedule.allows_except(now, &self.holidays) => {
+                Decision::Denied(DenyReason::OutsideSchedule)
+            }
+            _ => self
+                .positions
+                .evaluate(card.position(), door_id)
+                .unwrap_or_else(|| self.check_zoned(card, perms, door)),
+        }
+    }
+
+    /// Decide which floors of an installed elevator a card may select,
+    /// consulting the clock for any schedules.
+    ///
+    /// A floor is granted by its required permissions or by a zone it's placed in.
+    pub fn floors<C: Clock>(
+        &self,
+        card: &Card,
+        elevator_id: DoorId,
+        clock: C,
+    ) -> Result<FloorSet, DenyReason> {
+        let Some(elevator) = self.elevators.get(&elevator_id) else {
+            return Err(DenyReason::UnknownDoor);
+        };
+        if self.is_locked_down(elevator_id) {
+            return Err(DenyReason::LockedDown);
+        }
+
+        let now = clock.now();
+        let perms = self.effective_permissions(card, now);
+        if Self::bypasses(perms, elevator) {
+            return Ok(FloorSet::all(elevator));
+        }
+
+        match self.door_schedules.get(&elevator_id) {
+            Some(schedule) if !schedule.allows_except(now, &self.holidays) => {
+                return Err(DenyReason::OutsideSchedule);
+            }
+            _ => {}
+        }
+        let car = self
+            .positions
+            .evaluate(card.position(), elevator_id)
+            .unwrap_or_else(|| Self::check(perms, elevator));
+        if let Decision::Denied(reason) = car {
+            return Err(reason);
+        }
+
+        let mut floors = FloorSet::empty();
+        for (index, floor) in elevator.floors().iter().enumerate() {
+            let zoned = floor
+                .zone()
+                .is_some_and(|zone| self.zones.admits_zone(card, perms, zone));
+            if zoned || perms.contains(floor.required()) {
+                floors.insert(index);
+            }
+        }
+        if floors.is_empty() {
+            return Err(DenyReason::NoFloors);
+        }
+        Ok(floors)
+    }
+
+    /// Decide whether a card may open an access point at `now`, regardless of any schedules.
+    ///
+    /// Grants of the zone the access point is placed in and of the zones it's within
+    /// admit the card as well, as do grants of the groups it's a member of. The card's
+    /// roles are expanded into permissions as for [`AccessPolicy::effective_permissions`],
+    /// its own permissions that expired by `now` don't count.
+    pub fn can_open<A: AccessPoint + ?Sized>(
+        &self,
+        card: &Card,
+        point: &A,
+        now: Timestamp,
+    ) -> Decision {
+        let own = card
+            .permissions()
+            .difference(card.permission_expiry().expired(now));
+        let perms = own | self.roles.expand(card.roles());
+        if Self::bypasses(perms, point) {
+            return Decision::Granted;
+        }
+        self.check_zoned(card, perms, point)
+    }
+
+    #[inline]
+    fn bypasses<A: AccessPoint + ?Sized>(perms: Permissions, point: &A) -> bool {
+        perms.contains(Permissions::SUPER_ADMIN)
+            || (point.admin_bypass() && perms.contains(Permissions::ADMIN))
+    }
+
+    fn check_zoned<A: AccessPoint + ?Sized>(
+        &self,
+        card: &Card,
+        perms: Permissions,
+        point: &A,
+    ) -> Decision {
+        if self.zones.admits(card, perms, point.id()) || self.groups.admits(card, perms, point.id())
+        {
+            return Decision::Granted;
+        }
+        Self::check(perms, point)
+    }
+
+    fn check<A: AccessPoint + ?Sized>(perms: Permissions, point: &A) -> Decision {
+        let missing = point.required().difference(perms);
+        if missing.is_empty() {
+            Decision::Granted
+        } else {
+            Decision::Denied(DenyReason::MissingPermissions(missing))
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Duty-cycling the RF field of battery-operated readers.
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::{
+    errors::AccessError, logging::kernel_error, Kernel, NfcService, ReaderId, Timestamp, Uid,
+};
+
+/// How often [`NfcService::duty_cycle`] wakes the readers to poll.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PowerSchedule {
+    /// The wait between polls while tags are being presented.
+    pub active: Duration,
+    /// The wait between polls once no tag was seen for `linger`.
+    pub idle: Duration,
+    /// How long in seconds readers keep polling at the active rate after the last tag.
+    pub linger: u64,
+}
+
+impl PowerSchedule {
+    /// Poll five times a second for half a minute after a tag, and once a second otherwise.
+    pub const DEFAULT: PowerSchedule = PowerSchedule {
+        active: Duration::from_millis(200),
+        idle: Duration::from_secs(1),
+        linger: 30,
+    };
+}
+
+impl Default for PowerSchedule {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The schedule of a service and when it last saw a tag.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct PowerState {
+    schedule: PowerSchedule,
+    last_seen: Option<Timestamp>,
+}
+
+impl PowerState {
+    #[inline]
+    pub(crate) const fn new(schedule: PowerSchedule) -> Self {
+        Self {
+            schedule,
+            last_seen: None,
+        }
+    }
+
+    fn wait(&self, now: Timestamp) -> Duration {
+        match self.last_seen {
+            Some(seen) if now.saturating_sub(seen) < self.schedule.linger => self.schedule.active,
+            _ => self.schedule.idle,
+        }
+    }
+}
+
+/// The outcome of a single [`NfcService::duty_cycle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DutyCycle {
+    /// The tags that were present.
+    pub detected: Vec<(ReaderId, Uid)>,
+    /// How long to stay in low-power mode before the next cycle.
+    pub wait: Duration,
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Poll the readers on `schedule` when duty-cycling, see [`NfcService::duty_cycle`].
+    #[must_use]
+    pub fn with_power_schedule(mut self, schedule: PowerSchedule) -> Self {
+        self.set_power_schedule(schedule);
+        self
+    }
+
+    /// Replace the power schedule of this service.
+    #[inline]
+    pub fn set_power_schedule(&mut self, schedule: PowerSchedule) {
+        self.power.schedule = schedule;
+    }
+
+    #[inline]
+    pub const fn power_schedule(&self) -> &PowerSchedule {
+        &self.power.schedule
+    }
+
+    /// Put a reader in its low-power mode, turning its RF field off.
+    pub fn sleep(&mut self, reader: ReaderId) -> Result<(), AccessError> {
+        let kernel = self
+            .readers
+            .get_mut(&reader)
+            .ok_or(AccessError::UnknownReader(reader))?;
+        kernel.sleep().map_err(|why| kernel_error(reader, why))
+    }
+
+    /// Bring a reader back from its low-power mode.
+    pub fn wake(&mut self, reader: ReaderId) -> Result<(), AccessError> {
+        let kernel = self
+            .readers
+            .get_mut(&reader)
+            .ok_or(AccessError::UnknownReader(reader))?;
+        kernel.wake().map_err(|why| kernel_error(reader, why))
+    }
+
+    /// Wake every reader, poll it once and put it back to sleep.
+    ///
+    /// Battery-operated devices call this in a loop, sleeping for [`DutyCycle::wait`] in
+    /// between. Readers that fail to wake or poll are skipped until the next cycle.
+    pub fn duty_cycle(&mut self) -> DutyCycle {
+        for (&reader, kernel) in self.readers.iter_mut() {
+            if let Err(why) = kernel.wake() {
+                let _ = kernel_error(reader, why);
+            }
+        }
+
+        let detected = self.poll();
+
+        for (&reader, kernel) in self.readers.iter_mut() {
+            if let Err(why) = kernel.sleep() {
+                let _ = kernel_error(reader, why);
+            }
+        }
+
+        let now = self.now();
+        if !detected.is_empty() {
+            self.power.last_seen = Some(now);
+        }
+        DutyCycle {
+            detected,
+            wait: self.power.wait(now),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! A framed serial protocol for provisioning cards from a host tool.
+//!
+//! See [`crate::frame`] for the layout of the frames exchanged.
+use alloc::vec::Vec;
+
+use crate::{
+    codec,
+    errors::AccessError,
+    frame::{Frame, MAX_PAYLOAD, RESPONSE},
+    message::{Request, Response},
+    Card, Kernel, NfcService, Permissions, Uid,
+};
+
+/// The commands a host can send.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Command {
+    /// Register a card, the payload is the card as returned by [`Card::try_to_bytes`].
+    Enroll = 0x01,
+    /// Set the permissions of a card, the payload is the UID followed by the permission bits,
+    /// either four bytes in little endian or the single byte older hosts send.
+    Assign = 0x02,
+    /// Check a card is registered, the payload is the UID and the card is answered back.
+    Verify = 0x03,
+    /// Handle a [`Request`], the payload is the encoded request and the encoded
+    /// [`Response`] is answered back. Responses that don't fit in a frame are answered
+    /// as refused with [`AccessError::TooLarge`], page audit queries to stay within one.
+    Message = 0x04,
+}
+
+impl Command {
+    #[inline]
+    pub const fn from_u8(command: u8) -> Option<Self> {
+        match command {
+            0x01 => Some(Self::Enroll),
+            0x02 => Some(Self::Assign),
+            0x03 => Some(Self::Verify),
+            0x04 => Some(Self::Message),
+            _ => None,
+        }
+    }
+}
+
+/// The first byte of every response payload.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Status {
+    Ok = 0x00,
+    UnknownCard = 0x01,
+    Malformed = 0x02,
+    UnknownCommand = 0x03,
+    Refused = 0x04,
+    /// The response doesn't fit in a frame.
+    TooLarge = 0x05,
+}
+
+/// Read the permission bits following a UID, in either of the layouts [`Command::Assign`] takes.
+fn take_bits((uid, bits): (Uid, &[u8])) -> Option<(Uid, u32)> {
+    match *bits {
+        [bits] => Some((uid, bits as u32)),
+        [a, b, c, d] => Some((uid, u32::from_le_bytes([a, b, c, d]))),
+        _ => None,
+    }
+}
+
+/// Split a length prefixed UID off the start of a payload.
+fn take_uid(payload: &[u8]) -> Option<(Uid, &[u8])> {
+    let (&len, rest) = payload.split_first()?;
+    let len = len as usize;
+    if rest.len() < len {
+        return None;
+    }
+    Some((Uid::new(&rest[..len])?, &rest[len..]))
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Handle a provisioning request returning the response to send back.
+    ///
+    /// The response always fits in a frame, answering [`Status::TooLarge`] otherwise.
+    pub fn provision(&mut self, request: &Frame) -> Frame {
+        let (status, data) = match Command::from_u8(request.command) {
+            Some(Command::Enroll) => match Card::from_bytes(&request.payload) {
+                Ok(card) => {
+                    self.put(card);
+                    (Status::Ok, Vec::new())
+                }
+                Err(..) => (Status::Malformed, Vec::new()),
+            },
+            Some(Command::Assign) => match take_uid(&request.payload).and_then(take_bits) {
+                Some((uid, bits)) => match Permissions::from_bits(bits) {
+                    Some(perms) => match self.set_permissions(uid, perms) {
+                        Ok(()) => (Status::Ok, Vec::new()),
+                        Err(..) => (Status::UnknownCard, Vec::new()),
+                    },
+                    None => (Status::Malformed, Vec::new()),
+                },
+                _ => (Status::Malformed, Vec::new()),
+            },
+            Some(Command::Verify) => match take_uid(&request.payload) {
+                Some((uid, [])) if self.revoked.is_revoked(uid) => (Status::Refused, Vec::new()),
+                Some((uid, [])) => match self.get(&uid) {
+                    Some(card) => match card.try_to_bytes() {
+                        Ok(bytes) => (Status::Ok, bytes),
+                        Err(..) => (Status::Malformed, Vec::new()),
+                    },
+                    None => (Status::UnknownCard, Vec::new()),
+                },
+                _ => (Status::Malformed, Vec::new()),
+            },
+            Some(Command::Message) => {
+                match codec::from_slice::<Request>("Request", &request.payload) {
+                    Ok(message) => {
+                        let response = codec::to_vec(&self.handle(message)).and_then(|bytes| {
+                            match bytes.len() < MAX_PAYLOAD {
+                                true => Ok(bytes),
+                                false => codec::to_vec(&Response::Refused(AccessError::TooLarge)),
+                            }
+                        });
+                        match response {
+                            Ok(bytes) => (Status::Ok, bytes),
+                            Err(..) => (Status::Malformed, Vec::new()),
+                        }
+                    }
+                    Err(..) => (Status::Malformed, Vec::new()),
+                }
+            }
+            None => (Status::UnknownCommand, Vec::new()),
+        };
+        let (status, data) = match data.len() < MAX_PAYLOAD {
+            true => (status, data),
+            false => (Status::TooLarge, Vec::new()),
+        };
+
+        let mut payload = Vec::with_capacity(1 + data.len());
+        payload.push(status as u8);
+        payload.extend_from_slice(&data);
+        Frame::new(request.command | RESPONSE, payload)
+    }
+
+    /// Serve provisioning requests over a serial port until it reaches end of file.
+    #[cfg(feature = "embedded-io")]
+    pub fn serve<P>(&mut self, port: &mut P) -> Result<(), P::Error>
+    where
+        P: embedded_io::Read + embedded_io::Write,
+    {
+        let mut decoder = crate::frame::FrameDecoder::new();
+        let mut buf = [0; 64];
+        loop {
+            let read = port.read(&mut buf)?;
+            if read == 0 {
+                return Ok(());
+            }
+
+            decoder.push(&buf[..read]);
+            while let Some(request) = decoder.next_frame() {
+                // Responses always fit in a frame.
+                if let Ok(response) = self.provision(&request).encode() {
+                    port.write_all(&response)?;
+                }
+            }
+            port.flush()?;
+        }
+    }
+}
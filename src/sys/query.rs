@@ -0,0 +1,80 @@
+//! Borrowing queries over the registered cards.
+use alloc::vec::Vec;
+use core::ops::Bound;
+
+use crate::{Card, Kernel, NfcService, Permissions, Uid};
+
+/// A page of registered cards, see [`NfcService::page`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<'a> {
+    /// The cards on this page in ascending id order.
+    pub cards: Vec<&'a Card>,
+    /// The cursor of the next page, `None` if this is the last one.
+    pub next: Option<Uid>,
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// An iterator over the registered cards in ascending id order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Card> + '_ {
+        self.cards.values()
+    }
+
+    /// An iterator over the registered cards holding all of `perms`.
+    pub fn iter_with(&self, perms: Permissions) -> impl Iterator<Item = &Card> + '_ {
+        self.cards.values().filter(move |card| card.is(perms))
+    }
+
+    /// The first registered card, in ascending id order, matching a predicate.
+    pub fn find<P>(&self, mut predicate: P) -> Option<&Card>
+    where
+        P: FnMut(&Card) -> bool,
+    {
+        self.cards.values().find(|card| predicate(card))
+    }
+
+    /// The number of registered cards holding all of `perms`.
+    #[inline]
+    pub fn count_with(&self, perms: Permissions) -> usize {
+        self.iter_with(perms).count()
+    }
+
+    /// The number of registered cards holding each permission.
+    pub fn count_by_permission(&self) -> Vec<(Permissions, usize)> {
+        let mut counts = Permissions::all()
+            .iter()
+            .map(|perm| (perm, 0))
+            .collect::<Vec<_>>();
+        for card in self.cards.values() {
+            for (perm, count) in counts.iter_mut() {
+                if card.is(*perm) {
+                    *count += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    /// Up to `limit` registered cards following the `after` cursor.
+    ///
+    /// Start with `None` and pass each page's [`Page::next`] to walk the whole registry,
+    /// cards registered or removed in between don't shift the pages.
+    pub fn page(&self, after: Option<Uid>, limit: usize) -> Page<'_> {
+        let start = after.map_or(Bound::Unbounded, Bound::Excluded);
+        let mut range = self.cards.range((start, Bound::Unbounded));
+        let cards = range
+            .by_ref()
+            .take(limit)
+            .map(|(_, card)| card)
+            .collect::<Vec<_>>();
+        let next = match range.next() {
+            Some(..) => cards.last().map(|card| card.id),
+            None => None,
+        };
+        Page { cards, next }
+    }
+}
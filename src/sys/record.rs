@@ -0,0 +1,652 @@
+//! Recording the commands a service sends a [`Kernel`] and replaying them.
+//!
+//! Wrap a reader's kernel in a [`RecordingKernel`] to capture a field issue, then replay
+//! the [`Recording`] with a [`ReplayKernel`], or turn it into a [`MockKernel`] to keep it
+//! around as a regression test.
+use alloc::vec::Vec;
+use core::{
+    cell::UnsafeCell,
+    hint,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use crate::{
+    challenge::{Answer, Mac, Nonce},
+    errors::KernelError,
+    firmware::FirmwareVersion,
+    mock::MockKernel,
+    tamper::Tamper,
+    Card, Kernel, Technology, Uid,
+};
+
+/// A command sent to a [`Kernel`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Read a tag, through [`Kernel::read`] or [`Kernel::read_into`], answered with the
+    /// payload read by the latter.
+    Read(Uid),
+    Write {
+        card: Card,
+        data: Vec<u8>,
+    },
+    /// Read part of a tag's memory, through [`Kernel::read_pages`].
+    ReadPages {
+        card: Uid,
+        page: usize,
+        len: usize,
+    },
+    /// Write part of a tag's memory, through [`Kernel::write_pages`].
+    WritePages {
+        card: Card,
+        page: usize,
+        data: Vec<u8>,
+    },
+    SetTechnology(Technology),
+    Transceive(Vec<u8>),
+    Challenge {
+        card: Uid,
+        challenge: Nonce,
+    },
+    Confirm {
+        card: Uid,
+        proof: Mac,
+    },
+    FirmwareVersion,
+    BeginFirmware(usize),
+    WriteFirmware {
+        offset: usize,
+        chunk: Vec<u8>,
+    },
+    FinishFirmware,
+    CheckEeprom,
+    Sleep,
+    Wake,
+    Tamper,
+    Sense,
+    SenseTimeout(Duration),
+}
+
+/// What a [`Kernel`] answered a [`Command`] with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    /// The command succeeded without returning anything.
+    Done,
+    /// The tag read, as it was before the caller changed it.
+    Card(Card),
+    Bytes(Vec<u8>),
+    Answer(Answer),
+    Firmware(FirmwareVersion),
+    Tamper(Option<Tamper>),
+    Sense(Option<Uid>),
+}
+
+/// A command and its response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exchange {
+    pub command: Command,
+    pub response: Result<Reply, KernelError>,
+}
+
+/// The exchanges recorded by a [`RecordingKernel`], in the order they happened.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    exchanges: Vec<Exchange>,
+}
+
+#[allow(dead_code)]
+impl Recording {
+    /// Create a new empty Recording.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            exchanges: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn exchanges(&self) -> &[Exchange] {
+        &self.exchanges
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.exchanges.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.exchanges.is_empty()
+    }
+
+    /// Script a [`MockKernel`] answering like the recorded kernel.
+    ///
+    /// The mock serves the tags read and answers the polls, APDU exchanges and failed
+    /// reads and writes in the recorded order. Challenge-response, firmware updates and
+    /// tamper sensors aren't scripted, the mock doesn't support them.
+    pub fn to_mock(&self) -> MockKernel {
+        let mut mock = MockKernel::new();
+        let (mut reads, mut writes) = (0, 0);
+        for exchange in &self.exchanges {
+            let response = exchange.response.as_ref();
+            mock = match (&exchange.command, response) {
+                (Command::Read(..), Ok(Reply::Card(card))) => {
+                    reads += 1;
+                    mock.with_card(*card)
+                }
+                (Command::Read(..), Ok(Reply::Bytes(payload))) => {
+                    reads += 1;
+                    match Card::from_bytes(payload) {
+                        Ok(card) => mock.with_card(card),
+                        Err(..) => mock,
+                    }
+                }
+                (Command::Read(..), Err(why)) => {
+                    reads += 1;
+                    mock.fail_read(reads - 1, *why)
+                }
+                (Command::Write { .. }, Err(why)) => {
+                    writes += 1;
+                    mock.fail_write(writes - 1, *why)
+                }
+                (Command::Write { .. }, _) => {
+                    writes += 1;
+                    mock
+                }
+                (Command::Transceive(..), Ok(Reply::Bytes(bytes))) => {
+                    mock.respond(Ok(bytes.clone()))
+                }
+                (Command::Transceive(..), Err(why)) => mock.respond(Err(*why)),
+                (Command::FirmwareVersion, Ok(Reply::Firmware(version))) => {
+                    mock.with_firmware(*version)
+                }
+                (Command::Sense | Command::SenseTimeout(..), Ok(Reply::Sense(Some(id)))) => {
+                    mock.present(*id)
+                }
+                (Command::Sense | Command::SenseTimeout(..), Ok(..)) => mock.absent(),
+                (Command::Sense | Command::SenseTimeout(..), Err(why)) => mock.fail(*why),
+                _ => mock,
+            };
+        }
+        mock
+    }
+}
+
+impl FromIterator<Exchange> for Recording {
+    fn from_iter<I: IntoIterator<Item = Exchange>>(iter: I) -> Self {
+        Self {
+            exchanges: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// The exchanges a [`RecordingKernel`] recorded so far.
+///
+/// [`Kernel::read`] only borrows the kernel, so recording is guarded by a spin lock.
+/// It's never contended, since the service holds its kernels exclusively.
+#[derive(Debug, Default)]
+struct Tape {
+    busy: AtomicBool,
+    recording: UnsafeCell<Recording>,
+}
+
+// SAFETY: the recording is only reached through `push` while holding `busy`,
+// or through an exclusive reference.
+unsafe impl Sync for Tape {}
+
+impl Tape {
+    fn push(&self, command: Command, response: Result<Reply, KernelError>) {
+        while self
+            .busy
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            hint::spin_loop();
+        }
+        // SAFETY: `busy` is held, nothing else touches the recording.
+        let recording = unsafe { &mut *self.recording.get() };
+        recording.exchanges.push(Exchange { command, response });
+        self.busy.store(false, Ordering::Release);
+    }
+
+    #[inline]
+    fn get(&mut self) -> &Recording {
+        self.recording.get_mut()
+    }
+
+    #[inline]
+    fn take(&mut self) -> Recording {
+        core::mem::take(self.recording.get_mut())
+    }
+}
+
+/// A [`Kernel`] decorator recording every command sent to the kernel it wraps,
+/// along with its response.
+///
+/// [`Kernel::update_firmware`] and [`Kernel::self_test`] are recorded as the commands
+/// they're made of.
+#[derive(Debug)]
+pub struct RecordingKernel<K> {
+    inner: K,
+    tape: Tape,
+}
+
+#[allow(dead_code)]
+impl<K: Kernel> RecordingKernel<K> {
+    /// Start recording the commands sent to a kernel.
+    #[inline]
+    pub fn new(inner: K) -> Self {
+        Self {
+            inner,
+            tape: Tape::default(),
+        }
+    }
+
+    /// The exchanges recorded so far.
+    #[inline]
+    pub fn recording(&mut self) -> &Recording {
+        self.tape.get()
+    }
+
+    /// Take the exchanges recorded so far, recording the next ones afresh.
+    #[inline]
+    pub fn take_recording(&mut self) -> Recording {
+        self.tape.take()
+    }
+
+    #[inline]
+    pub const fn inner(&self) -> &K {
+        &self.inner
+    }
+
+    /// Stop recording, returning the kernel and what it recorded.
+    #[inline]
+    pub fn into_parts(mut self) -> (K, Recording) {
+        let recording = self.tape.take();
+        (self.inner, recording)
+    }
+
+    fn record<T>(
+        &self,
+        command: Command,
+        result: Result<T, KernelError>,
+        reply: impl FnOnce(&T) -> Reply,
+    ) -> Result<T, KernelError> {
+        self.tape
+            .push(command, result.as_ref().map(reply).map_err(|why| *why));
+        result
+    }
+}
+
+impl<K: Kernel> Kernel for RecordingKernel<K> {
+    const FIRMWARE_CHUNK: usize = K::FIRMWARE_CHUNK;
+    const POLL_INTERVAL: Duration = K::POLL_INTERVAL;
+    const PAGE_SIZE: Option<usize> = K::PAGE_SIZE;
+
+    fn read(&self, card: Uid) -> Result<&Card, KernelError> {
+        let result = self.inner.read(card);
+        self.record(Command::Read(card), result, |card| Reply::Card(**card))
+    }
+
+    fn read_into(&mut self, card: Uid, buf: &mut [u8]) -> Result<usize, KernelError> {
+        let result = self.inner.read_into(card, buf);
+        self.record(Command::Read(card), result, |len| {
+            Reply::Bytes(buf[..*len].into())
+        })
+    }
+
+    fn write(&mut self, card: &Card, data: &[u8]) -> Result<(), KernelError> {
+        let command = Command::Write {
+            card: *card,
+            data: data.into(),
+        };
+        let result = self.inner.write(card, data);
+        self.record(command, result, |_| Reply::Done)
+    }
+
+    fn read_pages(&mut self, card: Uid, page: usize, buf: &mut [u8]) -> Result<(), KernelError> {
+        let command = Command::ReadPages {
+            card,
+            page,
+            len: buf.len(),
+        };
+        let result = self.inner.read_pages(card, page, buf);
+        self.record(command, result, |_| Reply::Bytes(buf.into()))
+    }
+
+    fn write_pages(&mut self, card: &Card, page: usize, data: &[u8]) -> Result<(), KernelError> {
+        let command = Command::WritePages {
+            card: *card,
+            page,
+            data: data.into(),
+        };
+        let result = self.inner.write_pages(card, page, data);
+        self.record(command, result, |_| Reply::Done)
+    }
+
+    fn set_technology(&mut self, technology: Technology) -> Result<(), KernelError> {
+        let result = self.inner.set_technology(technology);
+        self.record(Command::SetTechnology(technology), result, |_| Reply::Done)
+    }
+
+    fn transceive(&mut self, apdu: &[u8]) -> Result<Vec<u8>, KernelError> {
+        let result = self.inner.transceive(apdu);
+        self.record(Command::Transceive(apdu.into()), result, |bytes| {
+            Reply::Bytes(bytes.clone())
+        })
+    }
+
+    fn challenge(&mut self, card: Uid, challenge: &Nonce) -> Result<Answer, KernelError> {
+        let command = Command::Challenge {
+            card,
+            challenge: *challenge,
+        };
+        let result = self.inner.challenge(card, challenge);
+        self.record(command, result, |answer| Reply::Answer(*answer))
+    }
+
+    fn confirm(&mut self, card: Uid, proof: &Mac) -> Result<(), KernelError> {
+        let command = Command::Confirm {
+            card,
+            proof: *proof,
+        };
+        let result = self.inner.confirm(card, proof);
+        self.record(command, result, |_| Reply::Done)
+    }
+
+    fn firmware_version(&mut self) -> Result<FirmwareVersion, KernelError> {
+        let result = self.inner.firmware_version();
+        self.record(Command::FirmwareVersion, result, |version| {
+            Reply::Firmware(*version)
+        })
+    }
+
+    fn begin_firmware(&mut self, len: usize) -> Result<(), KernelError> {
+        let result = self.inner.begin_firmware(len);
+        self.record(Command::BeginFirmware(len), result, |_| Reply::Done)
+    }
+
+    fn write_firmware(&mut self, offset: usize, chunk: &[u8]) -> Result<(), KernelError> {
+        let command = Command::WriteFirmware {
+            offset,
+            chunk: chunk.into(),
+        };
+        let result = self.inner.write_firmware(offset, chunk);
+        self.record(command, result, |_| Reply::Done)
+    }
+
+    fn finish_firmware(&mut self) -> Result<FirmwareVersion, KernelError> {
+        let result = self.inner.finish_firmware();
+        self.record(Command::FinishFirmware, result, |version| {
+            Reply::Firmware(*version)
+        })
+    }
+
+    fn check_eeprom(&mut self) -> Result<(), KernelError> {
+        let result = self.inner.check_eeprom();
+        self.record(Command::CheckEeprom, result, |_| Reply::Done)
+    }
+
+    fn sleep(&mut self) -> Result<(), KernelError> {
+        let result = self.inner.sleep();
+        self.record(Command::Sleep, result, |_| Reply::Done)
+    }
+
+    fn wake(&mut self) -> Result<(), KernelError> {
+        let result = self.inner.wake();
+        self.record(Command::Wake, result, |_| Reply::Done)
+    }
+
+    fn tamper(&mut self) -> Result<Option<Tamper>, KernelError> {
+        let result = self.inner.tamper();
+        self.record(Command::Tamper, result, |tamper| Reply::Tamper(*tamper))
+    }
+
+    fn delay(&mut self, duration: Duration) {
+        self.inner.delay(duration);
+    }
+
+    fn sense(&mut self) -> Result<Option<Uid>, KernelError> {
+        let result = self.inner.sense();
+        self.record(Command::Sense, result, |id| Reply::Sense(*id))
+    }
+
+    fn sense_timeout(&mut self, timeout: Duration) -> Result<Option<Uid>, KernelError> {
+        let result = self.inner.sense_timeout(timeout);
+        self.record(Command::SenseTimeout(timeout), result, |id| {
+            Reply::Sense(*id)
+        })
+    }
+}
+
+/// What a [`ReplayKernel`] fails commands with once they differ from the recording.
+const DIVERGED: KernelError = KernelError::Unsupported("replay diverged");
+
+/// A [`Kernel`] answering the commands sent to it from a [`Recording`].
+///
+/// Commands must be sent in the recorded order. The first one that isn't, and every one
+/// after it, fails with [`KernelError::Unsupported`], see [`ReplayKernel::diverged`].
+/// It doesn't address pages, so recordings of kernels that do diverge at their first
+/// [`Command::WritePages`] sent for a delta write.
+#[derive(Debug)]
+pub struct ReplayKernel {
+    recording: Recording,
+    next: AtomicUsize,
+    diverged: AtomicUsize,
+}
+
+#[allow(dead_code)]
+impl ReplayKernel {
+    #[inline]
+    pub const fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            next: AtomicUsize::new(0),
+            diverged: AtomicUsize::new(usize::MAX),
+        }
+    }
+
+    /// The index of the exchange the replay diverged from the recording at, if it did.
+    #[inline]
+    pub fn diverged(&self) -> Option<usize> {
+        match self.diverged.load(Ordering::Relaxed) {
+            usize::MAX => None,
+            at => Some(at),
+        }
+    }
+
+    /// The exchanges left to replay.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.recording.len() - self.next.load(Ordering::Relaxed)
+    }
+
+    /// Whether every recorded exchange was replayed without diverging.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.diverged().is_none() && self.remaining() == 0
+    }
+
+    /// Move past the next exchange if it's this command, returning its index.
+    fn advance(&self, command: &Command) -> Result<usize, KernelError> {
+        if self.diverged().is_some() {
+            return Err(DIVERGED);
+        }
+        let at = self.next.load(Ordering::Relaxed);
+        match self.recording.exchanges.get(at) {
+            Some(exchange) if exchange.command == *command => {
+                self.next.store(at + 1, Ordering::Relaxed);
+                Ok(at)
+            }
+            _ => {
+                self.diverged.store(at, Ordering::Relaxed);
+                Err(DIVERGED)
+            }
+        }
+    }
+
+    /// Replay a command, returning its recorded reply.
+    fn replay(&self, command: Command) -> Result<&Reply, KernelError> {
+        let at = self.advance(&command)?;
+        self.recording.exchanges[at]
+            .response
+            .as_ref()
+            .map_err(|why| *why)
+    }
+
+    fn done(&self, command: Command) -> Result<(), KernelError> {
+        match self.replay(command)? {
+            Reply::Done => Ok(()),
+            _ => Err(DIVERGED),
+        }
+    }
+
+    fn firmware(&self, command: Command) -> Result<FirmwareVersion, KernelError> {
+        match self.replay(command)? {
+            Reply::Firmware(version) => Ok(*version),
+            _ => Err(DIVERGED),
+        }
+    }
+
+    fn sensed(&self, command: Command) -> Result<Option<Uid>, KernelError> {
+        match self.replay(command)? {
+            Reply::Sense(id) => Ok(*id),
+            _ => Err(DIVERGED),
+        }
+    }
+}
+
+impl Kernel for ReplayKernel {
+    fn read(&self, card: Uid) -> Result<&Card, KernelError> {
+        match self.replay(Command::Read(card))? {
+            Reply::Card(card) => Ok(card),
+            _ => Err(DIVERGED),
+        }
+    }
+
+    fn read_into(&mut self, card: Uid, buf: &mut [u8]) -> Result<usize, KernelError> {
+        match self.replay(Command::Read(card))? {
+            Reply::Card(card) => card
+                .encode_into(buf)
+                .map_err(|_| KernelError::Read { status: 0 }),
+            Reply::Bytes(payload) => {
+                let read = buf
+                    .get_mut(..payload.len())
+                    .ok_or(KernelError::Read { status: 0 })?;
+                read.copy_from_slice(payload);
+                Ok(payload.len())
+            }
+            _ => Err(DIVERGED),
+        }
+    }
+
+    fn write(&mut self, card: &Card, data: &[u8]) -> Result<(), KernelError> {
+        self.done(Command::Write {
+            card: *card,
+            data: data.into(),
+        })
+    }
+
+    fn read_pages(&mut self, card: Uid, page: usize, buf: &mut [u8]) -> Result<(), KernelError> {
+        let command = Command::ReadPages {
+            card,
+            page,
+            len: buf.len(),
+        };
+        match self.replay(command)? {
+            Reply::Bytes(bytes) if bytes.len() == buf.len() => {
+                buf.copy_from_slice(bytes);
+                Ok(())
+            }
+            _ => Err(DIVERGED),
+        }
+    }
+
+    fn write_pages(&mut self, card: &Card, page: usize, data: &[u8]) -> Result<(), KernelError> {
+        self.done(Command::WritePages {
+            card: *card,
+            page,
+            data: data.into(),
+        })
+    }
+
+    fn set_technology(&mut self, technology: Technology) -> Result<(), KernelError> {
+        self.done(Command::SetTechnology(technology))
+    }
+
+    fn transceive(&mut self, apdu: &[u8]) -> Result<Vec<u8>, KernelError> {
+        match self.replay(Command::Transceive(apdu.into()))? {
+            Reply::Bytes(bytes) => Ok(bytes.clone()),
+            _ => Err(DIVERGED),
+        }
+    }
+
+    fn challenge(&mut self, card: Uid, challenge: &Nonce) -> Result<Answer, KernelError> {
+        let command = Command::Challenge {
+            card,
+            challenge: *challenge,
+        };
+        match self.replay(command)? {
+            Reply::Answer(answer) => Ok(*answer),
+            _ => Err(DIVERGED),
+        }
+    }
+
+    fn confirm(&mut self, card: Uid, proof: &Mac) -> Result<(), KernelError> {
+        self.done(Command::Confirm {
+            card,
+            proof: *proof,
+        })
+    }
+
+    fn firmware_version(&mut self) -> Result<FirmwareVersion, KernelError> {
+        self.firmware(Command::FirmwareVersion)
+    }
+
+    fn begin_firmware(&mut self, len: usize) -> Result<(), KernelError> {
+        self.done(Command::BeginFirmware(len))
+    }
+
+    fn write_firmware(&mut self, offset: usize, chunk: &[u8]) -> Result<(), KernelError> {
+        self.done(Command::WriteFirmware {
+            offset,
+            chunk: chunk.into(),
+        })
+    }
+
+    fn finish_firmware(&mut self) -> Result<FirmwareVersion, KernelError> {
+        self.firmware(Command::FinishFirmware)
+    }
+
+    fn check_eeprom(&mut self) -> Result<(), KernelError> {
+        self.done(Command::CheckEeprom)
+    }
+
+    fn sleep(&mut self) -> Result<(), KernelError> {
+        self.done(Command::Sleep)
+    }
+
+    fn wake(&mut self) -> Result<(), KernelError> {
+        self.done(Command::Wake)
+    }
+
+    fn tamper(&mut self) -> Result<Option<Tamper>, KernelError> {
+        match self.replay(Command::Tamper)? {
+            Reply::Tamper(tamper) => Ok(*tamper),
+            _ => Err(DIVERGED),
+        }
+    }
+
+    /// Replays don't wait between retries.
+    fn delay(&mut self, duration: Duration) {
+        let _ = duration;
+    }
+
+    fn sense(&mut self) -> Result<Option<Uid>, KernelError> {
+        self.sensed(Command::Sense)
+    }
+
+    fn sense_timeout(&mut self, timeout: Duration) -> Result<Option<Uid>, KernelError> {
+        self.sensed(Command::SenseTimeout(timeout))
+    }
+}
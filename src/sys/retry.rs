@@ -0,0 +1,133 @@
+//! Retrying reads and writes of tags that fail transiently, i.e. a card moved out of the field.
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::{
+    errors::{KernelError, RetryError},
+    Kernel, NfcService,
+};
+
+/// How the service retries the reads and writes of tags, doubling the delay after every
+/// attempt.
+///
+/// Only errors that may clear up are retried, see [`KernelError::is_transient`]. The
+/// timeouts bound the delays waited between the attempts of an operation, a retry that
+/// would exceed them isn't made. The attempts of a read or write that failed at the kernel
+/// are returned with its error, see [`TagError`](crate::errors::TagError).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The attempts an operation gets, at least one.
+    pub attempts: u8,
+    /// The delay before the first retry.
+    pub backoff: Duration,
+    /// The longest reads are retried for.
+    pub read_timeout: Duration,
+    /// The longest writes are retried for.
+    pub write_timeout: Duration,
+}
+
+#[allow(dead_code)]
+impl RetryPolicy {
+    /// Make every operation once.
+    pub const NONE: Self = Self {
+        attempts: 1,
+        backoff: Duration::ZERO,
+        read_timeout: Duration::ZERO,
+        write_timeout: Duration::ZERO,
+    };
+
+    /// Make 3 attempts, starting after 50 milliseconds, reads for at most half a second
+    /// and writes for at most a second.
+    pub const DEFAULT: Self = Self {
+        attempts: 3,
+        backoff: Duration::from_millis(50),
+        read_timeout: Duration::from_millis(500),
+        write_timeout: Duration::from_secs(1),
+    };
+
+    /// The delay before a retry, counting from `0`.
+    #[inline]
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.backoff.saturating_mul(1 << retry.min(16))
+    }
+
+    /// Read through a kernel, retrying as configured.
+    pub(crate) fn read<K, T, F>(&self, kernel: &mut K, operation: F) -> Result<T, RetryError>
+    where
+        K: Kernel + ?Sized,
+        F: FnMut(&mut K) -> Result<T, KernelError>,
+    {
+        self.run(kernel, self.read_timeout, operation)
+    }
+
+    /// Write through a kernel, retrying as configured.
+    pub(crate) fn write<K, T, F>(&self, kernel: &mut K, operation: F) -> Result<T, RetryError>
+    where
+        K: Kernel + ?Sized,
+        F: FnMut(&mut K) -> Result<T, KernelError>,
+    {
+        self.run(kernel, self.write_timeout, operation)
+    }
+
+    fn run<K, T, F>(
+        &self,
+        kernel: &mut K,
+        timeout: Duration,
+        mut operation: F,
+    ) -> Result<T, RetryError>
+    where
+        K: Kernel + ?Sized,
+        F: FnMut(&mut K) -> Result<T, KernelError>,
+    {
+        let mut attempts = Vec::new();
+        let mut waited = Duration::ZERO;
+        loop {
+            let why = match operation(kernel) {
+                Ok(value) => return Ok(value),
+                Err(why) => why,
+            };
+            attempts.push(why);
+
+            let delay = self.delay(attempts.len() as u32 - 1);
+            if !why.is_transient()
+                || attempts.len() >= self.attempts.max(1) as usize
+                || waited.saturating_add(delay) > timeout
+            {
+                return Err(RetryError { attempts });
+            }
+            kernel.delay(delay);
+            waited += delay;
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Retry the reads and writes of tags with `policy`, [`RetryPolicy::DEFAULT`] unless
+    /// configured.
+    #[must_use]
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.set_retry_policy(policy);
+        self
+    }
+
+    /// Replace the retry policy of this service, see [`NfcService::with_retry_policy`].
+    #[inline]
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry = policy;
+    }
+
+    #[inline]
+    pub const fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+}
@@ -0,0 +1,78 @@
+use alloc::{collections::btree_set::BTreeSet, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::{codec, errors::ConversionError, Uid};
+
+/// A list of revoked card ids.
+///
+/// The list is consulted on every access decision, a revoked card is denied
+/// even if the tag itself still carries a valid payload.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevocationList {
+    ids: BTreeSet<Uid>,
+}
+
+#[allow(dead_code)]
+impl RevocationList {
+    /// Create a new empty `RevocationList`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            ids: BTreeSet::new(),
+        }
+    }
+
+    /// Revoke a card. Returns `false` if it was already revoked.
+    #[inline]
+    pub fn revoke(&mut self, card_id: Uid) -> bool {
+        self.ids.insert(card_id)
+    }
+
+    /// Lift the revocation of a card. Returns `false` if it wasn't revoked.
+    #[inline]
+    pub fn reinstate(&mut self, card_id: Uid) -> bool {
+        self.ids.remove(&card_id)
+    }
+
+    /// Revoke every card revoked in another list, returning how many weren't revoked yet.
+    pub fn merge(&mut self, other: &RevocationList) -> usize {
+        let len = self.ids.len();
+        self.ids.extend(other.iter());
+        self.ids.len() - len
+    }
+
+    /// Check whether a card is revoked.
+    #[inline]
+    pub fn is_revoked(&self, card_id: Uid) -> bool {
+        self.ids.contains(&card_id)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// An iterator over the revoked card ids in ascending order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = Uid> + '_ {
+        self.ids.iter().copied()
+    }
+
+    /// Convert this list into a bytes payload ready to get persisted.
+    #[inline]
+    pub fn as_bytes(&self) -> Vec<u8> {
+        // A set of integers always serializes.
+        codec::to_vec(self).unwrap_or_default()
+    }
+
+    /// Restore a list from a persisted bytes payload.
+    #[inline]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ConversionError> {
+        codec::from_slice("RevocationList", bytes)
+    }
+}
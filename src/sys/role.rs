@@ -0,0 +1,187 @@
+use alloc::{collections::btree_map::BTreeMap, string::String};
+use serde::{Deserialize, Serialize};
+
+use crate::Permissions;
+
+/// The identifier of a [`Role`] within a [`RoleRegistry`].
+pub type RoleId = u8;
+
+/// A named group of permissions, i.e. "Security" or "Facilities".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Role {
+    name: String,
+    permissions: Permissions,
+}
+
+#[allow(dead_code)]
+impl Role {
+    /// Create a new Role.
+    #[inline]
+    pub fn new(name: impl Into<String>, permissions: Permissions) -> Self {
+        Self {
+            name: name.into(),
+            permissions,
+        }
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The permissions this Role expands to.
+    #[inline]
+    pub const fn permissions(&self) -> Permissions {
+        self.permissions
+    }
+}
+
+/// A compact set of [`RoleId`]s assigned to a Card.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RoleSet(u32);
+
+#[allow(dead_code)]
+impl RoleSet {
+    /// The maximum number of roles a set can hold.
+    pub const CAPACITY: RoleId = u32::BITS as RoleId;
+
+    /// An empty set.
+    #[inline]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Insert a role. Returns `false` if it was already present or out of range.
+    #[inline]
+    pub fn insert(&mut self, role: RoleId) -> bool {
+        if role >= Self::CAPACITY || self.contains(role) {
+            return false;
+        }
+        self.0 |= 1 << role;
+        true
+    }
+
+    /// Remove a role. Returns `false` if it wasn't present.
+    #[inline]
+    pub fn remove(&mut self, role: RoleId) -> bool {
+        let present = self.contains(role);
+        if present {
+            self.0 &= !(1 << role);
+        }
+        present
+    }
+
+    #[inline]
+    pub const fn contains(&self, role: RoleId) -> bool {
+        role < Self::CAPACITY && self.0 & (1 << role) != 0
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// An iterator over the roles in this set in ascending order.
+    #[inline]
+    pub fn iter(self) -> impl Iterator<Item = RoleId> {
+        (0..Self::CAPACITY).filter(move |role| self.contains(*role))
+    }
+}
+
+/// The roles known to an installation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoleRegistry {
+    roles: BTreeMap<RoleId, Role>,
+    /// The ids of removed roles that cards may still hold, never given to a new role.
+    #[serde(default)]
+    retired: RoleSet,
+}
+
+#[allow(dead_code)]
+impl RoleRegistry {
+    /// Create a new empty `RoleRegistry`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            roles: BTreeMap::new(),
+            retired: RoleSet::empty(),
+        }
+    }
+
+    /// A registry with the common "Security", "IT" and "Facilities" roles.
+    pub fn standard() -> Self {
+        let mut this = Self::new();
+        let _ = this.define(Role::new(
+            "Security",
+            Permissions::REGULAR | Permissions::OPEN_DOORS,
+        ));
+        let _ = this.define(Role::new(
+            "IT",
+            Permissions::REGULAR | Permissions::IT_SUPPORT,
+        ));
+        let _ = this.define(Role::new(
+            "Facilities",
+            Permissions::REGULAR | Permissions::OPEN_DOORS,
+        ));
+        this
+    }
+
+    /// Define a new role. Returns its id or `None` if the registry is full.
+    ///
+    /// The ids of removed roles aren't reused until they're released.
+    pub fn define(&mut self, role: Role) -> Option<RoleId> {
+        let id = (0..RoleSet::CAPACITY)
+            .find(|id| !self.roles.contains_key(id) && !self.retired.contains(*id))?;
+        let _ = self.roles.insert(id, role);
+        Some(id)
+    }
+
+    /// Remove a role. Cards that were assigned it no longer get its permissions.
+    ///
+    /// Its id is retired, cards still holding it would gain the permissions of a new role
+    /// given the id. See [`NfcService::remove_role`](crate::NfcService::remove_role) to
+    /// remove it from the cards as well.
+    pub fn remove(&mut self, id: RoleId) -> Option<Role> {
+        let role = self.roles.remove(&id)?;
+        let _ = self.retired.insert(id);
+        Some(role)
+    }
+
+    /// Make a retired id available to new roles again, once no card holds it anymore.
+    pub(crate) fn release(&mut self, id: RoleId) {
+        let _ = self.retired.remove(id);
+    }
+
+    pub fn get(&self, id: RoleId) -> Option<&Role> {
+        self.roles.get(&id)
+    }
+
+    /// Find a role by its name.
+    pub fn find(&self, name: &str) -> Option<(RoleId, &Role)> {
+        self.roles
+            .iter()
+            .find(|(_, role)| role.name == name)
+            .map(|(id, role)| (*id, role))
+    }
+
+    /// Change the permissions a role expands to. Returns the old permissions.
+    pub fn set_permissions(&mut self, id: RoleId, permissions: Permissions) -> Option<Permissions> {
+        self.roles
+            .get_mut(&id)
+            .map(|role| core::mem::replace(&mut role.permissions, permissions))
+    }
+
+    /// An iterator over all the roles.
+    pub fn iter(&self) -> impl Iterator<Item = (RoleId, &Role)> + '_ {
+        self.roles.iter().map(|(id, role)| (*id, role))
+    }
+
+    /// Expand a set of roles into the union of their permissions.
+    pub fn expand(&self, roles: RoleSet) -> Permissions {
+        roles
+            .iter()
+            .filter_map(|id| self.roles.get(&id))
+            .fold(Permissions::empty(), |perms, role| perms | role.permissions)
+    }
+}
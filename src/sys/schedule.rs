@@ -0,0 +1,366 @@
+use alloc::{collections::btree_set::BTreeSet, string::ToString, vec::Vec};
+use core::{fmt, str::FromStr};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{errors::HolidayError, Timestamp};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+bitflags::bitflags! {
+    /// Days of the week a [`Schedule`] applies to.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+    pub struct Days: u8 {
+        const MONDAY = 1 << 0;
+        const TUESDAY = 1 << 1;
+        const WEDNESDAY = 1 << 2;
+        const THURSDAY = 1 << 3;
+        const FRIDAY = 1 << 4;
+        const SATURDAY = 1 << 5;
+        const SUNDAY = 1 << 6;
+        /// Monday through Friday.
+        const WEEKDAYS = Self::MONDAY.bits()
+            | Self::TUESDAY.bits()
+            | Self::WEDNESDAY.bits()
+            | Self::THURSDAY.bits()
+            | Self::FRIDAY.bits();
+        /// Saturday and Sunday.
+        const WEEKEND = Self::SATURDAY.bits() | Self::SUNDAY.bits();
+    }
+}
+
+impl Days {
+    /// The day of the week `timestamp` falls on.
+    #[inline]
+    pub const fn of(timestamp: Timestamp) -> Self {
+        // The unix epoch was a Thursday.
+        Self::from_bits_retain(1 << ((timestamp / SECONDS_PER_DAY + 3) % 7))
+    }
+}
+
+/// A window of time within a day, in seconds since midnight.
+///
+/// Windows where `end` comes before `start` wrap around midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeWindow {
+    start: u32,
+    end: u32,
+}
+
+impl TimeWindow {
+    /// Create a new window from `start` to `end`, in seconds since midnight.
+    #[inline]
+    pub const fn new(start: u32, end: u32) -> Self {
+        Self { start, end }
+    }
+
+    /// Create a new window between two hours of the day, i.e. `hours(9, 17)`.
+    #[inline]
+    pub const fn hours(start: u8, end: u8) -> Self {
+        Self::new(start as u32 * 3600, end as u32 * 3600)
+    }
+
+    /// Check whether the second of the day falls within this window.
+    #[inline]
+    pub const fn contains(&self, second: u32) -> bool {
+        if self.start <= self.end {
+            second >= self.start && second < self.end
+        } else {
+            second >= self.start || second < self.end
+        }
+    }
+}
+
+/// A weekly schedule of days and time windows.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schedule {
+    days: Days,
+    windows: Vec<TimeWindow>,
+    /// The offset of local time from UTC, in seconds.
+    offset: i32,
+}
+
+#[allow(dead_code)]
+impl Schedule {
+    /// Create a new Schedule for the given days without any time windows.
+    ///
+    /// A schedule without windows applies for the whole day.
+    #[inline]
+    pub const fn new(days: Days) -> Self {
+        Self {
+            days,
+            windows: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    /// Weekdays from 9 to 17.
+    pub fn business_hours() -> Self {
+        Self::new(Days::WEEKDAYS).window(TimeWindow::hours(9, 17))
+    }
+
+    /// Add a time window to this Schedule.
+    #[inline]
+    pub fn window(mut self, window: TimeWindow) -> Self {
+        self.windows.push(window);
+        self
+    }
+
+    /// Set the offset of local time from UTC in seconds.
+    #[inline]
+    pub const fn with_offset(mut self, offset: i32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    #[inline]
+    pub const fn days(&self) -> Days {
+        self.days
+    }
+
+    #[inline]
+    pub fn windows(&self) -> &[TimeWindow] {
+        &self.windows
+    }
+
+    /// Check whether this Schedule allows access at `now`.
+    pub fn allows(&self, now: Timestamp) -> bool {
+        self.allows_except(now, &HolidayCalendar::new())
+    }
+
+    /// Check whether this Schedule allows access at `now`, which it never does on a
+    /// holiday of `holidays` in its local time.
+    pub fn allows_except(&self, now: Timestamp, holidays: &HolidayCalendar) -> bool {
+        let local = now.saturating_add_signed(self.offset as i64);
+        if !self.days.contains(Days::of(local)) || holidays.contains(local) {
+            return false;
+        }
+
+        let second = (local % SECONDS_PER_DAY) as u32;
+        self.windows.is_empty() || self.windows.iter().any(|w| w.contains(second))
+    }
+}
+
+/// A calendar date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+#[allow(dead_code)]
+impl Date {
+    /// Create a new Date. Returns `None` if the day doesn't exist, i.e. February 30th.
+    pub const fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        if month == 0 || month > 12 || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Self { year, month, day })
+    }
+
+    /// The date `timestamp` falls on.
+    pub const fn of(timestamp: Timestamp) -> Self {
+        // Howard Hinnant's civil_from_days, shifted to years starting in March.
+        let days = timestamp / SECONDS_PER_DAY + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days % 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted + 2) / 5 + 1) as u8;
+        let month = if shifted < 10 {
+            shifted + 3
+        } else {
+            shifted - 9
+        } as u8;
+        let year = year_of_era + era * 400 + (month <= 2) as u64;
+        Self {
+            year: year as u16,
+            month,
+            day,
+        }
+    }
+
+    #[inline]
+    pub const fn year(&self) -> u16 {
+        self.year
+    }
+
+    #[inline]
+    pub const fn month(&self) -> u8 {
+        self.month
+    }
+
+    #[inline]
+    pub const fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+const fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// A day on which schedules don't apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Holiday {
+    /// A single date, i.e. `2026-04-05`.
+    Date(Date),
+    /// The same day every year, i.e. `12-25`.
+    Yearly { month: u8, day: u8 },
+}
+
+#[allow(dead_code)]
+impl Holiday {
+    /// Check whether this Holiday falls on `date`.
+    #[inline]
+    pub fn falls_on(&self, date: Date) -> bool {
+        match *self {
+            Self::Date(holiday) => holiday == date,
+            Self::Yearly { month, day } => date.month == month && date.day == day,
+        }
+    }
+}
+
+impl fmt::Display for Holiday {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Date(date) => write!(f, "{:04}-{:02}-{:02}", date.year, date.month, date.day),
+            Self::Yearly { month, day } => write!(f, "{:02}-{:02}", month, day),
+        }
+    }
+}
+
+impl FromStr for Holiday {
+    type Err = HolidayError;
+
+    /// Parse a `YYYY-MM-DD` date or a yearly `MM-DD` one.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || HolidayError::InvalidDate(s.to_string());
+        let mut parts = s.trim().split('-').map(|part| part.parse::<u16>().ok());
+        let holiday = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Some(year)), Some(Some(month)), Some(Some(day)), None) => {
+                let month = u8::try_from(month).map_err(|_| invalid())?;
+                let day = u8::try_from(day).map_err(|_| invalid())?;
+                Self::Date(Date::new(year, month, day).ok_or_else(invalid)?)
+            }
+            (Some(Some(month)), Some(Some(day)), None, None) => {
+                let month = u8::try_from(month).map_err(|_| invalid())?;
+                let day = u8::try_from(day).map_err(|_| invalid())?;
+                // February 29th only exists on leap years, 2000 is one.
+                Date::new(2000, month, day).ok_or_else(invalid)?;
+                Self::Yearly { month, day }
+            }
+            _ => return Err(invalid()),
+        };
+        Ok(holiday)
+    }
+}
+
+impl Serialize for Holiday {
+    /// The string [`fmt::Display`] shows.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Holiday {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = Holiday;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a YYYY-MM-DD or yearly MM-DD date")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Holiday, E> {
+                s.parse()
+                    .map_err(|_| E::invalid_value(de::Unexpected::Str(s), &self))
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+/// The holidays on which business-hours schedules don't apply.
+///
+/// Loaded from config as a list of dates, or one date per line with [`FromStr`]:
+///
+/// ```text
+/// # New year's day, every year.
+/// 01-01
+/// 2026-04-05
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct HolidayCalendar {
+    holidays: BTreeSet<Holiday>,
+}
+
+#[allow(dead_code)]
+impl HolidayCalendar {
+    /// Create a new HolidayCalendar without any holidays.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            holidays: BTreeSet::new(),
+        }
+    }
+
+    /// Add a holiday to this calendar. Returns `false` if it was already in it.
+    #[inline]
+    pub fn add(&mut self, holiday: Holiday) -> bool {
+        self.holidays.insert(holiday)
+    }
+
+    #[inline]
+    pub fn remove(&mut self, holiday: Holiday) -> bool {
+        self.holidays.remove(&holiday)
+    }
+
+    #[inline]
+    pub fn holidays(&self) -> impl Iterator<Item = Holiday> + '_ {
+        self.holidays.iter().copied()
+    }
+
+    /// Check whether `timestamp` falls on a holiday of this calendar.
+    pub fn contains(&self, timestamp: Timestamp) -> bool {
+        if self.holidays.is_empty() {
+            return false;
+        }
+        let date = Date::of(timestamp);
+        self.holidays.iter().any(|holiday| holiday.falls_on(date))
+    }
+}
+
+impl FromIterator<Holiday> for HolidayCalendar {
+    fn from_iter<I: IntoIterator<Item = Holiday>>(iter: I) -> Self {
+        Self {
+            holidays: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl FromStr for HolidayCalendar {
+    type Err = HolidayError;
+
+    /// Parse one holiday per line, skipping blank lines and `#` comments.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            .filter(|line| !line.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
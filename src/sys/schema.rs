@@ -0,0 +1,111 @@
+//! Versioning of serialized cards, so payloads written by older firmware are
+//! upgraded as they're read instead of failing to decode.
+//!
+//! Migrations rewrite JSON payloads, postcard payloads can't be upgraded.
+#[cfg(feature = "json")]
+use alloc::{collections::btree_map::BTreeMap, string::String, string::ToString};
+#[cfg(feature = "json")]
+use serde_json::{Map, Value};
+
+#[cfg(feature = "json")]
+use crate::Uid;
+
+/// The version of the layout a Card was serialized with.
+pub type SchemaVersion = u16;
+
+/// The schema version cards are serialized with.
+pub const CURRENT: SchemaVersion = 2;
+
+/// The version of payloads written before they were versioned, which carry no `schema` field.
+#[cfg(feature = "json")]
+pub const LEGACY: SchemaVersion = 1;
+
+/// Upgrades the fields of a payload from one version to the next.
+#[cfg(feature = "json")]
+pub type Migration = fn(&mut Map<String, Value>);
+
+/// The migrations this crate ships with, by the version they upgrade from.
+#[cfg(feature = "json")]
+const BUILTIN: &[(SchemaVersion, Migration)] = &[(LEGACY, numeric_ids)];
+
+/// Legacy cards were identified by a numeric id rather than their tag's UID.
+#[cfg(feature = "json")]
+fn numeric_ids(fields: &mut Map<String, Value>) {
+    let id = fields
+        .get("id")
+        .and_then(Value::as_u64)
+        .and_then(|id| u16::try_from(id).ok());
+    if let Some(id) = id {
+        let _ = fields.insert("id".into(), Uid::from_u16(id).to_string().into());
+    }
+}
+
+#[inline]
+pub(crate) const fn current() -> SchemaVersion {
+    CURRENT
+}
+
+/// The migrations applied to payloads of older schema versions.
+///
+/// Migrations registered here take precedence over the ones this crate ships with.
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Default)]
+pub struct Migrations {
+    steps: BTreeMap<SchemaVersion, Migration>,
+}
+
+#[cfg(feature = "json")]
+#[allow(dead_code)]
+impl Migrations {
+    /// Create a new `Migrations` with only the builtin migrations.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            steps: BTreeMap::new(),
+        }
+    }
+
+    /// Register the migration upgrading payloads from version `from` to the next one.
+    pub fn register(&mut self, from: SchemaVersion, migration: Migration) -> Option<Migration> {
+        self.steps.insert(from, migration)
+    }
+
+    fn step(&self, from: SchemaVersion) -> Option<Migration> {
+        self.steps.get(&from).copied().or_else(|| {
+            BUILTIN
+                .iter()
+                .find(|(version, _)| *version == from)
+                .map(|(_, migration)| *migration)
+        })
+    }
+
+    /// Upgrade a payload to the [`CURRENT`] schema, returning the version it was written with.
+    ///
+    /// Fails with the version the payload is stuck at, if it's newer than [`CURRENT`]
+    /// or no migration upgrades it further.
+    pub fn upgrade(&self, payload: &mut Value) -> Result<SchemaVersion, SchemaVersion> {
+        let Some(fields) = payload.as_object_mut() else {
+            // Not a card at all, leave it for deserialization to reject.
+            return Ok(CURRENT);
+        };
+
+        let written = match fields.get("schema") {
+            None => LEGACY,
+            Some(version) => version
+                .as_u64()
+                .and_then(|version| SchemaVersion::try_from(version).ok())
+                .ok_or(SchemaVersion::MAX)?,
+        };
+        if written > CURRENT {
+            return Err(written);
+        }
+
+        let mut version = written;
+        while version < CURRENT {
+            self.step(version).ok_or(version)?(fields);
+            version += 1;
+        }
+        let _ = fields.insert("schema".into(), CURRENT.into());
+        Ok(written)
+    }
+}
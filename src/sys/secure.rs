@@ -0,0 +1,104 @@
+//! Secure elements keeping keys out of reach of the firmware, i.e. an ATECC608.
+use alloc::collections::btree_map::BTreeMap;
+
+use crate::{
+    challenge::{Authenticator, Mac, Nonce},
+    keys::{derivation, KeyVersion, SecretKey},
+    Card,
+};
+
+/// The index of a key slot within a secure element.
+pub type Slot = u8;
+
+/// A secure element storing keys in slots and computing with them internally.
+///
+/// MACs are HMAC-SHA256 truncated to [`MAC_SIZE`](crate::challenge::MAC_SIZE) bytes,
+/// the same as [`HmacSha256`](crate::keys::HmacSha256) computes in software.
+#[allow(unused)]
+pub trait SecureElement {
+    type Error;
+
+    /// Write a key into a slot, replacing the one it held.
+    fn store_key(&mut self, slot: Slot, key: &SecretKey) -> Result<(), Self::Error>;
+
+    /// MAC `data` with the key in a slot.
+    fn sign(&mut self, slot: Slot, data: &[u8]) -> Result<Mac, Self::Error>;
+
+    /// Check `mac` was computed over `data` with the key in a slot.
+    fn verify(&mut self, slot: Slot, data: &[u8], mac: &Mac) -> Result<bool, Self::Error>;
+
+    /// Fill `buf` from the element's random number generator.
+    fn random(&mut self, buf: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+/// An [`Authenticator`] whose master keys never leave a [`SecureElement`].
+///
+/// Card keys are derived inside the element, like [`KeyStore::derive`](crate::keys::KeyStore::derive),
+/// and loaded into a scratch slot to MAC with.
+#[derive(Debug)]
+pub struct ElementAuthenticator<E> {
+    element: E,
+    masters: BTreeMap<KeyVersion, Slot>,
+    scratch: Slot,
+}
+
+#[allow(dead_code)]
+impl<E: SecureElement> ElementAuthenticator<E> {
+    /// Create a new `ElementAuthenticator` deriving card keys into the `scratch` slot.
+    #[inline]
+    pub const fn new(element: E, scratch: Slot) -> Self {
+        Self {
+            element,
+            masters: BTreeMap::new(),
+            scratch,
+        }
+    }
+
+    /// Use the master key in `slot` for cards of a key version.
+    #[must_use]
+    pub fn with_master(mut self, version: KeyVersion, slot: Slot) -> Self {
+        let _ = self.masters.insert(version, slot);
+        self
+    }
+
+    /// Stop accepting cards of a key version.
+    pub fn retire(&mut self, version: KeyVersion) -> Option<Slot> {
+        self.masters.remove(&version)
+    }
+
+    /// Release the element.
+    #[inline]
+    pub fn into_inner(self) -> E {
+        self.element
+    }
+
+    /// Derive the key of a card into the scratch slot.
+    fn load(&mut self, card: &Card) -> Option<()> {
+        let master = *self.masters.get(&card.key_version())?;
+        let key = self
+            .element
+            .sign(master, &derivation(card.id(), card.key_version()))
+            .ok()?;
+        self.element
+            .store_key(self.scratch, &SecretKey::new(key))
+            .ok()
+    }
+}
+
+impl<E: SecureElement> Authenticator for ElementAuthenticator<E> {
+    fn nonce(&mut self) -> Option<Nonce> {
+        let mut nonce = Nonce::default();
+        self.element.random(&mut nonce).ok()?;
+        Some(nonce)
+    }
+
+    fn mac(&mut self, card: &Card, data: &[u8]) -> Option<Mac> {
+        self.load(card)?;
+        self.element.sign(self.scratch, data).ok()
+    }
+
+    fn verify(&mut self, card: &Card, data: &[u8], mac: &Mac) -> bool {
+        self.load(card).is_some()
+            && matches!(self.element.verify(self.scratch, data, mac), Ok(true))
+    }
+}
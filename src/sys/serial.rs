@@ -0,0 +1,192 @@
+//! A [`Kernel`] for reader modules attached over a UART.
+//!
+//! Requests and responses use the framing in [`crate::frame`], a response carries
+//! its request's command with [`RESPONSE`] set and a status byte before its payload.
+use alloc::{collections::btree_map::BTreeMap, vec::Vec};
+
+use embedded_io::{Read, Write};
+
+use crate::{
+    errors::KernelError,
+    firmware::FirmwareVersion,
+    frame::{Frame, FrameDecoder, RESPONSE},
+    Card, Kernel, Technology, Uid,
+};
+
+/// The commands understood by the reader module.
+pub mod command {
+    /// Poll the field, answered with the UID and stored payload of the present tag.
+    pub const SENSE: u8 = 0x01;
+    /// Read the payload stored on a tag.
+    pub const READ: u8 = 0x02;
+    /// Write a payload to a tag.
+    pub const WRITE: u8 = 0x03;
+    /// Exchange a raw APDU with a tag.
+    pub const TRANSCEIVE: u8 = 0x04;
+    /// Switch the polled card technology.
+    pub const TECHNOLOGY: u8 = 0x05;
+    /// Report the module's firmware version.
+    pub const VERSION: u8 = 0x06;
+}
+
+/// The status codes the reader module answers with.
+pub mod status {
+    pub const OK: u8 = 0x00;
+    pub const NO_CARD: u8 = 0x01;
+    pub const TIMEOUT: u8 = 0x02;
+    pub const CRC: u8 = 0x03;
+    pub const AUTH: u8 = 0x04;
+    pub const UNSUPPORTED: u8 = 0x05;
+}
+
+/// Prefix a payload with a UID's length and bytes.
+fn with_uid(id: Uid, data: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(1 + id.len() + data.len());
+    payload.push(id.len() as u8);
+    payload.extend_from_slice(id.as_bytes());
+    payload.extend_from_slice(data);
+    payload
+}
+
+/// A reader module attached over a UART.
+///
+/// Cards are cached as the module reports them, [`Kernel::read`] answers from the cache
+/// while [`Kernel::read_into`] always asks the module.
+pub struct SerialKernel<P> {
+    port: P,
+    decoder: FrameDecoder,
+    cards: BTreeMap<Uid, Card>,
+}
+
+#[allow(dead_code)]
+impl<P> SerialKernel<P>
+where
+    P: Read + Write,
+{
+    #[inline]
+    pub const fn new(port: P) -> Self {
+        Self {
+            port,
+            decoder: FrameDecoder::new(),
+            cards: BTreeMap::new(),
+        }
+    }
+
+    /// Give the port back.
+    #[inline]
+    pub fn release(self) -> P {
+        self.port
+    }
+
+    /// Send a request and wait for its response's payload.
+    fn request(&mut self, command: u8, payload: &[u8]) -> Result<Vec<u8>, KernelError> {
+        let transport = |_| KernelError::Transport { status: 0 };
+        let frame = Frame::new(command, payload)
+            .encode()
+            .map_err(|_| KernelError::Transport { status: 0 })?;
+        self.port.write_all(&frame).map_err(transport)?;
+        self.port.flush().map_err(transport)?;
+
+        let mut buf = [0; 64];
+        loop {
+            while let Some(frame) = self.decoder.next_frame() {
+                if frame.command != command | RESPONSE {
+                    continue;
+                }
+                return match frame.payload.split_first() {
+                    Some((&status::OK, data)) => Ok(data.to_vec()),
+                    Some((&status::NO_CARD, _)) => Err(KernelError::NoCard),
+                    Some((&status::TIMEOUT, _)) => Err(KernelError::Timeout),
+                    Some((&status::CRC, _)) => Err(KernelError::Crc),
+                    Some((&status::AUTH, _)) => Err(KernelError::Auth),
+                    Some((&status::UNSUPPORTED, _)) => Err(KernelError::Unsupported("command")),
+                    Some((&other, _)) => Err(KernelError::Transport {
+                        status: other as u16,
+                    }),
+                    None => Err(KernelError::Transport { status: 0 }),
+                };
+            }
+
+            match self.port.read(&mut buf).map_err(transport)? {
+                0 => return Err(KernelError::Timeout),
+                read => self.decoder.push(&buf[..read]),
+            }
+        }
+    }
+
+    /// Decode and cache the card payload read from a tag.
+    fn cache(&mut self, id: Uid, data: &[u8]) -> Result<(), KernelError> {
+        let card = Card::from_bytes(data).map_err(|_| KernelError::Read { status: 0 })?;
+        if card.id != id {
+            return Err(KernelError::Read { status: 0 });
+        }
+        let _ = self.cards.insert(id, card);
+        Ok(())
+    }
+}
+
+impl<P> Kernel for SerialKernel<P>
+where
+    P: Read + Write + Send + Sync + 'static,
+{
+    fn read(&self, card: Uid) -> Result<&Card, KernelError> {
+        self.cards.get(&card).ok_or(KernelError::NoCard)
+    }
+
+    fn read_into(&mut self, card: Uid, buf: &mut [u8]) -> Result<usize, KernelError> {
+        let data = self.request(command::READ, &with_uid(card, &[]))?;
+        self.cache(card, &data)?;
+        buf.get_mut(..data.len())
+            .ok_or(KernelError::Read { status: 0 })?
+            .copy_from_slice(&data);
+        Ok(data.len())
+    }
+
+    fn write(&mut self, card: &Card, data: &[u8]) -> Result<(), KernelError> {
+        self.request(command::WRITE, &with_uid(card.id, data))?;
+        self.cards.insert(card.id, *card);
+        Ok(())
+    }
+
+    fn set_technology(&mut self, technology: Technology) -> Result<(), KernelError> {
+        let technology = match technology {
+            Technology::Iso14443A => 0,
+            Technology::FeliCa => 1,
+        };
+        self.request(command::TECHNOLOGY, &[technology]).map(|_| ())
+    }
+
+    fn transceive(&mut self, apdu: &[u8]) -> Result<Vec<u8>, KernelError> {
+        self.request(command::TRANSCEIVE, apdu)
+    }
+
+    fn firmware_version(&mut self) -> Result<FirmwareVersion, KernelError> {
+        match self.request(command::VERSION, &[])?[..] {
+            [major, minor, patch, ..] => Ok(FirmwareVersion {
+                major,
+                minor,
+                patch,
+            }),
+            _ => Err(KernelError::Transport { status: 0 }),
+        }
+    }
+
+    fn sense(&mut self) -> Result<Option<Uid>, KernelError> {
+        let data = match self.request(command::SENSE, &[]) {
+            Ok(data) => data,
+            Err(KernelError::NoCard) => return Ok(None),
+            Err(why) => return Err(why),
+        };
+
+        let (&len, rest) = data
+            .split_first()
+            .ok_or(KernelError::Transport { status: 0 })?;
+        let (id, payload) = rest.split_at_checked(len as usize).unwrap_or((rest, &[]));
+        let id = Uid::new(id).ok_or(KernelError::Read { status: 0 })?;
+        // Blank or foreign tags don't carry a card, they're still reported as present.
+        if !payload.is_empty() {
+            let _ = self.cache(id, payload);
+        }
+        Ok(Some(id))
+    }
+}
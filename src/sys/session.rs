@@ -0,0 +1,184 @@
+//! Card sessions, the steps of an interaction with a tag in a reader's field in order.
+//!
+//! A [`Session`] moves through its [`SessionState`]s one step at a time:
+//!
+//! ```text
+//! Idle → Selected → Authenticated → ReadWrite → Done
+//! ```
+//!
+//! A step taken out of order, i.e. a write before the tag was authenticated, fails with
+//! [`SessionError::OutOfOrder`] and leaves the session as it was. A step that fails ends
+//! the session, a new one has to be started to retry.
+use crate::{
+    errors::{AccessError, KernelError, SessionError},
+    logging::kernel_error,
+    Card, Kernel, NfcService, ReaderId, Uid,
+};
+
+/// The state of a [`Session`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SessionState {
+    /// No tag is selected yet.
+    Idle,
+    /// A tag in the reader's field was selected.
+    Selected,
+    /// The tag passed the challenge-response handshake, if the service has an authenticator.
+    Authenticated,
+    /// The tag's card was read and authorized, it may be written back.
+    ReadWrite,
+    /// The session ended, either finished or after a failed step.
+    Done,
+}
+
+/// An interaction with a tag at a reader, see [`NfcService::session`].
+///
+/// The reader's transaction ends when the session is dropped.
+#[derive(Debug)]
+pub struct Session<'a, K>
+where
+    K: Kernel,
+{
+    nfc: &'a mut NfcService<K>,
+    reader: ReaderId,
+    state: SessionState,
+    card: Option<Uid>,
+}
+
+#[allow(dead_code)]
+impl<'a, K> Session<'a, K>
+where
+    K: Kernel,
+{
+    #[inline]
+    pub const fn state(&self) -> SessionState {
+        self.state
+    }
+
+    #[inline]
+    pub const fn reader(&self) -> ReaderId {
+        self.reader
+    }
+
+    /// The selected tag, `None` before [`Session::select`] and once the session ended.
+    #[inline]
+    pub const fn card(&self) -> Option<Uid> {
+        self.card
+    }
+
+    /// Check that a step is taken in one of the states it's allowed in.
+    fn expect(
+        &self,
+        operation: &'static str,
+        allowed: &[SessionState],
+    ) -> Result<(), SessionError> {
+        if allowed.contains(&self.state) {
+            return Ok(());
+        }
+        Err(SessionError::OutOfOrder {
+            state: self.state,
+            operation,
+        })
+    }
+
+    /// Check that a step on the selected tag is taken in one of the states it's allowed in.
+    fn expect_selected(
+        &self,
+        operation: &'static str,
+        allowed: &[SessionState],
+    ) -> Result<Uid, SessionError> {
+        self.expect(operation, allowed)?;
+        self.card.ok_or(SessionError::OutOfOrder {
+            state: self.state,
+            operation,
+        })
+    }
+
+    /// Move to `next` if a step succeeded, end the session if it failed.
+    fn step<T>(
+        &mut self,
+        next: SessionState,
+        result: Result<T, AccessError>,
+    ) -> Result<T, SessionError> {
+        match result {
+            Ok(..) => self.state = next,
+            Err(..) => {
+                let _ = self.finish();
+            }
+        }
+        result.map_err(SessionError::Access)
+    }
+
+    /// Select the tag in the reader's field, beginning its transaction.
+    pub fn select(&mut self) -> Result<Uid, SessionError> {
+        self.expect("select", &[SessionState::Idle])?;
+        let reader = self.reader;
+        let selected = self
+            .nfc
+            .sense(reader)
+            .and_then(|uid| uid.ok_or_else(|| kernel_error(reader, KernelError::NoCard)));
+        self.card = selected.ok();
+        self.step(SessionState::Selected, selected)
+    }
+
+    /// Run the challenge-response handshake with the selected tag,
+    /// see [`NfcService::authenticate`].
+    pub fn authenticate(&mut self) -> Result<(), SessionError> {
+        let card = self.expect_selected("authenticate", &[SessionState::Selected])?;
+        let result = self.nfc.authenticate(self.reader, card);
+        self.step(SessionState::Authenticated, result)
+    }
+
+    /// Read the authenticated tag and authorize its card, see [`NfcService::read`].
+    ///
+    /// The tag may be read again once it was.
+    pub fn read(&mut self) -> Result<Card, SessionError> {
+        let card = self.expect_selected(
+            "read",
+            &[SessionState::Authenticated, SessionState::ReadWrite],
+        )?;
+        let result = self.nfc.read_with(self.reader, card, false);
+        self.step(SessionState::ReadWrite, result.map_err(AccessError::from))
+    }
+
+    /// Write the registered card back to the tag once it was read, see [`NfcService::write`].
+    pub fn write(&mut self) -> Result<(), SessionError> {
+        let card = self.expect_selected("write", &[SessionState::ReadWrite])?;
+        let result = self.nfc.write(self.reader, card);
+        self.step(SessionState::ReadWrite, result.map_err(AccessError::from))
+    }
+
+    /// End the session, returning the state it ended in.
+    pub fn finish(&mut self) -> SessionState {
+        if self.card.take().is_some() {
+            let _ = self.nfc.end_transaction(self.reader);
+        }
+        core::mem::replace(&mut self.state, SessionState::Done)
+    }
+}
+
+impl<K> Drop for Session<'_, K>
+where
+    K: Kernel,
+{
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Start a session with the next tag presented to a reader.
+    #[inline]
+    pub fn session(&mut self, reader: ReaderId) -> Session<'_, K> {
+        Session {
+            nfc: self,
+            reader,
+            state: SessionState::Idle,
+            card: None,
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Sharing a service between interrupt handlers and the main loop on `no_std` targets.
+//!
+//! A [`SharedNfcService`] can be placed in a `static`, every access to the service runs in
+//! a critical section, so a card detected in an IRQ handler can be authorized against the
+//! same registry the main loop enrolls cards in.
+//!
+//! ```ignore
+//! static NFC: SharedNfcService<Pn532> = SharedNfcService::new(NfcService::empty());
+//!
+//! #[interrupt]
+//! fn EXTI0() {
+//!     NFC.with(|nfc| nfc.read(0, uid));
+//! }
+//! ```
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+
+use crate::{
+    errors::{AccessError, TagError},
+    transaction::Traced,
+    Card, Kernel, NfcService, ReaderId, Uid,
+};
+
+/// An [`NfcService`] behind a critical-section mutex.
+pub struct SharedNfcService<K>
+where
+    K: Kernel,
+{
+    inner: Mutex<RefCell<NfcService<K>>>,
+}
+
+#[allow(dead_code)]
+impl<K> SharedNfcService<K>
+where
+    K: Kernel,
+{
+    /// Share a service, i.e. one created with [`NfcService::empty`] in a `static`.
+    #[inline]
+    pub const fn new(nfc: NfcService<K>) -> Self {
+        Self {
+            inner: Mutex::new(RefCell::new(nfc)),
+        }
+    }
+
+    /// Run a closure on the service within a critical section.
+    ///
+    /// Interrupts are disabled for the duration of the closure, so keep it short.
+    ///
+    /// # Panics
+    ///
+    /// If called again from within the closure.
+    pub fn with<R>(&self, f: impl FnOnce(&mut NfcService<K>) -> R) -> R {
+        critical_section::with(|cs| f(&mut self.inner.borrow_ref_mut(cs)))
+    }
+
+    /// Authorize a card payload that was presented to a reader, see [`NfcService::authorize`].
+    #[inline]
+    pub fn authorize(&self, reader: ReaderId, payload: &Card) -> Result<Card, Traced<AccessError>> {
+        self.with(|nfc| nfc.authorize(reader, payload))
+    }
+
+    /// Read a card through a reader and authorize it, see [`NfcService::read`].
+    #[inline]
+    pub fn read(&self, reader: ReaderId, card_id: Uid) -> Result<Card, Traced<TagError>> {
+        self.with(|nfc| nfc.read(reader, card_id))
+    }
+
+    /// Check whether a card is registered.
+    #[inline]
+    pub fn contains(&self, card_id: &Uid) -> bool {
+        self.with(|nfc| nfc.contains(card_id))
+    }
+
+    /// An exclusive reference to the service, no critical section is needed to get one.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut NfcService<K> {
+        self.inner.get_mut().get_mut()
+    }
+
+    /// Take the service back.
+    #[inline]
+    pub fn into_inner(self) -> NfcService<K> {
+        self.inner.into_inner().into_inner()
+    }
+}
@@ -0,0 +1,156 @@
+//! Managing several sites from one service, each with its own cards, policy and audit log.
+use alloc::{collections::btree_map::BTreeMap, vec::Vec};
+
+use crate::{
+    door::DoorId, errors::AccessError, events::NfcEvent, Card, Kernel, NfcService, ReaderId, Uid,
+};
+
+/// The identifier of a site, i.e. a building or a tenant.
+pub type SiteId = u16;
+
+/// An NFC service managing multiple sites with isolated id spaces.
+///
+/// Every site is a separate [`NfcService`] owning its readers, so cards, revocations,
+/// policies and audit logs never leak between sites. Operations on a reader are routed
+/// to the site it's attached to.
+pub struct MultiSite<K>
+where
+    K: Kernel,
+{
+    sites: BTreeMap<SiteId, NfcService<K>>,
+    readers: BTreeMap<ReaderId, SiteId>,
+}
+
+impl<K> Default for MultiSite<K>
+where
+    K: Kernel,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
+impl<K> MultiSite<K>
+where
+    K: Kernel,
+{
+    /// Create a new `MultiSite` without any sites.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            sites: BTreeMap::new(),
+            readers: BTreeMap::new(),
+        }
+    }
+
+    /// Add a site without any readers, returning it to be set up.
+    ///
+    /// An existing site with the same id is returned as is.
+    pub fn add_site(&mut self, site: SiteId) -> &mut NfcService<K> {
+        self.sites.entry(site).or_insert_with(NfcService::empty)
+    }
+
+    /// Remove a site and detach all of its readers.
+    pub fn remove_site(&mut self, site: SiteId) -> Option<NfcService<K>> {
+        let service = self.sites.remove(&site)?;
+        self.readers.retain(|_, owner| *owner != site);
+        Some(service)
+    }
+
+    #[inline]
+    pub fn site(&self, site: SiteId) -> Option<&NfcService<K>> {
+        self.sites.get(&site)
+    }
+
+    #[inline]
+    pub fn site_mut(&mut self, site: SiteId) -> Option<&mut NfcService<K>> {
+        self.sites.get_mut(&site)
+    }
+
+    /// An iterator over the ids of all sites.
+    #[inline]
+    pub fn sites(&self) -> impl Iterator<Item = SiteId> + '_ {
+        self.sites.keys().copied()
+    }
+
+    /// Attach a reader to a site.
+    ///
+    /// Reader ids are unique across sites, the kernel is given back if the site doesn't
+    /// exist or the reader is attached to another site.
+    pub fn attach(&mut self, site: SiteId, reader: ReaderId, system: K) -> Result<Option<K>, K> {
+        if self
+            .readers
+            .get(&reader)
+            .is_some_and(|owner| *owner != site)
+        {
+            return Err(system);
+        }
+        let Some(service) = self.sites.get_mut(&site) else {
+            return Err(system);
+        };
+
+        let _ = self.readers.insert(reader, site);
+        Ok(service.attach(reader, system))
+    }
+
+    /// Detach a reader from whichever site it's attached to.
+    pub fn detach(&mut self, reader: ReaderId) -> Option<K> {
+        let site = self.readers.remove(&reader)?;
+        self.sites.get_mut(&site)?.detach(reader)
+    }
+
+    /// The site a reader is attached to.
+    #[inline]
+    pub fn site_of(&self, reader: ReaderId) -> Option<SiteId> {
+        self.readers.get(&reader).copied()
+    }
+
+    /// The service of the site a reader is attached to.
+    pub fn reader_site_mut(&mut self, reader: ReaderId) -> Result<&mut NfcService<K>, AccessError> {
+        self.readers
+            .get(&reader)
+            .and_then(|site| self.sites.get_mut(site))
+            .ok_or(AccessError::UnknownReader(reader))
+    }
+
+    /// Poll a reader's RF field once, see [`NfcService::sense`].
+    pub fn sense(&mut self, reader: ReaderId) -> Result<Option<Uid>, AccessError> {
+        self.reader_site_mut(reader)?.sense(reader)
+    }
+
+    /// Authorize a card presented to a reader against the reader's site.
+    pub fn authorize(&mut self, reader: ReaderId, payload: &Card) -> Result<Card, AccessError> {
+        Ok(self.reader_site_mut(reader)?.authorize(reader, payload)?)
+    }
+
+    /// Decide whether a card may open a door of the reader's site.
+    pub fn open(
+        &mut self,
+        reader: ReaderId,
+        payload: &Card,
+        door_id: DoorId,
+    ) -> Result<Card, AccessError> {
+        Ok(self
+            .reader_site_mut(reader)?
+            .open(reader, payload, door_id)?)
+    }
+
+    /// Read a card through a reader and authorize it against the reader's site.
+    pub fn read(&mut self, reader: ReaderId, card_id: Uid) -> Result<Card, AccessError> {
+        Ok(self.reader_site_mut(reader)?.read(reader, card_id)?)
+    }
+
+    /// Take all the events emitted by every site since the last call.
+    pub fn drain_events(&mut self) -> Vec<(SiteId, NfcEvent)> {
+        self.sites
+            .iter_mut()
+            .flat_map(|(site, service)| {
+                service
+                    .drain_events()
+                    .into_iter()
+                    .map(move |event| (*site, event))
+            })
+            .collect()
+    }
+}
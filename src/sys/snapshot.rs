@@ -0,0 +1,117 @@
+//! Lock-free read snapshots of the card registry.
+//!
+//! Access checks on a hot path, i.e. an interrupt handler or another thread, load the
+//! latest published [`Snapshot`] through a [`SnapshotHandle`] without ever waiting on
+//! the thread that owns and updates the service.
+use alloc::{collections::btree_map::BTreeMap, sync::Arc};
+
+use arc_swap::ArcSwap;
+
+use crate::{revocation::RevocationList, Card, Kernel, NfcService, Timestamp, Uid};
+
+/// A consistent, read-only view of the registered and revoked cards.
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    cards: BTreeMap<Uid, Card>,
+    revoked: RevocationList,
+    taken_at: Timestamp,
+}
+
+#[allow(dead_code)]
+impl Snapshot {
+    #[inline]
+    pub fn get(&self, card_id: &Uid) -> Option<&Card> {
+        self.cards.get(card_id)
+    }
+
+    #[inline]
+    pub fn contains(&self, card_id: &Uid) -> bool {
+        self.cards.contains_key(card_id)
+    }
+
+    #[inline]
+    pub fn is_revoked(&self, card_id: Uid) -> bool {
+        self.revoked.is_revoked(card_id)
+    }
+
+    /// An iterator over the cards in ascending id order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Card> + '_ {
+        self.cards.values()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// When this snapshot was published.
+    #[inline]
+    pub const fn taken_at(&self) -> Timestamp {
+        self.taken_at
+    }
+}
+
+/// A cloneable handle loading the latest [`Snapshot`] published by a service.
+#[derive(Debug, Clone)]
+pub struct SnapshotHandle(Arc<ArcSwap<Snapshot>>);
+
+#[allow(dead_code)]
+impl SnapshotHandle {
+    /// The latest published snapshot, this never blocks.
+    #[inline]
+    pub fn load(&self) -> Arc<Snapshot> {
+        self.0.load_full()
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    fn take_snapshot(&self) -> Snapshot {
+        Snapshot {
+            cards: self.cards.clone(),
+            revoked: self.revoked.clone(),
+            taken_at: self.now(),
+        }
+    }
+
+    /// A handle loading the snapshots this service publishes.
+    ///
+    /// The first call publishes the current registry.
+    pub fn snapshots(&mut self) -> SnapshotHandle {
+        if let Some(ref handle) = self.snapshots {
+            return handle.clone();
+        }
+        let handle = SnapshotHandle(Arc::new(ArcSwap::from_pointee(self.take_snapshot())));
+        self.snapshots = Some(handle.clone());
+        handle
+    }
+
+    /// Publish the current registry to every [`SnapshotHandle`].
+    ///
+    /// The service publishes whenever cards are registered, unbound, written, revoked or
+    /// reinstated, their permissions change, or they're imported, restored or synced. The
+    /// uses taken on every access aren't published until the next of those, publish to
+    /// make them visible sooner.
+    pub fn publish(&mut self) {
+        if let Some(SnapshotHandle(ref published)) = self.snapshots {
+            published.store(Arc::new(self.take_snapshot()));
+        }
+    }
+
+    /// The latest published snapshot, or one of the current registry if none was published yet.
+    pub fn snapshot(&self) -> Arc<Snapshot> {
+        match self.snapshots {
+            Some(ref handle) => handle.load(),
+            None => Arc::new(self.take_snapshot()),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! A snapshot of the full state of a service, for cloning a device or restoring one after
+//! it was replaced.
+//!
+//! Unlike an [`export`](crate::export), which is merged into the registry of another
+//! installation, the state replaces the cards, revocations and policy, roles included, of
+//! the service it's imported into. It's laid out the same way, behind its own magic.
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    codec,
+    errors::{ConversionError, ImportError},
+    export::{seal, unseal},
+    policy::AccessPolicy,
+    revocation::RevocationList,
+    Card, Kernel, NfcService,
+};
+
+/// Marks the start of an exported state.
+pub const MAGIC: [u8; 4] = *b"LWST";
+
+/// The version of the state format written by this build.
+pub const VERSION: u16 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct State {
+    cards: Vec<Card>,
+    revocations: RevocationList,
+    policy: AccessPolicy,
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Export the registered cards, the revocation list and the access policy as a single
+    /// versioned blob.
+    pub fn export_state(&self) -> Result<Vec<u8>, ConversionError> {
+        let state = State {
+            cards: self.cards.values().copied().collect(),
+            revocations: self.revoked.clone(),
+            policy: self.policy.clone(),
+        };
+        let body = codec::to_vec(&state).map_err(|_| ConversionError::serialize("State"))?;
+        Ok(seal(MAGIC, VERSION, &body))
+    }
+
+    /// Validate an exported state and replace the state of this service with it.
+    ///
+    /// The readers, subscribers and audit log are kept, as are the lockdowns and emergency
+    /// unlocks of this device. Nothing is changed if the state fails to validate.
+    pub fn import_state(&mut self, bytes: &[u8]) -> Result<(), ImportError> {
+        let body = unseal(MAGIC, VERSION, bytes)?;
+        let state: State = codec::from_slice("State", body).map_err(ImportError::Malformed)?;
+        let roles = state.policy.roles();
+        let unknown = state
+            .cards
+            .iter()
+            .flat_map(|card| card.roles.iter())
+            .find(|role| roles.get(*role).is_none());
+        if let Some(role) = unknown {
+            return Err(ImportError::UnknownRole(role));
+        }
+
+        let _ = self.policy.replace(state.policy);
+        self.revoked = state.revocations;
+        self.cards = state
+            .cards
+            .into_iter()
+            .map(|card| (card.id, card))
+            .collect();
+        self.evict();
+        self.registry_changed();
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+//! Persistent storage for the card registry.
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{audit::AuditLog, codec, journal::JournalEntry, revocation::RevocationList, Card};
+
+#[cfg(feature = "std")]
+mod file;
+#[cfg(feature = "embedded-storage")]
+mod flash;
+
+#[cfg(feature = "std")]
+#[allow(unused_imports)]
+pub use file::FileStore;
+#[cfg(feature = "embedded-storage")]
+#[allow(unused_imports)]
+pub use flash::FlashStore;
+
+/// A named record a [`CardStore`] persists.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Slot {
+    /// The registered cards.
+    Cards,
+    /// The revocation list.
+    Revocations,
+    /// The write in flight, see [`crate::journal`].
+    Journal,
+    /// The audit log.
+    Audit,
+}
+
+#[allow(dead_code)]
+impl Slot {
+    /// All the slots, in the order they're laid out.
+    pub const ALL: [Slot; 4] = [Slot::Cards, Slot::Revocations, Slot::Journal, Slot::Audit];
+
+    /// The position of this slot in [`Slot::ALL`].
+    #[inline]
+    pub const fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// A short name of this slot, i.e. for file names.
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Cards => "cards",
+            Self::Revocations => "revocations",
+            Self::Journal => "journal",
+            Self::Audit => "audit",
+        }
+    }
+}
+
+/// Errors returned by [`CardStore`]s.
+#[allow(dead_code)]
+#[derive(Debug)]
+pub enum StoreError<E> {
+    /// The storage backend failed.
+    Backend(E),
+    /// A record was found but couldn't be decoded.
+    Corrupted(Slot),
+    /// A record doesn't fit in the space the backend reserves for it.
+    Full(Slot),
+}
+
+impl<E: fmt::Debug> fmt::Display for StoreError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Backend(err) => write!(f, "BackendError({:?})", err),
+            Self::Corrupted(slot) => write!(f, "Corrupted(slot: {})", slot.name()),
+            Self::Full(slot) => write!(f, "Full(slot: {})", slot.name()),
+        }
+    }
+}
+
+/// A storage backend the card registry survives power cycles in.
+///
+/// Backends only store opaque records, encoding them is done by the provided methods.
+pub trait CardStore {
+    type Error: fmt::Debug;
+
+    /// Read a record. Returns `None` if it was never written.
+    fn read(&mut self, slot: Slot) -> Result<Option<Vec<u8>>, StoreError<Self::Error>>;
+
+    /// Replace a record.
+    fn write(&mut self, slot: Slot, bytes: &[u8]) -> Result<(), StoreError<Self::Error>>;
+
+    /// Load the registered cards.
+    fn load_cards(&mut self) -> Result<Vec<Card>, StoreError<Self::Error>> {
+        match self.read(Slot::Cards)? {
+            Some(bytes) => {
+                codec::from_slice("Cards", &bytes).map_err(|_| StoreError::Corrupted(Slot::Cards))
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Persist the registered cards.
+    fn save_cards(&mut self, cards: &[Card]) -> Result<(), StoreError<Self::Error>> {
+        // Cards are plain data which always serialize.
+        let bytes = codec::to_vec(cards).unwrap_or_default();
+        self.write(Slot::Cards, &bytes)
+    }
+
+    /// Load the revocation list.
+    fn load_revocations(&mut self) -> Result<RevocationList, StoreError<Self::Error>> {
+        match self.read(Slot::Revocations)? {
+            Some(bytes) => RevocationList::from_bytes(&bytes)
+                .map_err(|_| StoreError::Corrupted(Slot::Revocations)),
+            None => Ok(RevocationList::new()),
+        }
+    }
+
+    /// Persist the revocation list.
+    fn save_revocations(
+        &mut self,
+        revoked: &RevocationList,
+    ) -> Result<(), StoreError<Self::Error>> {
+        self.write(Slot::Revocations, &revoked.as_bytes())
+    }
+
+    /// Load the write in flight, `None` if the last one was committed.
+    fn load_journal(&mut self) -> Result<Option<JournalEntry>, StoreError<Self::Error>> {
+        match self.read(Slot::Journal)? {
+            Some(bytes) if !bytes.is_empty() => codec::from_slice("JournalEntry", &bytes)
+                .map(Some)
+                .map_err(|_| StoreError::Corrupted(Slot::Journal)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Record the intent to write a card.
+    fn save_journal(&mut self, entry: &JournalEntry) -> Result<(), StoreError<Self::Error>> {
+        // Entries are plain data which always serialize.
+        let bytes = codec::to_vec(entry).unwrap_or_default();
+        self.write(Slot::Journal, &bytes)
+    }
+
+    /// Persist the audit log, replacing the entries flushed before.
+    fn save_audit(&mut self, audit: &AuditLog) -> Result<(), StoreError<Self::Error>> {
+        self.write(Slot::Audit, &audit.export())
+    }
+
+    /// Commit the write in flight, an empty record marks the journal clear.
+    fn clear_journal(&mut self) -> Result<(), StoreError<Self::Error>> {
+        self.write(Slot::Journal, &[])
+    }
+}
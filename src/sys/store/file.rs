@@ -0,0 +1,48 @@
+extern crate std;
+
+use alloc::vec::Vec;
+use std::{fs, io, path::PathBuf};
+
+use super::{CardStore, Slot, StoreError};
+
+/// A [`CardStore`] keeping every record in its own file within a directory.
+///
+/// Records are written to a temporary file first and renamed over the old one,
+/// so a crash mid-write never leaves a half written record behind.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+#[allow(dead_code)]
+impl FileStore {
+    /// Create a new FileStore in a directory, creating it if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, slot: Slot, ext: &str) -> PathBuf {
+        self.dir.join(slot.name()).with_extension(ext)
+    }
+}
+
+impl CardStore for FileStore {
+    type Error = io::Error;
+
+    fn read(&mut self, slot: Slot) -> Result<Option<Vec<u8>>, StoreError<Self::Error>> {
+        match fs::read(self.path(slot, "json")) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(StoreError::Backend(err)),
+        }
+    }
+
+    fn write(&mut self, slot: Slot, bytes: &[u8]) -> Result<(), StoreError<Self::Error>> {
+        let tmp = self.path(slot, "tmp");
+        fs::write(&tmp, bytes)
+            .and_then(|_| fs::rename(&tmp, self.path(slot, "json")))
+            .map_err(StoreError::Backend)
+    }
+}
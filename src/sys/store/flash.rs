@@ -0,0 +1,87 @@
+use alloc::{vec, vec::Vec};
+use embedded_storage::Storage;
+
+use super::{CardStore, Slot, StoreError};
+
+/// Marks a region holding a record, erased flash reads as all ones.
+const MAGIC: [u8; 2] = *b"LW";
+/// The magic followed by the record length.
+const HEADER: usize = MAGIC.len() + 4;
+
+/// A [`CardStore`] on flash or EEPROM through [`embedded_storage::Storage`].
+///
+/// Every slot is given a fixed size region, laid out one after the other from
+/// `base`. A region starts with a small header followed by the record itself.
+#[derive(Debug)]
+pub struct FlashStore<S> {
+    storage: S,
+    base: u32,
+    region: u32,
+}
+
+#[allow(dead_code)]
+impl<S: Storage> FlashStore<S> {
+    /// Create a new FlashStore giving every slot `region` bytes starting at `base`.
+    #[inline]
+    pub const fn new(storage: S, base: u32, region: u32) -> Self {
+        Self {
+            storage,
+            base,
+            region,
+        }
+    }
+
+    /// Give the storage back.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.storage
+    }
+
+    #[inline]
+    fn offset(&self, slot: Slot) -> u32 {
+        self.base + self.region * slot.index() as u32
+    }
+}
+
+impl<S: Storage> CardStore for FlashStore<S>
+where
+    S::Error: core::fmt::Debug,
+{
+    type Error = S::Error;
+
+    fn read(&mut self, slot: Slot) -> Result<Option<Vec<u8>>, StoreError<Self::Error>> {
+        let offset = self.offset(slot);
+        let mut header = [0; HEADER];
+        self.storage
+            .read(offset, &mut header)
+            .map_err(StoreError::Backend)?;
+        if header[..MAGIC.len()] != MAGIC {
+            return Ok(None);
+        }
+
+        let len = u32::from_le_bytes([header[2], header[3], header[4], header[5]]);
+        if len > self.region.saturating_sub(HEADER as u32) {
+            return Err(StoreError::Corrupted(slot));
+        }
+
+        let mut bytes = vec![0; len as usize];
+        self.storage
+            .read(offset + HEADER as u32, &mut bytes)
+            .map_err(StoreError::Backend)?;
+        Ok(Some(bytes))
+    }
+
+    fn write(&mut self, slot: Slot, bytes: &[u8]) -> Result<(), StoreError<Self::Error>> {
+        if bytes.len() + HEADER > self.region as usize {
+            return Err(StoreError::Full(slot));
+        }
+
+        let mut record = Vec::with_capacity(HEADER + bytes.len());
+        record.extend_from_slice(&MAGIC);
+        record.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        record.extend_from_slice(bytes);
+        self.storage
+            .write(self.offset(slot), &record)
+            .map_err(StoreError::Backend)
+    }
+}
@@ -0,0 +1,174 @@
+//! Keeping an offline copy of a central server's registry.
+//!
+//! Readers keep authorizing against their local registry while the network is down,
+//! a [`SyncClient`] catches up with the server whenever it's reachable again.
+use alloc::{collections::btree_map::BTreeMap, vec::Vec};
+use core::fmt;
+
+use crate::{
+    audit::AuditEntry, errors::SyncError, events::NfcEvent, revocation::RevocationList, Card,
+    Kernel, NfcService, Timestamp, Uid,
+};
+
+/// The registry as the central server knows it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Authoritative {
+    pub cards: Vec<Card>,
+    pub revocations: RevocationList,
+}
+
+/// The link to a central server, i.e. over HTTP or MQTT.
+pub trait SyncTransport {
+    type Error: fmt::Debug;
+
+    /// Fetch the authoritative registry.
+    fn pull(&mut self) -> Result<Authoritative, Self::Error>;
+
+    /// Upload audit entries recorded since the last push, oldest first.
+    fn push(&mut self, entries: &[AuditEntry]) -> Result<(), Self::Error>;
+}
+
+/// What a single [`SyncClient::sync`] did.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct SyncReport {
+    /// The audit entries pushed upstream.
+    pub pushed: usize,
+    /// The cards registered by the server that weren't known locally.
+    pub added: usize,
+    /// The local cards the server no longer knows.
+    pub removed: usize,
+}
+
+/// Periodically pushes audit entries to a server and pulls its registry.
+#[derive(Debug)]
+pub struct SyncClient<T> {
+    transport: T,
+    interval: u64,
+    last_attempt: Option<Timestamp>,
+    last_success: Option<Timestamp>,
+    pushed: usize,
+}
+
+#[allow(dead_code)]
+impl<T: SyncTransport> SyncClient<T> {
+    /// Create a new SyncClient syncing every `interval` seconds.
+    #[inline]
+    pub const fn new(transport: T, interval: u64) -> Self {
+        Self {
+            transport,
+            interval,
+            last_attempt: None,
+            last_success: None,
+            pushed: 0,
+        }
+    }
+
+    /// Give the transport back.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    /// When the last sync succeeded, `None` if none did yet.
+    #[inline]
+    pub const fn last_success(&self) -> Option<Timestamp> {
+        self.last_success
+    }
+
+    /// The number of audit entries of a service waiting to be pushed.
+    #[inline]
+    pub fn pending<K: Kernel>(&self, nfc: &NfcService<K>) -> usize {
+        nfc.audit.len().saturating_sub(self.pushed)
+    }
+
+    /// Sync if `interval` elapsed since the last attempt, returning `None` otherwise.
+    ///
+    /// A failed attempt is retried once the interval elapses again.
+    pub fn poll<K: Kernel>(
+        &mut self,
+        nfc: &mut NfcService<K>,
+    ) -> Option<Result<SyncReport, SyncError<T::Error>>> {
+        let now = nfc.now();
+        match self.last_attempt {
+            Some(at) if now.saturating_sub(at) < self.interval => None,
+            _ => Some(self.sync(nfc)),
+        }
+    }
+
+    /// Push the buffered audit entries, then replace the registry with the server's.
+    ///
+    /// Entries that fail to push stay buffered, the registry is left untouched if
+    /// either step fails.
+    pub fn sync<K: Kernel>(
+        &mut self,
+        nfc: &mut NfcService<K>,
+    ) -> Result<SyncReport, SyncError<T::Error>> {
+        let now = nfc.now();
+        self.last_attempt = Some(now);
+
+        let entries = nfc.audit.entries().get(self.pushed..).unwrap_or_default();
+        let pushed = entries.len();
+        if !entries.is_empty() {
+            self.transport.push(entries).map_err(SyncError::Push)?;
+            self.pushed += pushed;
+        }
+
+        let authoritative = self.transport.pull().map_err(SyncError::Pull)?;
+        let (added, removed) = nfc.apply_authoritative(authoritative);
+        self.last_success = Some(now);
+        Ok(SyncReport {
+            pushed,
+            added,
+            removed,
+        })
+    }
+}
+
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Replace the registry with the server's, returning the cards added and removed.
+    ///
+    /// Cards keep the higher of the local and the server's counter, so tags presented
+    /// while offline aren't mistaken for clones.
+    fn apply_authoritative(&mut self, authoritative: Authoritative) -> (usize, usize) {
+        let mut cards = authoritative
+            .cards
+            .into_iter()
+            .map(|card| (card.id, card))
+            .collect::<BTreeMap<_, _>>();
+        for (id, card) in cards.iter_mut() {
+            if let Some(local) = self.cards.get(id) {
+                card.counter = card.counter.max(local.counter);
+            }
+        }
+
+        let removed = self
+            .cards
+            .keys()
+            .filter(|id| !cards.contains_key(id))
+            .copied()
+            .collect::<Vec<Uid>>();
+        let added = cards
+            .keys()
+            .filter(|id| !self.cards.contains_key(id))
+            .copied()
+            .collect::<Vec<Uid>>();
+
+        self.cards = cards;
+        self.revoked = authoritative.revocations;
+        for id in &removed {
+            let _ = self.duress.remove(id);
+            self.capacity.forget(id);
+            self.emit(NfcEvent::CardRemoved { id: *id });
+        }
+        for &id in &added {
+            self.capacity.touch(id);
+            self.emit(NfcEvent::CardEnrolled { id });
+        }
+        self.evict();
+        self.registry_changed();
+        (added.len(), removed.len())
+    }
+}
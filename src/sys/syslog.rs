@@ -0,0 +1,209 @@
+//! Writing the audit log to syslog as RFC 5424 messages, for enterprise log infrastructure.
+//!
+//! Every entry becomes one message with its fields as structured data, i.e.
+//!
+//! ```text
+//! <37>1 2026-10-16T08:30:00Z gate-1 lowa 812 open [audit@32473 card="04A1B2C3" reader="1" transaction="0000002a" door="3" result="ok"] card 04A1B2C3 open: ok
+//! ```
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::fmt::Write as _;
+use std::{
+    io::{self, Write},
+    net::{TcpStream, ToSocketAddrs, UdpSocket},
+    process,
+};
+
+use crate::{
+    audit::{AuditAction, AuditEntry, AuditSink, Origin},
+    logging::log_debug,
+    schedule::Date,
+};
+
+/// The identifier of the structured data element, under the enterprise number
+/// reserved for documentation.
+const SD_ID: &str = "audit@32473";
+
+/// The longest an RFC 5424 hostname may be.
+const MAX_HOSTNAME: usize = 255;
+/// The longest an RFC 5424 app name may be.
+const MAX_APP: usize = 48;
+
+/// The syslog facilities an audit log can be written as.
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Facility {
+    /// Security and authorization messages.
+    Auth = 4,
+    /// Private security and authorization messages.
+    AuthPriv = 10,
+    Local0 = 16,
+    Local1 = 17,
+    Local2 = 18,
+    Local3 = 19,
+    Local4 = 20,
+    Local5 = 21,
+    Local6 = 22,
+    Local7 = 23,
+}
+
+/// How messages reach the syslog server.
+#[derive(Debug)]
+enum Transport {
+    /// A datagram per message, as in RFC 5426.
+    Udp(UdpSocket),
+    /// Octet counted messages over a stream, as in RFC 6587.
+    Tcp(TcpStream),
+}
+
+/// An [`AuditSink`] writing every entry to a syslog server.
+///
+/// Entries of failed operations are logged as warnings, the others as notices.
+/// Entries that can't be sent are dropped, the audit log itself keeps them.
+#[derive(Debug)]
+pub struct SyslogSink {
+    transport: Transport,
+    facility: Facility,
+    hostname: String,
+    app: String,
+    pid: u32,
+}
+
+#[allow(dead_code)]
+impl SyslogSink {
+    /// Send messages as UDP datagrams to a server, usually on port 514.
+    pub fn udp<A: ToSocketAddrs>(server: A) -> io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        socket.connect(server)?;
+        Ok(Self::new(Transport::Udp(socket)))
+    }
+
+    /// Send messages over a TCP connection to a server, usually on port 601.
+    pub fn tcp<A: ToSocketAddrs>(server: A) -> io::Result<Self> {
+        TcpStream::connect(server).map(|stream| Self::new(Transport::Tcp(stream)))
+    }
+
+    fn new(transport: Transport) -> Self {
+        Self {
+            transport,
+            facility: Facility::Auth,
+            hostname: String::from("-"),
+            app: String::from("lowa"),
+            pid: process::id(),
+        }
+    }
+
+    #[inline]
+    pub fn with_facility(mut self, facility: Facility) -> Self {
+        self.facility = facility;
+        self
+    }
+
+    /// Set the hostname messages are sent from, left out by default.
+    pub fn with_hostname(mut self, hostname: &str) -> Self {
+        self.hostname = printable(hostname, MAX_HOSTNAME);
+        self
+    }
+
+    /// Set the application name messages are sent as, `lowa` by default.
+    pub fn with_app(mut self, app: &str) -> Self {
+        self.app = printable(app, MAX_APP);
+        self
+    }
+
+    /// Format an entry as an RFC 5424 message.
+    pub fn format(&self, entry: &AuditEntry) -> String {
+        // Warning or notice.
+        let severity = if entry.is_ok() { 5 } else { 4 };
+        let priority = self.facility as u8 * 8 + severity;
+        let date = Date::of(entry.at);
+        let second = entry.at % 86_400;
+
+        let mut message = format!(
+            "<{}>1 {:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z {} {} {} {} [{} card=\"{}\"",
+            priority,
+            date.year(),
+            date.month(),
+            date.day(),
+            second / 3600,
+            second / 60 % 60,
+            second % 60,
+            self.hostname,
+            self.app,
+            self.pid,
+            entry.action.name(),
+            SD_ID,
+            entry.card,
+        );
+        // Writing to a String never fails.
+        let _ = match entry.origin {
+            Origin::Reader(reader) => write!(message, " reader=\"{}\"", reader),
+            Origin::Admin => write!(message, " origin=\"admin\""),
+        };
+        if let Some(transaction) = entry.transaction {
+            let _ = write!(message, " transaction=\"{}\"", transaction);
+        }
+        let _ = match entry.action {
+            AuditAction::Open(door) => write!(message, " door=\"{}\"", door),
+            AuditAction::AssignRole(role) | AuditAction::UnassignRole(role) => {
+                write!(message, " role=\"{}\"", role)
+            }
+            AuditAction::Grant(perms)
+            | AuditAction::SetPermissions(perms)
+            | AuditAction::RequestEscalation(perms)
+            | AuditAction::DenyEscalation(perms)
+            | AuditAction::Expire(perms)
+            | AuditAction::Elevate(perms)
+            | AuditAction::Revert(perms) => {
+                write!(message, " permissions=\"{}\"", perms)
+            }
+            _ => Ok(()),
+        };
+        let outcome = match entry.result {
+            Ok(()) => "ok".to_string(),
+            Err(reason) => reason.to_string(),
+        };
+        let _ = write!(
+            message,
+            " result=\"{}\"] card {} {}: {}",
+            if entry.is_ok() { "ok" } else { "failed" },
+            entry.card,
+            entry.action.name(),
+            outcome,
+        );
+        message
+    }
+
+    fn send(&mut self, message: &str) -> io::Result<()> {
+        match &mut self.transport {
+            Transport::Udp(socket) => socket.send(message.as_bytes()).map(|_| ()),
+            Transport::Tcp(stream) => write!(stream, "{} {}", message.len(), message),
+        }
+    }
+}
+
+impl AuditSink for SyslogSink {
+    fn record(&mut self, entry: &AuditEntry) {
+        let message = self.format(entry);
+        if self.send(&message).is_err() {
+            log_debug!("dropped a syslog audit entry: {}", entry.action.name());
+        }
+    }
+}
+
+/// Keep the printable ASCII characters RFC 5424 header fields allow, `-` if none are left.
+fn printable(field: &str, max: usize) -> String {
+    let field: String = field
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max)
+        .collect();
+    if field.is_empty() {
+        String::from("-")
+    } else {
+        field
+    }
+}
@@ -0,0 +1,133 @@
+//! Reader tamper detection, locking down the doors of a reader that was tampered with.
+use alloc::{collections::btree_map::BTreeMap, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    door::DoorId, errors::KernelError, events::NfcEvent, Kernel, NfcService, ReaderId, Uid,
+};
+
+/// How a reader was tampered with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[serde(rename_all = "snake_case")]
+pub enum Tamper {
+    /// The reader's case was opened.
+    CaseOpen,
+    /// The reader was pulled off the wall.
+    Removed,
+    /// The RF field is being jammed.
+    Jamming,
+}
+
+/// The tamper state of the readers attached to a service.
+#[derive(Debug, Clone)]
+pub(crate) struct Tampers {
+    /// The consecutive polls failing with RF errors before a reader is considered jammed.
+    threshold: u8,
+    failures: BTreeMap<ReaderId, u8>,
+    tampered: BTreeMap<ReaderId, Tamper>,
+    /// The doors locked down when a reader is tampered with.
+    doors: BTreeMap<ReaderId, Vec<DoorId>>,
+}
+
+impl Tampers {
+    /// The consecutive failed polls before a reader is considered jammed, unless configured.
+    pub const DEFAULT_THRESHOLD: u8 = 5;
+
+    pub const fn new() -> Self {
+        Self {
+            threshold: Self::DEFAULT_THRESHOLD,
+            failures: BTreeMap::new(),
+            tampered: BTreeMap::new(),
+            doors: BTreeMap::new(),
+        }
+    }
+
+    /// Count a poll of a reader's RF field, returning whether it looks jammed.
+    ///
+    /// A jammed field garbles or drowns every frame, while a quiet one without tags
+    /// polls fine.
+    fn jammed(&mut self, reader: ReaderId, poll: &Result<Option<Uid>, KernelError>) -> bool {
+        let failures = self.failures.entry(reader).or_default();
+        match poll {
+            Err(KernelError::Crc | KernelError::Timeout) => {
+                *failures = failures.saturating_add(1);
+            }
+            _ => *failures = 0,
+        }
+        *failures >= self.threshold
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Lock down doors when a reader is tampered with, i.e. the doors it controls.
+    pub fn lock_down_on_tamper(&mut self, reader: ReaderId, doors: &[DoorId]) {
+        let _ = self.tampers.doors.insert(reader, doors.into());
+    }
+
+    /// Set the consecutive polls failing with RF errors before a reader is considered
+    /// jammed, at least one.
+    #[inline]
+    pub fn set_jamming_threshold(&mut self, polls: u8) {
+        self.tampers.threshold = polls.max(1);
+    }
+
+    /// Check a reader for tampering, call this periodically.
+    ///
+    /// The reader's own sensors are asked first, then its RF field is polled for signs of
+    /// jamming. The first time a tamper is found [`NfcEvent::TamperDetected`] is emitted
+    /// and the reader's doors are locked down until [`NfcService::clear_tamper`].
+    pub fn check_tamper(&mut self, reader: ReaderId) -> Option<Tamper> {
+        let kernel = self.readers.get_mut(&reader)?;
+        let tamper = match kernel.tamper() {
+            Ok(Some(tamper)) => Some(tamper),
+            _ => {
+                let poll = kernel.sense();
+                self.tampers
+                    .jammed(reader, &poll)
+                    .then_some(Tamper::Jamming)
+            }
+        }?;
+
+        if self.tampers.tampered.insert(reader, tamper).is_none() {
+            let doors = self.tampers.doors.get(&reader).cloned().unwrap_or_default();
+            for door in doors {
+                self.policy.lock_down(door);
+            }
+            self.emit(NfcEvent::TamperDetected { reader, tamper });
+        }
+        Some(tamper)
+    }
+
+    /// How a reader was tampered with, if it was.
+    #[inline]
+    pub fn tampered(&self, reader: ReaderId) -> Option<Tamper> {
+        self.tampers.tampered.get(&reader).copied()
+    }
+
+    /// Clear a reader's tamper once it was inspected, lifting the lockdown of its doors.
+    ///
+    /// Doors shared with another reader that's still tampered with stay locked down.
+    pub fn clear_tamper(&mut self, reader: ReaderId) -> Option<Tamper> {
+        let tamper = self.tampers.tampered.remove(&reader)?;
+        let _ = self.tampers.failures.remove(&reader);
+        let tampers = &self.tampers;
+        let still_locked = |door: &DoorId| {
+            tampers
+                .tampered
+                .keys()
+                .filter_map(|other| tampers.doors.get(other))
+                .any(|doors| doors.contains(door))
+        };
+        for door in tampers.doors.get(&reader).into_iter().flatten() {
+            if !still_locked(door) {
+                let _ = self.policy.lift_lockdown(*door);
+            }
+        }
+        Some(tamper)
+    }
+}
@@ -0,0 +1,158 @@
+//! Templates standardizing how new cards are minted.
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    role::{RoleId, RoleSet},
+    Card, Kernel, NfcService, Permissions, Position, Timestamp, Uid,
+};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// When cards minted from a [`CardTemplate`] expire.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ExpiryPolicy {
+    /// The cards never expire.
+    #[default]
+    Never,
+    /// The cards expire this many seconds after they're issued.
+    After(u64),
+    /// The cards expire at a fixed moment, i.e. the end of a contract.
+    At(Timestamp),
+    /// The cards expire at the end of the UTC day they're issued on.
+    EndOfDay,
+}
+
+impl ExpiryPolicy {
+    /// The moment a card issued at `now` expires, if it does.
+    #[inline]
+    pub const fn valid_until(&self, now: Timestamp) -> Option<Timestamp> {
+        match *self {
+            Self::Never => None,
+            Self::After(seconds) => Some(now.saturating_add(seconds)),
+            Self::At(at) => Some(at),
+            Self::EndOfDay => Some((now - now % DAY).saturating_add(DAY)),
+        }
+    }
+}
+
+/// The defaults new cards are issued with, i.e. "Contractor" or "Visitor".
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardTemplate {
+    permissions: Permissions,
+    #[serde(default)]
+    position: Position,
+    #[serde(default)]
+    roles: RoleSet,
+    #[serde(default)]
+    expiry: ExpiryPolicy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uses: Option<u32>,
+}
+
+#[allow(dead_code)]
+impl CardTemplate {
+    /// Create a new CardTemplate issuing cards with `permissions` that never expire.
+    #[inline]
+    pub const fn new(permissions: Permissions) -> Self {
+        Self {
+            permissions,
+            position: Position::Coordinator,
+            roles: RoleSet::empty(),
+            expiry: ExpiryPolicy::Never,
+            uses: None,
+        }
+    }
+
+    /// A template for visitor cards with `permissions`, limited to `uses` accesses
+    /// and expiring at the end of the day they're issued.
+    #[inline]
+    pub const fn visitor(permissions: Permissions, uses: u32) -> Self {
+        Self::new(permissions)
+            .with_expiry(ExpiryPolicy::EndOfDay)
+            .with_uses(uses)
+    }
+
+    #[inline]
+    pub const fn with_position(mut self, position: Position) -> Self {
+        self.position = position;
+        self
+    }
+
+    #[inline]
+    pub const fn with_expiry(mut self, expiry: ExpiryPolicy) -> Self {
+        self.expiry = expiry;
+        self
+    }
+
+    /// Limit the cards issued to a number of accesses.
+    #[inline]
+    pub const fn with_uses(mut self, uses: u32) -> Self {
+        self.uses = Some(uses);
+        self
+    }
+
+    /// Assign a role to the cards issued, roles out of range are ignored.
+    #[inline]
+    pub fn with_role(mut self, role: RoleId) -> Self {
+        let _ = self.roles.insert(role);
+        self
+    }
+
+    #[inline]
+    pub const fn permissions(&self) -> Permissions {
+        self.permissions
+    }
+
+    #[inline]
+    pub const fn position(&self) -> Position {
+        self.position
+    }
+
+    #[inline]
+    pub const fn roles(&self) -> RoleSet {
+        self.roles
+    }
+
+    #[inline]
+    pub const fn expiry(&self) -> ExpiryPolicy {
+        self.expiry
+    }
+
+    #[inline]
+    pub const fn uses(&self) -> Option<u32> {
+        self.uses
+    }
+
+    /// Mint a card with this template's defaults at `now`.
+    pub const fn mint(&self, id: Uid, now: Timestamp) -> Card {
+        let mut card = Card::new(id, self.permissions).with_position(self.position);
+        card.roles = self.roles;
+        card.valid_until = self.expiry.valid_until(now);
+        card.uses = self.uses;
+        card
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Mint a card from a template under the next free UID and register it.
+    ///
+    /// Issued UIDs are single size and allocated upwards from `00000001`,
+    /// skipping any already registered.
+    pub fn issue(&mut self, template: &CardTemplate) -> Card {
+        let card = template.mint(self.free_uid(), self.now());
+        self.put(card);
+        card
+    }
+
+    /// The next free UID [`NfcService::issue`] would allocate.
+    pub(crate) fn free_uid(&self) -> Uid {
+        (1..=u32::MAX)
+            .map(Uid::from_u32)
+            .find(|id| !self.cards.contains_key(id))
+            .expect("the registry can't hold every single size UID")
+    }
+}
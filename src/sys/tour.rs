@@ -0,0 +1,256 @@
+//! Guard tours, patrols tapping checkpoint readers along a route in time.
+//!
+//! A guard starts a patrol on a [`PatrolRoute`] and taps its checkpoints in order, each
+//! within its window after the previous one. Checkpoints tapped out of order emit
+//! [`NfcEvent::CheckpointOutOfOrder`], skipped or overdue ones [`NfcEvent::CheckpointMissed`].
+use alloc::{collections::btree_map::BTreeMap, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::AccessError, events::NfcEvent, transaction::Traced, Card, Kernel, NfcService, ReaderId,
+    Timestamp, Uid,
+};
+
+/// The identifier of a [`PatrolRoute`].
+pub type RouteId = u16;
+
+/// A reader on a patrol route.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub reader: ReaderId,
+    /// The seconds the checkpoint must be tapped within after the previous one,
+    /// or after the patrol started for the first checkpoint.
+    pub within: u64,
+}
+
+/// The checkpoints of a patrol, in the order they're tapped.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatrolRoute {
+    checkpoints: Vec<Checkpoint>,
+}
+
+#[allow(dead_code)]
+impl PatrolRoute {
+    /// Create a new PatrolRoute without any checkpoints.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            checkpoints: Vec::new(),
+        }
+    }
+
+    /// Add a checkpoint to tap within `within` seconds after the previous one.
+    #[inline]
+    pub fn checkpoint(mut self, reader: ReaderId, within: u64) -> Self {
+        self.checkpoints.push(Checkpoint { reader, within });
+        self
+    }
+
+    #[inline]
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        &self.checkpoints
+    }
+}
+
+/// A patrol in progress.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Patrol {
+    pub route: RouteId,
+    /// The index of the next checkpoint to tap.
+    pub next: usize,
+    /// When the previous checkpoint was tapped or missed, or when the patrol started.
+    pub since: Timestamp,
+}
+
+/// The patrol routes of a service and the patrols in progress.
+#[derive(Debug, Clone, Default)]
+pub struct GuardTours {
+    routes: BTreeMap<RouteId, PatrolRoute>,
+    patrols: BTreeMap<Uid, Patrol>,
+}
+
+#[allow(dead_code)]
+impl GuardTours {
+    /// Create a new `GuardTours` without any routes.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            routes: BTreeMap::new(),
+            patrols: BTreeMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn route(&self, id: RouteId) -> Option<&PatrolRoute> {
+        self.routes.get(&id)
+    }
+
+    /// The patrol a guard is on.
+    #[inline]
+    pub fn patrol(&self, guard: Uid) -> Option<&Patrol> {
+        self.patrols.get(&guard)
+    }
+
+    /// An iterator over the guards on patrol and their patrols.
+    pub fn patrols(&self) -> impl Iterator<Item = (Uid, &Patrol)> + '_ {
+        self.patrols.iter().map(|(guard, patrol)| (*guard, patrol))
+    }
+
+    /// Whether a reader is a checkpoint of any route.
+    pub fn is_checkpoint(&self, reader: ReaderId) -> bool {
+        self.routes
+            .values()
+            .flat_map(|route| route.checkpoints.iter())
+            .any(|checkpoint| checkpoint.reader == reader)
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Add a patrol route, replacing any route with the same id.
+    ///
+    /// Patrols on a replaced route carry on from the same checkpoint index.
+    pub fn add_route(&mut self, id: RouteId, route: PatrolRoute) -> Option<PatrolRoute> {
+        self.tours.routes.insert(id, route)
+    }
+
+    /// Remove a patrol route, ending the patrols on it.
+    pub fn remove_route(&mut self, id: RouteId) -> Option<PatrolRoute> {
+        self.tours.patrols.retain(|_, patrol| patrol.route != id);
+        self.tours.routes.remove(&id)
+    }
+
+    /// An immutable reference to the patrol routes and patrols in progress.
+    #[inline]
+    pub const fn tours(&self) -> &GuardTours {
+        &self.tours
+    }
+
+    /// Start a guard's patrol on a route, replacing the patrol they were on.
+    ///
+    /// Returns `false` if the guard's card isn't registered or the route doesn't exist.
+    pub fn start_patrol(&mut self, guard: Uid, route: RouteId) -> bool {
+        if !self.cards.contains_key(&guard) || !self.tours.routes.contains_key(&route) {
+            return false;
+        }
+        let since = self.now();
+        let _ = self.tours.patrols.insert(
+            guard,
+            Patrol {
+                route,
+                next: 0,
+                since,
+            },
+        );
+        true
+    }
+
+    /// End a guard's patrol before its last checkpoint.
+    #[inline]
+    pub fn end_patrol(&mut self, guard: Uid) -> Option<Patrol> {
+        self.tours.patrols.remove(&guard)
+    }
+
+    /// Authorize a card tapped at a checkpoint reader like [`NfcService::authorize`],
+    /// advancing the guard's patrol.
+    pub fn tap(&mut self, reader: ReaderId, payload: &Card) -> Result<Card, Traced<AccessError>> {
+        self.transact_traced(reader, payload.id, |this| {
+            this.tap_checkpoint(reader, payload)
+        })
+    }
+
+    fn tap_checkpoint(&mut self, reader: ReaderId, payload: &Card) -> Result<Card, AccessError> {
+        let card = self.authorize(reader, payload)?;
+        let now = self.now();
+        self.check_patrol(card.id, now);
+
+        let Some(patrol) = self.tours.patrols.get(&card.id).copied() else {
+            return Ok(card);
+        };
+        let Some(route) = self.tours.routes.get(&patrol.route) else {
+            return Ok(card);
+        };
+        // The checkpoint is still ahead of the patrol, the ones before it were skipped.
+        let ahead = &route.checkpoints[patrol.next..];
+        let skipped: Option<Vec<ReaderId>> = ahead
+            .iter()
+            .position(|checkpoint| checkpoint.reader == reader)
+            .map(|index| ahead[..index].iter().map(|c| c.reader).collect());
+        let expected = ahead[0].reader;
+        let len = route.checkpoints.len();
+
+        if skipped.as_ref().is_none_or(|skipped| !skipped.is_empty()) {
+            self.emit(NfcEvent::CheckpointOutOfOrder {
+                reader,
+                id: card.id,
+                expected,
+            });
+        }
+        let Some(skipped) = skipped else {
+            return Ok(card);
+        };
+        let next = patrol.next + skipped.len() + 1;
+        for missed in skipped {
+            self.emit(NfcEvent::CheckpointMissed {
+                reader: missed,
+                id: card.id,
+                due: now,
+            });
+        }
+        self.advance_patrol(card.id, next, len, now);
+        Ok(card)
+    }
+
+    /// Report the checkpoints of every patrol that are overdue, call this periodically.
+    pub fn check_patrols(&mut self) {
+        let now = self.now();
+        let guards: Vec<Uid> = self.tours.patrols.keys().copied().collect();
+        for guard in guards {
+            self.check_patrol(guard, now);
+        }
+    }
+
+    /// Report the overdue checkpoints of a guard's patrol, moving past them.
+    fn check_patrol(&mut self, guard: Uid, now: Timestamp) {
+        while let Some(patrol) = self.tours.patrols.get(&guard).copied() {
+            let Some(route) = self.tours.routes.get(&patrol.route) else {
+                return;
+            };
+            let len = route.checkpoints.len();
+            let Some(checkpoint) = route.checkpoints.get(patrol.next).copied() else {
+                let _ = self.tours.patrols.remove(&guard);
+                return;
+            };
+            let due = patrol.since.saturating_add(checkpoint.within);
+            if now <= due {
+                return;
+            }
+            self.emit(NfcEvent::CheckpointMissed {
+                reader: checkpoint.reader,
+                id: guard,
+                due,
+            });
+            self.advance_patrol(guard, patrol.next + 1, len, due);
+        }
+    }
+
+    /// Move a guard's patrol to a checkpoint, completing it past the last one.
+    fn advance_patrol(&mut self, guard: Uid, next: usize, len: usize, since: Timestamp) {
+        if next >= len {
+            if let Some(patrol) = self.tours.patrols.remove(&guard) {
+                self.emit(NfcEvent::PatrolCompleted {
+                    id: guard,
+                    route: patrol.route,
+                });
+            }
+            return;
+        }
+        if let Some(patrol) = self.tours.patrols.get_mut(&guard) {
+            patrol.next = next;
+            patrol.since = since;
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Transaction ids correlating the steps of a card's presentation at a reader.
+//!
+//! Polling a reader that detects a tag begins a transaction for it, which the operations on
+//! that tag at the reader continue, i.e. sense → read → decide → write back. An operation on a
+//! card the reader's last poll didn't detect is a transaction of its own.
+//!
+//! The id is carried by the audit entries, events and errors of the transaction, see
+//! [`AuditEntry::transaction`](crate::audit::AuditEntry::transaction), [`TracedEvent`] and
+//! [`Traced`].
+use alloc::collections::btree_map::BTreeMap;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::AccessError, events::TracedEvent, Kernel, NfcService, ReaderId, Uid};
+
+/// A unique id of a transaction at a reader.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[serde(transparent)]
+pub struct TransactionId(u32);
+
+#[allow(dead_code)]
+impl TransactionId {
+    #[inline]
+    pub const fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+impl fmt::Display for TransactionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:08x}", self.0)
+    }
+}
+
+/// An error of an operation on a card at a reader, with the transaction it was part of.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Traced<E> {
+    pub transaction: TransactionId,
+    pub error: E,
+}
+
+#[allow(dead_code)]
+impl<E> Traced<E> {
+    #[inline]
+    pub const fn new(transaction: TransactionId, error: E) -> Self {
+        Self { transaction, error }
+    }
+
+    /// The error without its transaction.
+    #[inline]
+    pub fn into_inner(self) -> E {
+        self.error
+    }
+}
+
+impl<E> From<Traced<E>> for AccessError
+where
+    AccessError: From<E>,
+{
+    #[inline]
+    fn from(traced: Traced<E>) -> Self {
+        traced.error.into()
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for Traced<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Traced(transaction: {}, error: {})",
+            self.transaction, self.error
+        )
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for Traced<E> {}
+
+/// Whether [`Transactions::enter`] began the transaction it returned.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Entered {
+    id: TransactionId,
+    began: bool,
+}
+
+/// The open transaction of each reader.
+#[derive(Debug, Clone)]
+pub(crate) struct Transactions {
+    next: u32,
+    open: BTreeMap<ReaderId, (Uid, TransactionId)>,
+}
+
+impl Transactions {
+    pub const fn new() -> Self {
+        Self {
+            next: 0,
+            open: BTreeMap::new(),
+        }
+    }
+
+    fn fresh(&mut self) -> TransactionId {
+        let id = TransactionId(self.next);
+        self.next = self.next.wrapping_add(1);
+        id
+    }
+
+    /// Begin a transaction for a tag detected by a reader, ending the one it had open.
+    pub fn begin(&mut self, reader: ReaderId, card: Uid) -> TransactionId {
+        let id = self.fresh();
+        let _ = self.open.insert(reader, (card, id));
+        id
+    }
+
+    /// End the open transaction of a reader, i.e. once its field is empty.
+    pub fn end(&mut self, reader: ReaderId) -> Option<TransactionId> {
+        self.open.remove(&reader).map(|(_, id)| id)
+    }
+
+    /// The open transaction of a reader, whichever card it's for.
+    pub fn open(&self, reader: ReaderId) -> Option<TransactionId> {
+        self.open.get(&reader).map(|&(_, id)| id)
+    }
+
+    /// The open transaction of a reader, if it's for this card.
+    pub fn current(&self, reader: ReaderId, card: Uid) -> Option<TransactionId> {
+        match self.open.get(&reader) {
+            Some(&(open, id)) if open == card => Some(id),
+            _ => None,
+        }
+    }
+
+    /// The transaction an event or audit entry for a card at a reader is part of,
+    /// a transaction of its own if the reader has none open for the card.
+    pub fn of(&mut self, reader: ReaderId, card: Uid) -> TransactionId {
+        match self.current(reader, card) {
+            Some(id) => id,
+            None => self.fresh(),
+        }
+    }
+
+    /// Continue the open transaction of a reader for an operation on a card, beginning one
+    /// for the operation if there's none open for the card.
+    pub fn enter(&mut self, reader: ReaderId, card: Uid) -> Entered {
+        match self.current(reader, card) {
+            Some(id) => Entered { id, began: false },
+            None => Entered {
+                id: self.begin(reader, card),
+                began: true,
+            },
+        }
+    }
+
+    /// Leave an operation, ending its transaction if the operation began it.
+    pub fn leave(&mut self, reader: ReaderId, entered: Entered) {
+        if entered.began && self.open(reader) == Some(entered.id) {
+            let _ = self.open.remove(&reader);
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Begin a transaction for a card at a reader, for flows that don't poll the reader
+    /// through the service.
+    ///
+    /// It's continued by the operations on the card at the reader until the reader detects
+    /// another tag or [`NfcService::end_transaction`].
+    #[inline]
+    pub fn begin_transaction(&mut self, reader: ReaderId, card: Uid) -> TransactionId {
+        self.transactions.begin(reader, card)
+    }
+
+    /// End the open transaction of a reader.
+    #[inline]
+    pub fn end_transaction(&mut self, reader: ReaderId) -> Option<TransactionId> {
+        self.transactions.end(reader)
+    }
+
+    /// The open transaction of a reader, if it's for this card.
+    #[inline]
+    pub fn transaction(&self, reader: ReaderId, card: Uid) -> Option<TransactionId> {
+        self.transactions.current(reader, card)
+    }
+
+    /// Take all the events emitted since the last call, along with their transactions.
+    pub fn drain_traced_events(&mut self) -> alloc::vec::Vec<TracedEvent> {
+        core::mem::take(&mut self.events)
+    }
+
+    /// Run an operation on a card at a reader as part of its transaction.
+    pub(crate) fn transact<T>(
+        &mut self,
+        reader: ReaderId,
+        card: Uid,
+        operation: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        let entered = self.transactions.enter(reader, card);
+        let result = operation(self);
+        self.transactions.leave(reader, entered);
+        result
+    }
+
+    /// Run an operation on a card at a reader as part of its transaction, tracing its error
+    /// to the transaction.
+    pub(crate) fn transact_traced<T, E>(
+        &mut self,
+        reader: ReaderId,
+        card: Uid,
+        operation: impl FnOnce(&mut Self) -> Result<T, E>,
+    ) -> Result<T, Traced<E>> {
+        let entered = self.transactions.enter(reader, card);
+        let result = operation(self).map_err(|error| Traced::new(entered.id, error));
+        self.transactions.leave(reader, entered);
+        result
+    }
+
+    /// Trace the error of an operation at a reader that failed before its card was known,
+    /// to the reader's open transaction or one of its own.
+    pub(crate) fn trace<E>(&mut self, reader: ReaderId, error: E) -> Traced<E> {
+        let transaction = match self.transactions.open(reader) {
+            Some(id) => id,
+            None => self.transactions.fresh(),
+        };
+        Traced::new(transaction, error)
+    }
+}
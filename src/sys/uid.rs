@@ -0,0 +1,202 @@
+use core::{cmp::Ordering, fmt, hash};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// The unique identifier of an NFC tag.
+///
+/// Tags carry single (4 bytes), double (7 bytes) or triple (10 bytes) size UIDs,
+/// this is stored inline without any allocation.
+#[derive(Copy, Clone)]
+pub struct Uid {
+    len: u8,
+    bytes: [u8; Uid::MAX_LEN],
+}
+
+#[allow(dead_code)]
+impl Uid {
+    /// The size of the largest UID in bytes.
+    pub const MAX_LEN: usize = 10;
+
+    /// Create a new Uid from its bytes. Returns `None` if it isn't 4, 7 or 10 bytes long.
+    #[inline]
+    pub const fn new(uid: &[u8]) -> Option<Self> {
+        if !matches!(uid.len(), 4 | 7 | 10) {
+            return None;
+        }
+
+        let mut bytes = [0; Self::MAX_LEN];
+        let mut i = 0;
+        while i < uid.len() {
+            bytes[i] = uid[i];
+            i += 1;
+        }
+        Some(Self {
+            len: uid.len() as u8,
+            bytes,
+        })
+    }
+
+    /// Create a single size Uid from a numeric card id.
+    ///
+    /// This keeps the numeric ids cards were registered with before working.
+    #[inline]
+    pub const fn from_u16(id: u16) -> Self {
+        let [hi, lo] = id.to_be_bytes();
+        let mut bytes = [0; Self::MAX_LEN];
+        bytes[2] = hi;
+        bytes[3] = lo;
+        Self { len: 4, bytes }
+    }
+
+    /// Create a single size Uid from a 32 bit number, as allocated by
+    /// [`NfcService::issue`](crate::NfcService::issue).
+    #[inline]
+    pub const fn from_u32(id: u32) -> Self {
+        let [a, b, c, d] = id.to_be_bytes();
+        let mut bytes = [0; Self::MAX_LEN];
+        bytes[0] = a;
+        bytes[1] = b;
+        bytes[2] = c;
+        bytes[3] = d;
+        Self { len: 4, bytes }
+    }
+
+    /// The bytes of this Uid.
+    #[inline]
+    pub const fn as_bytes(&self) -> &[u8] {
+        self.bytes.split_at(self.len as usize).0
+    }
+
+    /// Parse a Uid from its hex representation, as shown by [`fmt::Display`].
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if !hex.len().is_multiple_of(2) || hex.len() > Self::MAX_LEN * 2 {
+            return None;
+        }
+
+        let mut bytes = [0; Self::MAX_LEN];
+        for (i, pair) in hex.as_bytes().chunks_exact(2).enumerate() {
+            let pair = core::str::from_utf8(pair).ok()?;
+            bytes[i] = u8::from_str_radix(pair, 16).ok()?;
+        }
+        Self::new(&bytes[..hex.len() / 2])
+    }
+
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len as usize
+    }
+}
+
+impl From<u16> for Uid {
+    #[inline]
+    fn from(id: u16) -> Self {
+        Self::from_u16(id)
+    }
+}
+
+impl PartialEq for Uid {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for Uid {}
+
+impl PartialOrd for Uid {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Uid {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+impl hash::Hash for Uid {
+    #[inline]
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state)
+    }
+}
+
+impl fmt::Display for Uid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.as_bytes() {
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Uid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Uid({})", self)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Uid {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "Uid({=[u8]:02X})", self.as_bytes())
+    }
+}
+
+impl Serialize for Uid {
+    /// A hex string, or the raw bytes for binary formats.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return serializer.serialize_bytes(self.as_bytes());
+        }
+
+        let mut hex = [0; Uid::MAX_LEN * 2];
+        for (i, byte) in self.as_bytes().iter().enumerate() {
+            hex[i * 2] = HEX[(byte >> 4) as usize];
+            hex[i * 2 + 1] = HEX[(byte & 0xf) as usize];
+        }
+        // SAFETY: Only ASCII hex digits were written.
+        serializer.serialize_str(unsafe { core::str::from_utf8_unchecked(&hex[..self.len() * 2]) })
+    }
+}
+
+const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+impl<'de> Deserialize<'de> for Uid {
+    /// Accepts a hex string, or a numeric id for payloads written before UIDs.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = Uid;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a 4, 7 or 10 byte hex string or a numeric card id")
+            }
+
+            fn visit_u64<E: de::Error>(self, id: u64) -> Result<Uid, E> {
+                u16::try_from(id)
+                    .map(Uid::from_u16)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(id), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, hex: &str) -> Result<Uid, E> {
+                Uid::from_hex(hex).ok_or_else(|| E::invalid_value(de::Unexpected::Str(hex), &self))
+            }
+
+            fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Uid, E> {
+                Uid::new(bytes).ok_or_else(|| E::invalid_length(bytes.len(), &self))
+            }
+        }
+
+        // Binary formats like postcard don't describe their values.
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(Visitor)
+        } else {
+            deserializer.deserialize_bytes(Visitor)
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Posting service events to a webhook, for SIEM or chat-ops integrations.
+//!
+//! Every event is POSTed as the same JSON it serializes to, i.e.
+//! `{"event":"card_revoked","reader":1,..}`, from a background thread. Access decisions are
+//! POSTed as [`AccessEvent`]s, i.e. `{"event":"access","tx_id":7,"uid":"04A1B2C3",..}`.
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, time::Duration};
+use std::{
+    sync::mpsc::{self, Receiver, SyncSender},
+    thread,
+};
+
+use crate::{
+    events::{AccessEvent, NfcEvent, Subscriber, TracedEvent},
+    logging::{log_debug, log_warn},
+};
+
+/// The number of events queued for the webhook before new ones are dropped.
+const CAPACITY: usize = 64;
+
+/// How failed deliveries are retried, doubling the delay after every attempt.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Backoff {
+    /// The retries after the first attempt before an event is dropped.
+    pub retries: u32,
+    /// The delay before the first retry.
+    pub initial: Duration,
+    /// The longest delay between two retries.
+    pub max: Duration,
+}
+
+impl Backoff {
+    /// Retry 5 times, starting after a second and waiting at most 30 seconds.
+    pub const DEFAULT: Backoff = Backoff {
+        retries: 5,
+        initial: Duration::from_secs(1),
+        max: Duration::from_secs(30),
+    };
+
+    /// The delay before a retry, counting from `0`.
+    #[inline]
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.initial
+            .saturating_mul(1 << retry.min(16))
+            .min(self.max)
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Errors returned when delivering an event to a webhook.
+#[derive(Debug)]
+pub enum WebhookError {
+    /// The endpoint answered with an error status.
+    Status(u16),
+    /// The endpoint couldn't be reached.
+    Transport(String),
+}
+
+impl WebhookError {
+    /// Whether delivering again may succeed, client errors other than rate limits won't.
+    #[inline]
+    pub const fn is_transient(&self) -> bool {
+        match *self {
+            Self::Status(status) => status == 408 || status == 429 || status >= 500,
+            Self::Transport(..) => true,
+        }
+    }
+}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status(status) => write!(f, "Status(status: {})", status),
+            Self::Transport(why) => write!(f, "Transport({})", why),
+        }
+    }
+}
+
+impl core::error::Error for WebhookError {}
+
+/// The endpoint events are POSTed to.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    url: String,
+    headers: Vec<(String, String)>,
+    backoff: Backoff,
+    timeout: Duration,
+}
+
+#[allow(dead_code)]
+impl Webhook {
+    /// Create a new Webhook posting to `url`, retrying with [`Backoff::DEFAULT`].
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            headers: Vec::new(),
+            backoff: Backoff::DEFAULT,
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// Send a header with every request, i.e. `Authorization`.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    #[inline]
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Set how long a single request may take.
+    #[inline]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    #[inline]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// POST a JSON body once, without retrying.
+    pub fn post(&self, agent: &ureq::Agent, body: &[u8]) -> Result<(), WebhookError> {
+        let request = self
+            .headers
+            .iter()
+            .fold(agent.post(&self.url), |request, (name, value)| {
+                request.set(name, value)
+            })
+            .set("Content-Type", "application/json");
+        match request.send_bytes(body) {
+            Ok(..) => Ok(()),
+            Err(ureq::Error::Status(status, _)) => Err(WebhookError::Status(status)),
+            Err(ureq::Error::Transport(why)) => Err(WebhookError::Transport(why.to_string())),
+        }
+    }
+
+    /// POST a JSON body, retrying transient failures with backoff.
+    pub fn deliver(&self, agent: &ureq::Agent, body: &[u8]) -> Result<(), WebhookError> {
+        let mut retry = 0;
+        loop {
+            match self.post(agent, body) {
+                Err(why) if why.is_transient() && retry < self.backoff.retries => {
+                    thread::sleep(self.backoff.delay(retry));
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Start delivering events from a background thread, which runs for as long
+    /// as the returned notifier lives.
+    pub fn spawn(self) -> WebhookNotifier {
+        let (sender, receiver) = mpsc::sync_channel(CAPACITY);
+        let _ = thread::spawn(move || self.run(receiver));
+        WebhookNotifier { sender }
+    }
+
+    fn run(self, events: Receiver<(&'static str, Vec<u8>)>) {
+        let agent = ureq::AgentBuilder::new().timeout(self.timeout).build();
+        for (name, body) in events {
+            if self.deliver(&agent, &body).is_err() {
+                log_warn!("gave up delivering a webhook event: {}", name);
+            }
+        }
+    }
+}
+
+/// A [`Subscriber`] posting every event it's notified of to a [`Webhook`].
+///
+/// Posting never blocks the service, events are dropped while the queue is full.
+pub struct WebhookNotifier {
+    sender: SyncSender<(&'static str, Vec<u8>)>,
+}
+
+impl Subscriber for WebhookNotifier {
+    fn on_event(&mut self, event: &NfcEvent) {
+        self.on_traced(&TracedEvent {
+            transaction: None,
+            event: *event,
+        })
+    }
+
+    fn on_traced(&mut self, event: &TracedEvent) {
+        if !event.event.is_access() {
+            self.send(event.event.name(), event);
+        }
+    }
+
+    fn on_access(&mut self, event: &AccessEvent) {
+        self.send("access", event);
+    }
+}
+
+impl WebhookNotifier {
+    /// Queue an event for delivery, dropping it if the queue is full.
+    fn send<E: serde::Serialize>(&mut self, name: &'static str, event: &E) {
+        let Ok(body) = serde_json::to_vec(event) else {
+            return;
+        };
+
+        if self.sender.try_send((name, body)).is_err() {
+            log_debug!("dropped a webhook event: {}", name);
+        }
+    }
+}
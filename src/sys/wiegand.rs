@@ -0,0 +1,152 @@
+//! Wiegand output for driving legacy access control panels.
+use crate::{errors::AccessError, Card, Kernel, NfcService, ReaderId, Uid};
+
+/// The supported Wiegand frame formats.
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Format {
+    /// 26 bits, an 8-bit facility code and a 16-bit card number.
+    #[default]
+    W26,
+    /// 34 bits, a 16-bit facility code and a 16-bit card number.
+    W34,
+}
+
+impl Format {
+    /// The number of bits in a frame of this format.
+    #[inline]
+    pub const fn bits(self) -> u8 {
+        match self {
+            Self::W26 => 26,
+            Self::W34 => 34,
+        }
+    }
+
+    /// The largest facility code this format can carry.
+    #[inline]
+    pub const fn max_facility(self) -> u16 {
+        match self {
+            Self::W26 => u8::MAX as u16,
+            Self::W34 => u16::MAX,
+        }
+    }
+}
+
+/// An encoded Wiegand frame, the first bit to send is the most significant of `len` bits.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Frame {
+    bits: u64,
+    len: u8,
+}
+
+#[allow(dead_code)]
+impl Frame {
+    /// The frame's bits, right aligned.
+    #[inline]
+    pub const fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    /// The number of bits in this frame.
+    #[inline]
+    pub const fn len(&self) -> u8 {
+        self.len
+    }
+
+    /// Iterate over the frame's bits in transmission order.
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).rev().map(|i| self.bits >> i & 1 == 1)
+    }
+}
+
+/// Encodes card UIDs into Wiegand frames for a facility.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Encoder {
+    format: Format,
+    facility: u16,
+}
+
+#[allow(dead_code)]
+impl Encoder {
+    /// Create a new encoder. Returns `None` if `facility` doesn't fit in `format`.
+    #[inline]
+    pub const fn new(format: Format, facility: u16) -> Option<Self> {
+        if facility > format.max_facility() {
+            return None;
+        }
+        Some(Self { format, facility })
+    }
+
+    #[inline]
+    pub const fn format(&self) -> Format {
+        self.format
+    }
+
+    #[inline]
+    pub const fn facility(&self) -> u16 {
+        self.facility
+    }
+
+    /// The card number sent for a UID, its two least significant bytes.
+    #[inline]
+    pub fn card_number(id: Uid) -> u16 {
+        match id.as_bytes() {
+            [.., hi, lo] => u16::from_be_bytes([*hi, *lo]),
+            _ => 0,
+        }
+    }
+
+    /// Encode the frame for a UID.
+    pub fn encode(&self, id: Uid) -> Frame {
+        let width = self.format.bits() as u32 - 2;
+        let data = (self.facility as u64) << 16 | Self::card_number(id) as u64;
+
+        // The leading bit gives even parity over the first half of the data,
+        // the trailing bit gives odd parity over the second half.
+        let half = width / 2;
+        let high = data >> half;
+        let low = data & ((1 << half) - 1);
+        let even = (high.count_ones() & 1) as u64;
+        let odd = (low.count_ones() & 1 == 0) as u64;
+
+        Frame {
+            bits: even << (width + 1) | data << 1 | odd,
+            len: self.format.bits(),
+        }
+    }
+}
+
+/// An output line that can transmit Wiegand frames to a panel.
+#[allow(unused)]
+pub trait WiegandOutput {
+    type Error;
+
+    /// Transmit a single frame.
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error>;
+}
+
+#[allow(dead_code)]
+impl<K> NfcService<K>
+where
+    K: Kernel,
+{
+    /// Authorize a card and forward granted reads to a panel as a Wiegand frame.
+    ///
+    /// A frame that fails to transmit is reported as [`AccessError::Kernel`].
+    pub fn relay<W>(
+        &mut self,
+        reader: ReaderId,
+        payload: &Card,
+        encoder: &Encoder,
+        output: &mut W,
+    ) -> Result<Card, AccessError>
+    where
+        W: WiegandOutput,
+    {
+        let card = self.authorize(reader, payload)?;
+        output
+            .send(encoder.encode(card.id))
+            .map_err(|_| AccessError::Kernel)?;
+        Ok(card)
+    }
+}
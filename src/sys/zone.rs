@@ -0,0 +1,227 @@
+//! Hierarchical access zones, i.e. a building containing floors containing rooms.
+//!
+//! Doors are placed in zones and access can be granted at any level of the hierarchy,
+//! a grant on a building opens the doors of all its floors and rooms.
+use alloc::{collections::btree_map::BTreeMap, string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::{door::DoorId, errors::ZoneError, role::RoleId, Card, Permissions};
+
+/// The identifier of a [`Zone`].
+pub type ZoneId = u16;
+
+/// The level of a [`Zone`] in the hierarchy, a zone's parent is always of a higher kind.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneKind {
+    Room,
+    Floor,
+    Building,
+}
+
+/// Who a [`Zone`] admits through all of its doors.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneGrant {
+    /// Cards assigned this role.
+    Role(RoleId),
+    /// Cards holding all of these permissions.
+    Permissions(Permissions),
+}
+
+/// An area of a site, i.e. "Building A" or "Server room".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Zone {
+    name: String,
+    kind: ZoneKind,
+    parent: Option<ZoneId>,
+    grants: Vec<ZoneGrant>,
+}
+
+#[allow(dead_code)]
+impl Zone {
+    /// Create a new top level Zone without any grants.
+    #[inline]
+    pub fn new(name: impl Into<String>, kind: ZoneKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            parent: None,
+            grants: Vec::new(),
+        }
+    }
+
+    /// Place this Zone within another one.
+    #[inline]
+    pub fn within(mut self, parent: ZoneId) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// Admit cards matching `grant` through this Zone and all the zones within it.
+    #[inline]
+    pub fn grant(mut self, grant: ZoneGrant) -> Self {
+        self.grants.push(grant);
+        self
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub const fn kind(&self) -> ZoneKind {
+        self.kind
+    }
+
+    #[inline]
+    pub const fn parent(&self) -> Option<ZoneId> {
+        self.parent
+    }
+
+    #[inline]
+    pub fn grants(&self) -> &[ZoneGrant] {
+        &self.grants
+    }
+}
+
+/// The zones of a site and the doors placed in them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Zones {
+    zones: BTreeMap<ZoneId, Zone>,
+    doors: BTreeMap<DoorId, ZoneId>,
+}
+
+#[allow(dead_code)]
+impl Zones {
+    /// Create a new `Zones` without any zones.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            zones: BTreeMap::new(),
+            doors: BTreeMap::new(),
+        }
+    }
+
+    /// Add a zone, replacing any zone with the same id.
+    ///
+    /// Its parent must already exist and be of a higher kind, i.e. a floor within a building.
+    pub fn add(&mut self, id: ZoneId, zone: Zone) -> Result<Option<Zone>, ZoneError> {
+        if let Some(parent) = zone.parent {
+            match self.zones.get(&parent) {
+                Some(outer) if outer.kind > zone.kind => {}
+                Some(..) => return Err(ZoneError::InvalidParent { zone: id, parent }),
+                None => return Err(ZoneError::UnknownZone(parent)),
+            }
+        }
+        let outgrown = self
+            .zones
+            .iter()
+            .find(|(_, inner)| inner.parent == Some(id) && inner.kind >= zone.kind);
+        if let Some((&inner, _)) = outgrown {
+            return Err(ZoneError::InvalidParent {
+                zone: inner,
+                parent: id,
+            });
+        }
+        Ok(self.zones.insert(id, zone))
+    }
+
+    /// Remove a zone, moving its zones and doors up to its parent.
+    ///
+    /// Zones and doors of a top level zone are left without one.
+    pub fn remove(&mut self, id: ZoneId) -> Option<Zone> {
+        let zone = self.zones.remove(&id)?;
+        for inner in self.zones.values_mut() {
+            if inner.parent == Some(id) {
+                inner.parent = zone.parent;
+            }
+        }
+        match zone.parent {
+            Some(parent) => self
+                .doors
+                .values_mut()
+                .filter(|placed| **placed == id)
+                .for_each(|placed| *placed = parent),
+            None => self.doors.retain(|_, placed| *placed != id),
+        }
+        Some(zone)
+    }
+
+    #[inline]
+    pub fn get(&self, id: ZoneId) -> Option<&Zone> {
+        self.zones.get(&id)
+    }
+
+    /// An iterator over all the zones.
+    pub fn iter(&self) -> impl Iterator<Item = (ZoneId, &Zone)> + '_ {
+        self.zones.iter().map(|(id, zone)| (*id, zone))
+    }
+
+    /// Admit cards matching `grant` through a zone and all the zones within it.
+    pub fn grant(&mut self, id: ZoneId, grant: ZoneGrant) -> Result<(), ZoneError> {
+        let zone = self.zones.get_mut(&id).ok_or(ZoneError::UnknownZone(id))?;
+        if !zone.grants.contains(&grant) {
+            zone.grants.push(grant);
+        }
+        Ok(())
+    }
+
+    /// Take back a grant of a zone. Returns `false` if it wasn't granted there.
+    pub fn revoke(&mut self, id: ZoneId, grant: ZoneGrant) -> bool {
+        let Some(zone) = self.zones.get_mut(&id) else {
+            return false;
+        };
+        let len = zone.grants.len();
+        zone.grants.retain(|granted| *granted != grant);
+        zone.grants.len() != len
+    }
+
+    /// Place a door in a zone, returning the zone it was in before.
+    pub fn place(&mut self, door_id: DoorId, id: ZoneId) -> Result<Option<ZoneId>, ZoneError> {
+        if !self.zones.contains_key(&id) {
+            return Err(ZoneError::UnknownZone(id));
+        }
+        Ok(self.doors.insert(door_id, id))
+    }
+
+    /// Take a door out of its zone.
+    #[inline]
+    pub fn unplace(&mut self, door_id: DoorId) -> Option<ZoneId> {
+        self.doors.remove(&door_id)
+    }
+
+    /// The zone a door is placed in.
+    #[inline]
+    pub fn zone_of(&self, door_id: DoorId) -> Option<ZoneId> {
+        self.doors.get(&door_id).copied()
+    }
+
+    /// An iterator over a zone and the zones it's within, innermost first.
+    pub fn ancestors(&self, id: ZoneId) -> impl Iterator<Item = (ZoneId, &Zone)> + '_ {
+        let mut next = Some(id);
+        core::iter::from_fn(move || {
+            let id = next?;
+            let zone = self.zones.get(&id)?;
+            next = zone.parent;
+            Some((id, zone))
+        })
+    }
+
+    /// Check whether a zone the door is in, directly or not, admits a card with `perms`.
+    pub fn admits(&self, card: &Card, perms: Permissions, door_id: DoorId) -> bool {
+        self.zone_of(door_id)
+            .is_some_and(|id| self.admits_zone(card, perms, id))
+    }
+
+    /// Check whether a zone or one of the zones it's within admits a card with `perms`.
+    pub fn admits_zone(&self, card: &Card, perms: Permissions, id: ZoneId) -> bool {
+        self.ancestors(id)
+            .flat_map(|(_, zone)| zone.grants.iter())
+            .any(|grant| match *grant {
+                ZoneGrant::Role(role) => card.roles().contains(role),
+                ZoneGrant::Permissions(granted) => !granted.is_empty() && perms.contains(granted),
+            })
+    }
+}